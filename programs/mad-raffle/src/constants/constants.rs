@@ -1,41 +1,89 @@
 /// The public key of the native mint account on the Solana blockchain.
 pub const NATIVE_MINT: &str = "So11111111111111111111111111111111111111112";
 
-/// Fee Vault 
+// Constants marked as defaults only seed ProgramConfig at `initialize` or
+// `init_config`. The live values are in the config, set via `update_config`.
+
+/// Default fee vault.
 pub const FEE_VAULT: &str = "68zZq8P3An1z98askGjUeUPijnaHpvnYcZNVeiM2pTrz";
 
-/// This is the authority to initiatialize stuff.
+/// Authority allowed to run `initialize` and `init_config`, and the
+/// config's initial authority. Rotated in the config afterwards.
 pub const AUTHORITY: &str = "AUTHtStYmZz7G8KQz6R6FmussLgPrybNhHx4EZzQwFBF";
 // (DEV: AuthtWB95Cf3KaHh2gTsQLfKNtsGMgFg9BxgqbHjeLVy)
 
-/// The current version of the Raffle account.
 const _RENT_ADDITION: u64 = 1_120_560; // amount for + rent of 33 bytes
 
-/// The current ticket price.
+/// Default ticket price.
 pub const TICKET_PRICE: u64 = 670_000_000; // 0.67 SOL in lamports
 
-/// The current fee collected per ticket.
+/// Default ticket price in USD cents, used when the config's price mode is `UsdCents`.
+pub const USD_TICKET_PRICE: u64 = 1_000; // $10.00
+
+/// Default fee collected per ticket.
 pub const TICKET_FEE: u64 = 13_100_000; // 0.0131 SOL in lamports
 
-/// The current fee collected per ticket.
+/// Default super raffle fee collected per ticket.
 pub const SUPER_RAFFLE_FEE: u64 = 6_900_000; // 0.0069 SOL in lamports
 
-/// The  cost per new raffle (rounded up)
+/// Default cost per new raffle (rounded up).
 pub const NEW_RAFFLE_COST: u64 = 1_500_000;
 
-/// The maximum number of tickets that can be purchased per user.
+/// Default maximum number of tickets that can be purchased per user.
 pub const MAX_TICKETS_PER_USER: u8 = 50;
 
+/// The maximum number of NFTs listed to sell into a future pot at once.
+/// A lower ask evicts the highest one from a full book.
+pub const MAX_LISTINGS: usize = 32;
+
+/// The maximum number of pending work items a scheduler can be handed at once.
+pub const MAX_WORK_ITEMS: usize = 32;
+
+/// Default seconds a raffle can stay open before anyone can cancel it (0 disables).
+pub const MAX_RAFFLE_DURATION: i64 = 2_592_000; // 30 days
+
+/// Default seconds after creation a raffle stops selling tickets (0 is open ended).
+pub const RAFFLE_DURATION: i64 = 0;
+
+/// Default seconds after creation before a raffle can be ended.
+pub const MIN_RAFFLE_DURATION: i64 = 0;
+
+/// Default tickets a raffle must sell before it can be ended.
+pub const MIN_TICKETS: u32 = 1;
+
+/// Default prizes a raffle collects before it ends.
+pub const PRIZES_PER_RAFFLE: u8 = 1;
+
+/// The maximum number of prizes a raffle can collect.
+pub const MAX_PRIZES: usize = 5;
+
+/// Default seconds a winner has to claim a prize before it can be re-drawn (0 disables).
+pub const CLAIM_WINDOW: i64 = 2_592_000; // 30 days
+
+/// Seconds after a raffle ends before anyone can move a draw the operator
+/// never revealed onto public randomness.
+pub const REVEAL_TIMEOUT: i64 = 86_400; // 1 day
+
+/// Default lamports paid from a raffle to whoever draws or sends one of its prizes.
+pub const CRANK_REWARD: u64 = 500_000; // 0.0005 SOL
+
+/// The most `update_config` can set the crank reward to, so a bad update
+/// cannot hand each raffle's pot to whoever cranks it.
+pub const MAX_CRANK_REWARD: u64 = 10_000_000; // 0.01 SOL
+
 /// The number of points per ticket.
 pub const POINTS_PER_TICKET: u32 = 1;
 
 /// The number of points for selling.
 pub const POINTS_FOR_SELLING: u32 = 10;
 
-/// Collection Address
+/// Default collection address.
 pub const COLLECTION_ADDRESS: &str = "J1S9H3QjnRtBbbuD4HjPV6RpRhwuk4zKbxsnCHuTgh9w";
 // pub const COLLECTION_ADDRESS: &str = "CLxN2mQsewGLsTKw3gML1AWFQjrWpG6WgLYTLX9BdhRp"(dev) "J1S9H3QjnRtBbbuD4HjPV6RpRhwuk4zKbxsnCHuTgh9w"(prod);
 
-/// Price Feeds
+/// Default SOL/USD price feed.
 pub const SOL_PRICE_FEED: &str = "H6ARHf6YXhGYeQfUzQNGk6rDNnLBQKrenN712K4AQJEG";
-pub const STALENESS_THRESHOLD: u64 = 1; // staleness threshold in seconds
\ No newline at end of file
+/// Default price staleness threshold.
+pub const STALENESS_THRESHOLD: u64 = 1; // seconds
+/// Default widest accepted price confidence interval, in bps of the price.
+pub const MAX_PRICE_CONFIDENCE_BPS: u16 = 200; // 2%
\ No newline at end of file
@@ -5,4 +5,40 @@ pub const RAFFLE_SEED: [u8; 6] = *b"raffle";
 pub const TRACKER_SEED: [u8; 7] = *b"tracker";
 
 /// Anchor seeds for a Raffle PDA.
-pub const SUPER_RAFFLE_SEED: [u8; 5] = *b"skull";
\ No newline at end of file
+pub const SUPER_RAFFLE_SEED: [u8; 5] = *b"skull";
+
+/// Anchor seeds for the ProgramConfig PDA.
+pub const CONFIG_SEED: [u8; 6] = *b"config";
+
+/// Anchor seeds for the SuperDraw PDA.
+pub const SUPER_DRAW_SEED: [u8; 10] = *b"skull_draw";
+
+/// Anchor seeds for a SuperRaffleResult PDA (plus the draw id).
+pub const SUPER_RESULT_SEED: [u8; 12] = *b"skull_result";
+
+/// Anchor seeds for a TicketHolder PDA (plus the raffle id and user).
+pub const TICKET_HOLDER_SEED: [u8; 6] = *b"holder";
+
+/// Anchor seeds for a TicketReceipt PDA (plus the raffle id and receipt index).
+pub const TICKET_RECEIPT_SEED: [u8; 7] = *b"receipt";
+
+/// Anchor seeds for a UserStats PDA (plus the user).
+pub const USER_STATS_SEED: [u8; 10] = *b"user_stats";
+
+/// Anchor seeds for the points Season PDA.
+pub const SEASON_SEED: [u8; 6] = *b"season";
+
+/// Anchor seeds for a PaymentMint PDA (plus the mint).
+pub const PAYMENT_MINT_SEED: [u8; 12] = *b"payment_mint";
+
+/// Anchor seeds for a Listing PDA (plus the NFT mint).
+pub const LISTING_SEED: [u8; 7] = *b"listing";
+
+/// Anchor seeds for the ListingBook PDA.
+pub const LISTING_BOOK_SEED: [u8; 12] = *b"listing_book";
+
+/// Anchor seeds for a CollectionSettings PDA (plus the collection mint).
+pub const COLLECTION_SEED: [u8; 10] = *b"collection";
+
+/// Anchor seeds for the WorkQueue PDA.
+pub const WORK_QUEUE_SEED: [u8; 10] = *b"work_queue";
@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::CONFIG_SEED;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    /// The pending authority, checked in `ProgramConfig::accept_authority`
+    pub new_authority: Signer<'info>,
+}
+
+pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let new_authority = &ctx.accounts.new_authority;
+    ctx.accounts.config.accept_authority(new_authority.key)?;
+    msg!("Authority transferred to {}", new_authority.key());
+    Ok(())
+}
@@ -1,11 +1,15 @@
-use std::str::FromStr;
-
 use anchor_lang::{prelude::*, system_program};
 use solana_program::{system_instruction, pubkey::Pubkey};
 
-use crate::model::RaffleError;
-use crate::state::{Raffle, RaffleTracker, SuperVault};
-use crate::constants::{RAFFLE_SEED, TICKET_PRICE, TICKET_FEE, TRACKER_SEED, FEE_VAULT, MAX_TICKETS_PER_USER, SUPER_RAFFLE_FEE, POINTS_PER_TICKET, SUPER_RAFFLE_SEED};
+use crate::model::{RaffleError, FeedError, TicketPurchased};
+use crate::state::{
+    Raffle, RaffleTracker, SuperVault, ProgramConfig, TicketHolder, TicketReceipt, UserStats, Season,
+};
+use crate::constants::{
+    RAFFLE_SEED, TRACKER_SEED, POINTS_PER_TICKET, SUPER_RAFFLE_SEED, CONFIG_SEED,
+    TICKET_HOLDER_SEED, TICKET_RECEIPT_SEED, USER_STATS_SEED, SEASON_SEED,
+};
+use crate::utils::{load_price, usd_cents_to_lamports};
 
 #[derive(Accounts)]
 pub struct BuyTicket<'info> {
@@ -15,60 +19,108 @@ pub struct BuyTicket<'info> {
             RAFFLE_SEED.as_ref(),
             &(tracker.current_raffle).to_le_bytes(),  
         ],
-        bump = raffle.bump, 
-        realloc = Raffle::get_space((raffle.tickets.len() + 1 ) as usize),
-        realloc::payer = buyer,
-        realloc::zero = false
+        bump = raffle.bump
     )]
     pub raffle: Account<'info, Raffle>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = TicketHolder::get_space(),
+        seeds = [
+            TICKET_HOLDER_SEED.as_ref(),
+            &raffle.id.to_le_bytes(),
+            buyer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub ticket_holder: Account<'info, TicketHolder>,
+    #[account(
+        init,
+        payer = buyer,
+        space = TicketReceipt::get_space(),
+        seeds = [
+            TICKET_RECEIPT_SEED.as_ref(),
+            &raffle.id.to_le_bytes(),
+            &raffle.receipt_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub receipt: Account<'info, TicketReceipt>,
     #[account(mut)]
     pub buyer: Signer<'info>,
     #[account(
         mut, 
-        address = Pubkey::from_str(FEE_VAULT).unwrap() @ RaffleError::InvalidVault
+        address = config.fee_vault @ RaffleError::InvalidVault
     )]
     pub fee_vault: SystemAccount<'info>,
     #[account(address = system_program::ID)]
     pub system_program: Program<'info, System>,
+    #[account(seeds = [TRACKER_SEED.as_ref()], bump = tracker.bump)]
+    pub tracker: Account<'info, RaffleTracker>,
     #[account(
-        mut,
-        seeds = [TRACKER_SEED.as_ref()],
-        bump = tracker.bump,
-        realloc = RaffleTracker::get_space((tracker.scoreboard.len() + 1 ) as usize),
-        realloc::payer = buyer,
-        realloc::zero = false
+        init_if_needed,
+        payer = buyer,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), buyer.key().as_ref()],
+        bump
     )]
-    pub tracker: Account<'info, RaffleTracker>,
+    pub user_stats: Account<'info, UserStats>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Account<'info, Season>,
     #[account(mut, seeds = [SUPER_RAFFLE_SEED.as_ref()], bump=super_vault.bump)]
     pub super_vault: Account<'info, SuperVault>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// Required when the raffle is priced in USD (`raffle.usd_ticket_price`)
+    /// CHECK: Pyth feed, address checked and parsed by `load_price`
+    #[account(address = config.sol_price_feed @ FeedError::InvalidPriceFeed)]
+    pub sol_price_feed: Option<UncheckedAccount<'info>>,
 }
 
-pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
+pub fn buy_ticket(ctx: Context<BuyTicket>, quantity: u8) -> Result<()> {
     let raffle = &mut ctx.accounts.raffle;
+    let ticket_holder = &mut ctx.accounts.ticket_holder;
+    let receipt = &mut ctx.accounts.receipt;
     let buyer = &ctx.accounts.buyer;
     let fee_vault = &ctx.accounts.fee_vault;
-    let tracker = &mut ctx.accounts.tracker;
+    let tracker = &ctx.accounts.tracker;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let season = &mut ctx.accounts.season;
     let super_vault = &mut ctx.accounts.super_vault;
+    let config = &ctx.accounts.config;
 
     require!(raffle.active, RaffleError::NotActive);
+    require!(!raffle.is_past_end_time(Clock::get()?.unix_timestamp), RaffleError::RaffleClosed);
+    require!(quantity > 0, RaffleError::InvalidTicketQuantity);
 
-    
-    if let Some(ticket_holder) = raffle
-        .tickets
-        .iter()
-        .find(|ticket_holder| ticket_holder.user == *buyer.key)
-    {
-        require!(
-            ticket_holder.qty < MAX_TICKETS_PER_USER,
-            RaffleError::MaxTicketsPerUserExceeded
-        );
-    }
+    require!(
+        ticket_holder.qty as u16 + quantity as u16 <= config.max_tickets_per_user as u16,
+        RaffleError::MaxTicketsPerUserExceeded
+    );
+
+    let quantity_u64 = quantity as u64;
+    let ticket_price = match raffle.usd_ticket_price {
+        Some(usd_ticket_price) => {
+            let sol_price_feed = ctx.accounts.sol_price_feed.as_ref().ok_or(FeedError::InvalidPriceFeed)?;
+            let sol_price = load_price(
+                sol_price_feed,
+                Clock::get()?.unix_timestamp,
+                config.staleness_threshold,
+                config.max_confidence_bps,
+            )?;
+            let cents = usd_ticket_price.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+            usd_cents_to_lamports(cents, &sol_price)?
+        }
+        None => raffle.ticket_price.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?,
+    };
+    let ticket_fee = config.ticket_fee.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+    let super_raffle_fee = config.super_raffle_fee.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
     
     // Transfer funds to the Raffle Pool
     let transfer_instruction = system_instruction::transfer(
             buyer.key, 
-            &raffle.to_account_info().key, 
-            TICKET_PRICE
+            raffle.to_account_info().key, 
+            ticket_price
     );
 
     anchor_lang::solana_program::program::invoke_signed(
@@ -84,8 +136,8 @@ pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
     // Transfer funds to the Fee Vault
     let fee_transfer_instruction = system_instruction::transfer(
         buyer.key, 
-        &fee_vault.to_account_info().key, 
-        TICKET_FEE
+        fee_vault.to_account_info().key, 
+        ticket_fee
     );
 
     anchor_lang::solana_program::program::invoke_signed(
@@ -101,8 +153,8 @@ pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
     // Transfer funds to the Super Raffle
     let fee_transfer_instruction = system_instruction::transfer(
         buyer.key,
-        &super_vault.to_account_info().key, 
-        SUPER_RAFFLE_FEE
+        super_vault.to_account_info().key, 
+        super_raffle_fee
     );
 
     anchor_lang::solana_program::program::invoke_signed(
@@ -115,9 +167,32 @@ pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
         &[],
     )?;
 
-    raffle.buy_ticket(&buyer.key);
-    tracker.add_points(&buyer.key, POINTS_PER_TICKET);
+    let start = raffle.buy_ticket(quantity, ticket_holder.qty == 0)?;
+    ticket_holder.add_tickets(raffle.id, *ctx.bumps.get("ticket_holder").unwrap(), buyer.key, quantity);
+    ticket_holder.add_payment(ticket_price, 0)?;
+    receipt.set_inner(TicketReceipt {
+        raffle_id: raffle.id,
+        bump: *ctx.bumps.get("receipt").unwrap(),
+        user: *buyer.key,
+        start,
+        qty: quantity,
+    });
+    user_stats.initialize(buyer.key, *ctx.bumps.get("user_stats").unwrap());
+    user_stats.lifetime_tickets += quantity as u32;
+    tracker.add_points(season, user_stats, POINTS_PER_TICKET * quantity as u32);
 
-    msg!("{} bought a raffle ticket to raffle# {}", buyer.key(), raffle.id);
+    msg!("{} bought {} raffle ticket(s) to raffle# {}", buyer.key(), quantity, raffle.id);
+    emit!(TicketPurchased {
+        raffle_id: raffle.id,
+        buyer: buyer.key(),
+        receipt: receipt.key(),
+        quantity,
+        start,
+        payment_mint: None,
+        ticket_price,
+        ticket_fee,
+        super_raffle_fee,
+        total_tickets: raffle.get_ticket_count(),
+    });
     Ok(())
 }
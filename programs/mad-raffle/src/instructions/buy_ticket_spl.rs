@@ -0,0 +1,241 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token::{self, Mint, Token, TokenAccount, Transfer},
+};
+use solana_program::{system_instruction, pubkey::Pubkey};
+
+use crate::model::{RaffleError, PaymentError, FeedError, TicketPurchased};
+use crate::state::{
+    Raffle, RaffleTracker, SuperVault, ProgramConfig, TicketHolder, TicketReceipt, UserStats, Season,
+    PaymentMint,
+};
+use crate::constants::{
+    RAFFLE_SEED, TRACKER_SEED, POINTS_PER_TICKET, SUPER_RAFFLE_SEED, CONFIG_SEED,
+    TICKET_HOLDER_SEED, TICKET_RECEIPT_SEED, USER_STATS_SEED, SEASON_SEED, PAYMENT_MINT_SEED,
+};
+use crate::utils::{load_price, lamports_to_tokens, usd_cents_to_tokens};
+
+#[derive(Accounts)]
+pub struct BuyTicketSpl<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = TicketHolder::get_space(),
+        seeds = [
+            TICKET_HOLDER_SEED.as_ref(),
+            &raffle.id.to_le_bytes(),
+            buyer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub ticket_holder: Box<Account<'info, TicketHolder>>,
+    #[account(
+        init,
+        payer = buyer,
+        space = TicketReceipt::get_space(),
+        seeds = [
+            TICKET_RECEIPT_SEED.as_ref(),
+            &raffle.id.to_le_bytes(),
+            &raffle.receipt_count.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub receipt: Box<Account<'info, TicketReceipt>>,
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub payment_mint: Box<Account<'info, Mint>>,
+    #[account(
+        seeds = [PAYMENT_MINT_SEED.as_ref(), payment_mint.key().as_ref()],
+        bump = payment_config.bump,
+        constraint = payment_config.enabled @ PaymentError::MintNotWhitelisted
+    )]
+    pub payment_config: Box<Account<'info, PaymentMint>>,
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = buyer,
+    )]
+    pub buyer_token_account: Box<Account<'info, TokenAccount>>,
+    /// Raffle owned escrow, paid out by `end_raffle`
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = raffle,
+    )]
+    pub pot_escrow: Box<Account<'info, TokenAccount>>,
+    /// CHECK: Pyth feed, address checked and parsed by `load_price`
+    #[account(address = payment_config.price_feed @ FeedError::InvalidPriceFeed)]
+    pub token_price_feed: UncheckedAccount<'info>,
+    /// CHECK: Pyth feed, address checked and parsed by `load_price`
+    #[account(address = config.sol_price_feed @ FeedError::InvalidPriceFeed)]
+    pub sol_price_feed: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        address = config.fee_vault @ RaffleError::InvalidVault
+    )]
+    pub fee_vault: SystemAccount<'info>,
+    #[account(seeds = [TRACKER_SEED.as_ref()], bump = tracker.bump)]
+    pub tracker: Box<Account<'info, RaffleTracker>>,
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Box<Account<'info, UserStats>>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(mut, seeds = [SUPER_RAFFLE_SEED.as_ref()], bump=super_vault.bump)]
+    pub super_vault: Box<Account<'info, SuperVault>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Same as `buy_ticket`, but the pot share is paid in a whitelisted SPL
+/// mint at its Pyth price. Fees are still charged in lamports.
+/// The SOL feed is only read for raffles priced in lamports.
+pub fn buy_ticket_spl(ctx: Context<BuyTicketSpl>, quantity: u8) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let ticket_holder = &mut ctx.accounts.ticket_holder;
+    let receipt = &mut ctx.accounts.receipt;
+    let buyer = &ctx.accounts.buyer;
+    let fee_vault = &ctx.accounts.fee_vault;
+    let tracker = &ctx.accounts.tracker;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let season = &mut ctx.accounts.season;
+    let super_vault = &mut ctx.accounts.super_vault;
+    let config = &ctx.accounts.config;
+    let payment_mint = &ctx.accounts.payment_mint;
+
+    require!(raffle.active, RaffleError::NotActive);
+    let now = Clock::get()?.unix_timestamp;
+    require!(!raffle.is_past_end_time(now), RaffleError::RaffleClosed);
+    require!(quantity > 0, RaffleError::InvalidTicketQuantity);
+
+    require!(
+        ticket_holder.qty as u16 + quantity as u16 <= config.max_tickets_per_user as u16,
+        RaffleError::MaxTicketsPerUserExceeded
+    );
+    raffle.accept_payment_mint(payment_mint.key())?;
+
+    let quantity_u64 = quantity as u64;
+    let ticket_fee = config.ticket_fee.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+    let super_raffle_fee = config.super_raffle_fee.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+
+    // Price the tickets in the payment mint
+    let token_price = load_price(
+        &ctx.accounts.token_price_feed,
+        now,
+        config.staleness_threshold,
+        config.max_confidence_bps,
+    )?;
+    let token_amount = match raffle.usd_ticket_price {
+        Some(usd_ticket_price) => {
+            let cents = usd_ticket_price.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+            usd_cents_to_tokens(cents, &token_price, payment_mint.decimals)?
+        }
+        None => {
+            let ticket_price = raffle.ticket_price.checked_mul(quantity_u64).ok_or(RaffleError::Overflow)?;
+            let sol_price = load_price(
+                &ctx.accounts.sol_price_feed,
+                now,
+                config.staleness_threshold,
+                config.max_confidence_bps,
+            )?;
+            lamports_to_tokens(ticket_price, &sol_price, &token_price, payment_mint.decimals)?
+        }
+    };
+
+    // Transfer tokens to the Raffle escrow
+    let transfer_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.buyer_token_account.to_account_info(),
+            to: ctx.accounts.pot_escrow.to_account_info(),
+            authority: buyer.to_account_info(),
+        },
+    );
+    token::transfer(transfer_context, token_amount)?;
+
+    // Transfer funds to the Fee Vault
+    let fee_transfer_instruction = system_instruction::transfer(
+        buyer.key,
+        fee_vault.to_account_info().key,
+        ticket_fee
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &fee_transfer_instruction,
+        &[
+            buyer.to_account_info(),
+            fee_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[],
+    )?;
+
+    // Transfer funds to the Super Raffle
+    let fee_transfer_instruction = system_instruction::transfer(
+        buyer.key,
+        super_vault.to_account_info().key,
+        super_raffle_fee
+    );
+
+    anchor_lang::solana_program::program::invoke_signed(
+        &fee_transfer_instruction,
+        &[
+            buyer.to_account_info(),
+            super_vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+        &[],
+    )?;
+
+    let start = raffle.buy_ticket(quantity, ticket_holder.qty == 0)?;
+    ticket_holder.add_tickets(raffle.id, *ctx.bumps.get("ticket_holder").unwrap(), buyer.key, quantity);
+    ticket_holder.add_payment(0, token_amount)?;
+    receipt.set_inner(TicketReceipt {
+        raffle_id: raffle.id,
+        bump: *ctx.bumps.get("receipt").unwrap(),
+        user: *buyer.key,
+        start,
+        qty: quantity,
+    });
+    user_stats.initialize(buyer.key, *ctx.bumps.get("user_stats").unwrap());
+    user_stats.lifetime_tickets += quantity as u32;
+    tracker.add_points(season, user_stats, POINTS_PER_TICKET * quantity as u32);
+
+    msg!(
+        "{} bought {} raffle ticket(s) to raffle# {} for {} of {}",
+        buyer.key(), quantity, raffle.id, token_amount, payment_mint.key()
+    );
+    emit!(TicketPurchased {
+        raffle_id: raffle.id,
+        buyer: buyer.key(),
+        receipt: receipt.key(),
+        quantity,
+        start,
+        payment_mint: Some(payment_mint.key()),
+        ticket_price: token_amount,
+        ticket_fee,
+        super_raffle_fee,
+        total_tickets: raffle.get_ticket_count(),
+    });
+    Ok(())
+}
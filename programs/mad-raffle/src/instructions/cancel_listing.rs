@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{LISTING_SEED, LISTING_BOOK_SEED};
+// ProgNftShared (and its generated client modules) lives in end_raffle
+use crate::instructions::end_raffle::*;
+use crate::model::{RaffleError, ListingCancelled};
+use crate::state::{Listing, ListingBook};
+use crate::utils::{send_nft, AuthorizationDataLocal};
+
+#[derive(Accounts)]
+pub struct CancelListing<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &listing.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub escrow: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the seller's associated token account, created by `send_nft`
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &seller.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub dest: UncheckedAccount<'info>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED.as_ref(), nft_mint.key().as_ref()],
+        bump = listing.bump,
+        has_one = seller @ RaffleError::UnauthorizedSigner,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(mut, seeds = [LISTING_BOOK_SEED.as_ref()], bump = listing_book.bump)]
+    pub listing_book: Box<Account<'info, ListingBook>>,
+
+    /// Token program of `nft_mint`, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // pfnt
+    /// CHECK: assert_decode_metadata + seeds below
+    #[account(
+        mut,
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+    /// CHECK: seeds below
+    #[account(
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            mpl_token_metadata::state::EDITION.as_bytes(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump
+    )]
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                escrow.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub owner_token_record: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                dest.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub dest_token_record: UncheckedAccount<'info>,
+    pub pnft_shared: ProgNftShared<'info>,
+    //
+    // remaining accounts could be passed, in this order:
+    // - rules account
+}
+
+/// Returns a listed NFT to its seller and drops it from the listing book.
+pub fn cancel_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, CancelListing<'info>>,
+    authorization_data: Option<AuthorizationDataLocal>,
+    rules_acc_present: bool,
+) -> Result<()> {
+    let nft_mint = ctx.accounts.nft_mint.key();
+    let bump = [ctx.accounts.listing.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[LISTING_SEED.as_ref(), nft_mint.as_ref(), &bump]];
+
+    let rem_acc = &mut ctx.remaining_accounts.iter();
+    let auth_rules = if rules_acc_present {
+        Some(next_account_info(rem_acc)?)
+    } else {
+        None
+    };
+    send_nft(
+        &ctx.accounts.listing.to_account_info(),
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.escrow,
+        &ctx.accounts.dest,
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.edition,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.pnft_shared.instructions,
+        &ctx.accounts.owner_token_record,
+        &ctx.accounts.dest_token_record,
+        &ctx.accounts.pnft_shared.authorization_rules_program,
+        auth_rules,
+        authorization_data,
+        Some(signer_seeds),
+    )?;
+    ctx.accounts.listing_book.remove(&nft_mint);
+
+    let seller = ctx.accounts.seller.key();
+    msg!("{} cancelled the listing of {}", seller, nft_mint);
+    emit!(ListingCancelled { seller, nft_mint });
+    Ok(())
+}
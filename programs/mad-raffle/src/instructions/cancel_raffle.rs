@@ -0,0 +1,97 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, RaffleTracker, ProgramConfig};
+use crate::utils::carry_over_pot;
+
+#[derive(Accounts)]
+pub struct CancelRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Already created if a prize was sold
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = Raffle::get_space(),
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle + 1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump
+    )]
+    pub tracker: Account<'info, RaffleTracker>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// The authority can cancel at any time, anyone else only once
+    /// the raffle has been open longer than `config.max_raffle_duration`
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+    /// Required when a raffle with prizes escrowed SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    /// The new raffle's token account for the same mint, created beforehand
+    #[account(mut)]
+    pub new_pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    /// Required along with `pot_escrow`
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn cancel_raffle(ctx: Context<CancelRaffle>) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let new_raffle = &mut ctx.accounts.new_raffle;
+    let tracker = &mut ctx.accounts.tracker;
+    let config = &ctx.accounts.config;
+
+    require!(raffle.active, RaffleError::NotActive);
+    let clock = Clock::get()?;
+    require!(
+        ctx.accounts.signer.key() == config.authority
+            || raffle.is_expired(config.max_raffle_duration, clock.unix_timestamp),
+        RaffleError::UnauthorizedSigner
+    );
+
+    // Increment the raffle counter and initialize the new raffle, before any pot carries over
+    tracker.increment();
+    msg!("New raffle to be created: {}", tracker.current_raffle);
+    new_raffle.initialize(
+        tracker.current_raffle,
+        *ctx.bumps.get("new_raffle").unwrap(),
+        config,
+    )?;
+
+    if !raffle.prizes.is_empty() {
+        // Sold prizes were paid for, so they are drawn rather than refunded
+        raffle.end_with_prizes(clock.unix_timestamp, clock.slot)?;
+        let carried = carry_over_pot(
+            raffle,
+            new_raffle,
+            config,
+            ctx.accounts.pot_escrow.as_deref(),
+            ctx.accounts.new_pot_escrow.as_deref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+        msg!("Raffle# {} ended with {} prizes, {} lamports carried over", raffle.id, raffle.prize_count, carried);
+    } else {
+        // Ticket SOL stays in the raffle account until each holder claims a refund
+        raffle.cancel()?;
+        msg!("Raffle# {} cancelled", raffle.id);
+    }
+    Ok(())
+}
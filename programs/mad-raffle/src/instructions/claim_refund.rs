@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, Token, TokenAccount, Transfer},
+};
+
+use crate::constants::{RAFFLE_SEED, TICKET_HOLDER_SEED};
+use crate::model::{RaffleError, PaymentError};
+use crate::state::{Raffle, TicketHolder};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct ClaimRefund<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        constraint = raffle.id == raffle_id,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    // Closed to the user, returning its rent along with the refund
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            TICKET_HOLDER_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+            user.key().as_ref(),
+        ],
+        bump = ticket_holder.bump
+    )]
+    pub ticket_holder: Account<'info, TicketHolder>,
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// Required when the holder paid in SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Account<'info, TokenAccount>>,
+    #[account(mut)]
+    pub user_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+pub fn claim_refund(ctx: Context<ClaimRefund>) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let ticket_holder = &mut ctx.accounts.ticket_holder;
+    let user = &ctx.accounts.user;
+
+    require!(raffle.cancelled, RaffleError::NotCancelled);
+    let (refund, token_refund) = ticket_holder.refund()?;
+
+    // Raffle account size is unchanged, so its rent stays covered
+    **raffle.to_account_info().try_borrow_mut_lamports()? -= refund;
+    **user.to_account_info().try_borrow_mut_lamports()? += refund;
+
+    if token_refund > 0 {
+        let (Some(payment_mint), Some(pot_escrow), Some(user_token_account), Some(token_program)) = (
+            raffle.payment_mint,
+            ctx.accounts.pot_escrow.as_ref(),
+            ctx.accounts.user_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) else {
+            return err!(PaymentError::InvalidPaymentAccounts);
+        };
+        require_keys_eq!(
+            pot_escrow.key(),
+            get_associated_token_address(&raffle.key(), &payment_mint),
+            PaymentError::InvalidPaymentAccounts
+        );
+        require!(
+            user_token_account.mint == payment_mint && user_token_account.owner == user.key(),
+            PaymentError::InvalidPaymentAccounts
+        );
+        let raffle_id_bytes = raffle.id.to_le_bytes();
+        let bump = [raffle.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[RAFFLE_SEED.as_ref(), &raffle_id_bytes, &bump]];
+        let transfer_context = CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: pot_escrow.to_account_info(),
+                to: user_token_account.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_context, token_refund)?;
+        msg!("Refunded {} of {} to {}", token_refund, payment_mint, user.key());
+    }
+
+    msg!("Refunded {} lamports to {} from raffle# {}", refund, user.key(), raffle.id);
+    Ok(())
+}
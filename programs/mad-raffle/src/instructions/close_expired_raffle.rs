@@ -0,0 +1,96 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, RaffleTracker, ProgramConfig};
+use crate::utils::carry_over_pot;
+
+#[derive(Accounts)]
+pub struct CloseExpiredRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Already created if a prize was sold
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = Raffle::get_space(),
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle + 1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump
+    )]
+    pub tracker: Account<'info, RaffleTracker>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// Anyone can close a raffle once its end time has passed
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+    /// Required when a raffle with prizes escrowed SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    /// The new raffle's token account for the same mint, created beforehand
+    #[account(mut)]
+    pub new_pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    /// Required along with `pot_escrow`
+    pub token_program: Option<Program<'info, Token>>,
+}
+
+/// Closes a raffle past its end time. If NFTs were sold into it, it ends with
+/// those prizes and the rest of the pot goes to the next raffle. Otherwise it
+/// simply closes with no tickets, or is cancelled so buyers can `claim_refund`.
+pub fn close_expired_raffle(ctx: Context<CloseExpiredRaffle>) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let new_raffle = &mut ctx.accounts.new_raffle;
+    let tracker = &mut ctx.accounts.tracker;
+    let config = &ctx.accounts.config;
+
+    let clock = Clock::get()?;
+    require!(raffle.active, RaffleError::NotActive);
+    require!(raffle.is_past_end_time(clock.unix_timestamp), RaffleError::NotExpired);
+
+    // Increment the raffle counter and initialize the new raffle, before any pot carries over
+    tracker.increment();
+    msg!("New raffle to be created: {}", tracker.current_raffle);
+    new_raffle.initialize(
+        tracker.current_raffle,
+        *ctx.bumps.get("new_raffle").unwrap(),
+        config,
+    )?;
+
+    if !raffle.prizes.is_empty() {
+        raffle.end_with_prizes(clock.unix_timestamp, clock.slot)?;
+        let carried = carry_over_pot(
+            raffle,
+            new_raffle,
+            config,
+            ctx.accounts.pot_escrow.as_deref(),
+            ctx.accounts.new_pot_escrow.as_deref(),
+            ctx.accounts.token_program.as_ref(),
+        )?;
+        msg!("Raffle# {} expired with {} prizes, {} lamports carried over", raffle.id, raffle.prize_count, carried);
+    } else if raffle.get_ticket_count() == 0 {
+        raffle.close_without_prize()?;
+        msg!("Raffle# {} closed with no tickets", raffle.id);
+    } else {
+        raffle.cancel()?;
+        msg!("Raffle# {} expired, tickets can be refunded", raffle.id);
+    }
+    Ok(())
+}
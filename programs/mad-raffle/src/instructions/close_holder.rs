@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{RAFFLE_SEED, TICKET_HOLDER_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, TicketHolder};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct CloseHolder<'info> {
+    #[account(
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            TICKET_HOLDER_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+            user.key().as_ref(),
+        ],
+        bump = ticket_holder.bump
+    )]
+    pub ticket_holder: Account<'info, TicketHolder>,
+    /// CHECK: the buyer, receives the holder's rent
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+}
+
+/// Closes the ticket holder of `user` once every prize of the raffle is
+/// settled, returning its rent to them. Holders of a cancelled raffle are
+/// closed by `claim_refund` instead. Permissionless, like `close_receipt`.
+pub fn close_holder(ctx: Context<CloseHolder>) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    require!(raffle.draws_settled(), RaffleError::TicketsInUse);
+    msg!("Closed ticket holder of {} in raffle# {}", ctx.accounts.user.key(), raffle.id);
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{RAFFLE_SEED, TICKET_RECEIPT_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, TicketReceipt};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64, index: u32)]
+pub struct CloseReceipt<'info> {
+    #[account(
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            TICKET_RECEIPT_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+            &index.to_le_bytes(),
+        ],
+        bump = receipt.bump,
+        has_one = user @ RaffleError::UnauthorizedSigner,
+    )]
+    pub receipt: Account<'info, TicketReceipt>,
+    /// CHECK: the buyer, receives the receipt's rent
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+}
+
+/// Closes receipt `index` of a raffle no draw can read it in any more,
+/// returning its rent to the buyer. Permissionless, since the rent only
+/// goes to the buyer.
+pub fn close_receipt(ctx: Context<CloseReceipt>) -> Result<()> {
+    let raffle = &ctx.accounts.raffle;
+    require!(raffle.cancelled || raffle.draws_settled(), RaffleError::TicketsInUse);
+    msg!("Closed receipt of {} in raffle# {}", ctx.accounts.user.key(), raffle.id);
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, SEASON_SEED, SUPER_DRAW_SEED};
+use crate::model::{RaffleError, SuperRaffleError};
+use crate::state::{ProgramConfig, Season, SuperDraw};
+
+#[derive(Accounts)]
+pub struct CloseSuperRaffleEpoch<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SuperDraw::get_space(),
+        seeds = [SUPER_DRAW_SEED.as_ref()],
+        bump
+    )]
+    pub super_draw: Account<'info, SuperDraw>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Freezes the points of the next super raffle draw without committing it,
+/// so `tally_super_raffle` can take as many transactions as it needs before
+/// `commit_super_raffle` starts the ~512 slot reveal window. With
+/// `reset_season`, points earned from here on start a new season.
+pub fn close_super_raffle_epoch(ctx: Context<CloseSuperRaffleEpoch>, reset_season: bool) -> Result<()> {
+    let super_draw = &mut ctx.accounts.super_draw;
+    require!(!super_draw.epoch_closed, SuperRaffleError::DrawAlreadyCommitted);
+
+    super_draw.bump = *ctx.bumps.get("super_draw").unwrap();
+    super_draw.freeze_points(&mut ctx.accounts.season, reset_season);
+    msg!(
+        "Super raffle draw# {} frozen at {} points",
+        super_draw.draw_id(),
+        super_draw.total_points
+    );
+    Ok(())
+}
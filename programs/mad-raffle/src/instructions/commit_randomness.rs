@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, RaffleTracker, ProgramConfig};
+
+#[derive(Accounts)]
+pub struct CommitRandomness<'info> {
+    #[account(
+        mut, 
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),  
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump,
+    )]
+    pub tracker: Account<'info, RaffleTracker>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// Stores sha256(secret) on the current raffle. The secret is revealed
+/// after the raffle ends and mixed with a later slot hash to pick the winner.
+pub fn commit_randomness(ctx: Context<CommitRandomness>, commitment: [u8; 32]) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    require!(raffle.active, RaffleError::NotActive);
+    require!(!raffle.has_randomness_source(), RaffleError::RandomnessAlreadyCommitted);
+
+    raffle.commit_randomness(commitment);
+    msg!("Randomness committed for raffle# {}", raffle.id);
+    Ok(())
+}
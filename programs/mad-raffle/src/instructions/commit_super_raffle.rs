@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, SEASON_SEED, SUPER_DRAW_SEED};
+use crate::model::{RaffleError, SuperRaffleError};
+use crate::state::{ProgramConfig, Season, SuperDraw};
+
+#[derive(Accounts)]
+pub struct CommitSuperRaffle<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = SuperDraw::get_space(),
+        seeds = [SUPER_DRAW_SEED.as_ref()],
+        bump
+    )]
+    pub super_draw: Account<'info, SuperDraw>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Stores sha256(secret) for the next super raffle draw, same scheme as
+/// `commit_randomness` for regular raffles, and closes the points epoch so
+/// the draw's weights are frozen. With `reset_season`, points earned from
+/// here on start a new season. If the epoch is already closed by
+/// `close_super_raffle_epoch`, the tally is kept and `reset_season` is
+/// ignored. A draw whose reveal expired is recovered by
+/// `recommit_super_raffle`, not by committing again.
+pub fn commit_super_raffle(
+    ctx: Context<CommitSuperRaffle>,
+    commitment: [u8; 32],
+    reset_season: bool,
+) -> Result<()> {
+    let super_draw = &mut ctx.accounts.super_draw;
+    let season = &mut ctx.accounts.season;
+    require!(super_draw.commitment.is_none(), SuperRaffleError::DrawAlreadyCommitted);
+
+    let clock = Clock::get()?;
+    super_draw.bump = *ctx.bumps.get("super_draw").unwrap();
+    super_draw.freeze_points(season, reset_season);
+    super_draw.commit(commitment, clock.unix_timestamp, clock.slot)?;
+    msg!(
+        "Super raffle draw# {} committed over {} points",
+        super_draw.draw_id(),
+        super_draw.total_points
+    );
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+
+use crate::model::{RaffleError, OracleError, WinnerPicked};
+use crate::state::{Raffle, ProgramConfig, TicketReceipt, UserStats};
+use crate::constants::{RAFFLE_SEED, CONFIG_SEED, USER_STATS_SEED};
+use crate::utils::{pay_crank_reward, read_oracle_randomness};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct ConsumeRandomness<'info> {
+    #[account(
+        mut, 
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),  
+        ],
+        constraint = raffle.id == raffle_id,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Receipt holding the winning ticket
+    #[account(constraint = receipt.raffle_id == raffle_id @ RaffleError::WrongTicketReceipt)]
+    pub receipt: Account<'info, TicketReceipt>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_ref(), receipt.user.as_ref()],
+        bump = winner_stats.bump
+    )]
+    pub winner_stats: Account<'info, UserStats>,
+    /// CHECK: must match the oracle recorded on the raffle, verified by its provider
+    pub oracle: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// Paid `raffle.crank_reward`
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+/// Callback for an oracle request. Permissionless, since the outcome is fixed
+/// by the oracle's result. Test only, like `request_randomness`.
+pub fn consume_randomness(ctx: Context<ConsumeRandomness>) -> Result<()> {
+    require!(cfg!(feature = "mock-oracle"), OracleError::UnsupportedOracle);
+    let raffle = &mut ctx.accounts.raffle;
+    let oracle = &ctx.accounts.oracle;
+    let total_tickets: u32 = raffle.get_ticket_count();
+    require!(!raffle.active, RaffleError::StillActive);
+    require!(!raffle.cancelled, RaffleError::RaffleCancelled);
+    require!(total_tickets > 0, RaffleError::NoTickets);
+
+    let request = raffle.randomness_request.clone().ok_or(OracleError::NoRandomnessRequest)?;
+    require_keys_eq!(oracle.key(), request.oracle, OracleError::InvalidOracleAccount);
+
+    let randomness = read_oracle_randomness(request.provider, oracle, &raffle.key(), raffle.end_slot)?;
+    raffle.seed_draws(randomness)?;
+    let now = Clock::get()?.unix_timestamp;
+    // The first draw never repeats a winner, the others are made by `draw_winner`
+    if let Some((slot, winner)) = raffle.draw_winner(&ctx.accounts.receipt, now)? {
+        ctx.accounts.winner_stats.raffles_won += 1;
+        emit!(WinnerPicked {
+            raffle_id: raffle.id,
+            winner,
+            slot,
+            receipt: ctx.accounts.receipt.key(),
+            total_tickets,
+        });
+        pay_crank_reward(raffle, &ctx.accounts.cranker, &ctx.accounts.config)?;
+    }
+    Ok(())
+}
@@ -1,51 +1,60 @@
-use std::str::FromStr;
-
 use anchor_lang::prelude::*;
 use anchor_spl::metadata::MetadataAccount;
-use anchor_spl::token::{CloseAccount, self};
+use anchor_spl::token_2022::{self, CloseAccount};
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
-use crate::constants::{RAFFLE_SEED, COLLECTION_ADDRESS, AUTHORITY};
-use crate::model::{RaffleError, PnftError, PrizeError};
-use crate::state::Raffle;
-use crate::utils::{send_pnft, AuthorizationDataLocal};
+use crate::constants::{RAFFLE_SEED, CONFIG_SEED};
+use crate::model::{RaffleError, PrizeError, PrizeDistributed};
+use crate::state::{Raffle, ProgramConfig};
+// ProgNftShared (and its generated client modules) lives in end_raffle
+use crate::instructions::end_raffle::*;
+use crate::utils::{send_nft, pay_crank_reward, AuthorizationDataLocal};
 
 #[derive(Accounts)]
-#[instruction(raffle_id: u64)]
+#[instruction(raffle_id: u64, slot: u8)]
 pub struct DistributePrize<'info> {
+    /// Anyone, paid `raffle.crank_reward`. Pays for the winner's token account
+    /// and gets back the raffle's
     #[account(
         mut,
-        //address = Pubkey::from_str(AUTHORITY).unwrap() @ RaffleError::UnauthorizedSigner
     )]
     pub authority: Signer<'info>,
+    /// CHECK: the slot's winner, or the active raffle for a rolled over prize
     #[account(
         mut,
-        constraint = raffle.winner.is_some() @ RaffleError::WinnerNotSelected,  // Checks that a winner has been selected
-        address = raffle.winner.unwrap() @ PrizeError::InvalidWinner,           // Checks that the address is the winner
+        constraint = raffle.prizes.get(slot as usize).is_some_and(|s| s.winner.is_some() || s.rolled_over) @ RaffleError::WinnerNotSelected,  // Checks that a winner has been selected
+        constraint = raffle.prizes.get(slot as usize).is_some_and(|s| s.rolled_over || s.winner == Some(winner.key())) @ PrizeError::InvalidWinner, // Checks that the address is the winner
     )]
-    pub winner: SystemAccount<'info>,
+    pub winner: UncheckedAccount<'info>,
     #[account(
         mut,
-        associated_token::mint = nft_mint,
-        associated_token::authority = raffle,
+        address = get_associated_token_address_with_program_id(
+            &raffle.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
     )]
-    pub src: Box<Account<'info, TokenAccount>>,
+    pub src: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the winner's associated token account, created by `send_nft`
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = nft_mint,
-        associated_token::authority = winner,
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &winner.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
     )]
-    pub dest: Box<Account<'info, TokenAccount>>,
+    pub dest: UncheckedAccount<'info>,
     #[account(
-        constraint = raffle.prize.is_some() @ PrizeError::NoPrizeInRaffle,
-        address = *raffle.prize.as_ref().map(|p| &p.mint).unwrap() @ PrizeError::InvalidPrizeMint
+        constraint = raffle.prizes.get(slot as usize).is_some() @ PrizeError::NoPrizeInRaffle,
+        constraint = raffle.prizes.get(slot as usize).is_some_and(|s| s.prize.mint == nft_mint.key()) @ PrizeError::InvalidPrizeMint
     )]
-    pub nft_mint: Box<Account<'info, Mint>>,
-    pub token_program: Program<'info, Token>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// Token program of `nft_mint`, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -61,8 +70,6 @@ pub struct DistributePrize<'info> {
         ],
         seeds::program = mpl_token_metadata::id(),
         bump,
-        constraint = nft_metadata.collection.as_ref().unwrap().verified == true @ PnftError::NotVerifiedByCollection,
-        constraint = nft_metadata.collection.as_ref().unwrap().key == Pubkey::from_str(COLLECTION_ADDRESS).unwrap() @ PnftError::InvalidCollectionAddress    
     )]
     pub nft_metadata: Account<'info,MetadataAccount>,
     //note that MASTER EDITION and EDITION share the same seeds, and so it's valid to check them here
@@ -120,20 +127,31 @@ pub struct DistributePrize<'info> {
         constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
         constraint = !raffle.active @ RaffleError::StillActive,
     )]
-    pub raffle: Box<Account<'info, Raffle>>
+    pub raffle: Box<Account<'info, Raffle>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
 }
 
+/// Sends the prize of `slot` to its winner, or into the active raffle (passed
+/// as `winner`) once `redraw_unclaimed` rolled it over. Permissionless, since
+/// the prize can only go to the winner's associated token account.
 pub fn distribute_prize<'info>(
     ctx: Context<'_, '_, '_, 'info, DistributePrize<'info>>,
     _raffle_id: u64,
+    slot: u8,
     authorization_data: Option<AuthorizationDataLocal>,
     rules_acc_present: bool,
 ) -> Result<()> {
-    require!(
-        *ctx.accounts.authority.key == ctx.accounts.raffle.winner.unwrap() || 
-        *ctx.accounts.authority.key == Pubkey::from_str(AUTHORITY).unwrap(),
-        PrizeError::UnauthorizedDistributor
-    );
+    require!(!ctx.accounts.raffle.prizes[slot as usize].sent, PrizeError::PrizeAlreadySent);
+
+    let num_raffle_bytes = &(ctx.accounts.raffle.id).to_le_bytes();
+    let bump = &[ctx.accounts.raffle.bump];
+    // Should match raffle pda
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        RAFFLE_SEED.as_ref(),
+        num_raffle_bytes,
+        bump,
+    ]];
 
     let rem_acc = &mut ctx.remaining_accounts.iter();
     let auth_rules = if rules_acc_present {
@@ -141,7 +159,7 @@ pub fn distribute_prize<'info>(
     } else {
         None
     };
-    send_pnft(
+    send_nft(
         &ctx.accounts.raffle.to_account_info(),
         &ctx.accounts.authority.to_account_info(),
         &ctx.accounts.src,
@@ -159,23 +177,19 @@ pub fn distribute_prize<'info>(
         &ctx.accounts.pnft_shared.authorization_rules_program,
         auth_rules,
         authorization_data,
-        Some(&ctx.accounts.raffle)
+        Some(signer_seeds),
     )?;
 
-    if let Some(prize) = &mut ctx.accounts.raffle.prize {
-        prize.sent = true;
+    let prize_slot = &mut ctx.accounts.raffle.prizes[slot as usize];
+    prize_slot.sent = true;
+    if prize_slot.rolled_over {
+        let mut active_raffle = Account::<Raffle>::try_from(&ctx.accounts.winner)?;
+        active_raffle.add_rolled_over_prize(ctx.accounts.nft_mint.key(), ctx.accounts.dest.key())?;
+        active_raffle.exit(&crate::ID)?;
+        prize_slot.winner = Some(active_raffle.key());
     }
 
     // Close the Raffle's token account
-    let raffle = &mut ctx.accounts.raffle;
-    let num_raffle_bytes = &(raffle.id).to_le_bytes();
-    let bump = &[raffle.bump];
-    // Should match raffle pda
-    let signer_seeds: &[&[&[u8]]] = &[&[
-        RAFFLE_SEED.as_ref(),
-        num_raffle_bytes,
-        bump,
-    ]];
     let close_context = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
         CloseAccount {
@@ -184,26 +198,16 @@ pub fn distribute_prize<'info>(
             authority: ctx.accounts.raffle.to_account_info(),
         } 
     );
-    token::close_account(close_context.with_signer(signer_seeds))?;
+    token_2022::close_account(close_context.with_signer(signer_seeds))?;
+    pay_crank_reward(&ctx.accounts.raffle, &ctx.accounts.authority, &ctx.accounts.config)?;
 
+    emit!(PrizeDistributed {
+        raffle_id: ctx.accounts.raffle.id,
+        slot,
+        winner: ctx.accounts.winner.key(),
+        nft_mint: ctx.accounts.nft_mint.key(),
+        destination: ctx.accounts.dest.key(),
+        distributor: ctx.accounts.authority.key(),
+    });
     Ok(())
 }
-
-#[derive(Accounts)]
-pub struct ProgNftShared<'info> {
-    //can't deserialize directly coz Anchor traits not implemented
-    /// CHECK: address below
-    #[account(address = mpl_token_metadata::id())]
-    pub token_metadata_program: UncheckedAccount<'info>,
-
-    //sysvar ixs don't deserialize in anchor
-    /// CHECK: address below
-    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
-    pub instructions: UncheckedAccount<'info>,
-
-    /// CHECK: address below
-    #[account(address = mpl_token_auth_rules::id())]
-    pub authorization_rules_program: UncheckedAccount<'info>,
-}
-// --------------------------------------- replicating mplex type for anchor IDL export
-//have to do this because anchor won't include foreign structs in the IDL
\ No newline at end of file
@@ -0,0 +1,110 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{RAFFLE_SEED, CONFIG_SEED};
+use crate::model::{RaffleError, PrizeError, PrizeDistributed};
+use crate::state::{Raffle, ProgramConfig};
+// CnftShared (and its generated client modules) lives in end_raffle_cnft
+use crate::instructions::end_raffle_cnft::*;
+use crate::utils::{transfer_cnft, pay_crank_reward, CompressedAsset, account_compression, bubblegum};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64, slot: u8)]
+pub struct DistributePrizeCnft<'info> {
+    /// Anyone, paid `raffle.crank_reward`
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: the slot's winner, or the active raffle for a rolled over prize
+    #[account(
+        mut,
+        constraint = raffle.prizes.get(slot as usize).is_some_and(|s| s.winner.is_some() || s.rolled_over) @ RaffleError::WinnerNotSelected,  // Checks that a winner has been selected
+        constraint = raffle.prizes.get(slot as usize).is_some_and(|s| s.rolled_over || s.winner == Some(winner.key())) @ PrizeError::InvalidWinner, // Checks that the address is the winner
+    )]
+    pub winner: UncheckedAccount<'info>,
+    /// CHECK: the proof is checked against it by Bubblegum
+    #[account(mut, owner = account_compression::ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum::ID,
+        bump
+    )]
+    pub tree_authority: UncheckedAccount<'info>,
+    pub cnft_shared: CnftShared<'info>,
+    pub system_program: Program<'info, System>,
+    //
+    // remaining accounts are the proof nodes
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        bump = raffle.bump,
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+        constraint = !raffle.active @ RaffleError::StillActive,
+        constraint = raffle.prizes.get(slot as usize).is_some() @ PrizeError::NoPrizeInRaffle,
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
+}
+
+/// Same as `distribute_prize`, for a compressed NFT prize. `asset` must be the
+/// prize of `slot` as it is now, with the raffle as leaf owner and delegate.
+pub fn distribute_prize_cnft<'info>(
+    ctx: Context<'_, '_, '_, 'info, DistributePrizeCnft<'info>>,
+    _raffle_id: u64,
+    slot: u8,
+    asset: CompressedAsset,
+) -> Result<()> {
+    let prize_slot = &ctx.accounts.raffle.prizes[slot as usize];
+    let asset_id = asset.asset_id(ctx.accounts.merkle_tree.key);
+    require!(prize_slot.prize.asset_id == Some(asset_id), PrizeError::InvalidPrizeAsset);
+    require!(!prize_slot.sent, PrizeError::PrizeAlreadySent);
+
+    let num_raffle_bytes = &(ctx.accounts.raffle.id).to_le_bytes();
+    let bump = &[ctx.accounts.raffle.bump];
+    // Should match raffle pda
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        RAFFLE_SEED.as_ref(),
+        num_raffle_bytes,
+        bump,
+    ]];
+
+    let raffle_info = ctx.accounts.raffle.to_account_info();
+    transfer_cnft(
+        &ctx.accounts.tree_authority,
+        &raffle_info,
+        &raffle_info,
+        &ctx.accounts.winner.to_account_info(),
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.cnft_shared.log_wrapper,
+        &ctx.accounts.cnft_shared.compression_program,
+        &ctx.accounts.system_program,
+        &ctx.accounts.cnft_shared.bubblegum_program,
+        ctx.remaining_accounts,
+        &asset,
+        Some(signer_seeds),
+    )?;
+
+    let prize_slot = &mut ctx.accounts.raffle.prizes[slot as usize];
+    prize_slot.sent = true;
+    if prize_slot.rolled_over {
+        let mut active_raffle = Account::<Raffle>::try_from(&ctx.accounts.winner)?;
+        active_raffle.add_rolled_over_compressed_prize(asset_id)?;
+        active_raffle.exit(&crate::ID)?;
+        prize_slot.winner = Some(active_raffle.key());
+    }
+    pay_crank_reward(&ctx.accounts.raffle, &ctx.accounts.authority, &ctx.accounts.config)?;
+
+    emit!(PrizeDistributed {
+        raffle_id: ctx.accounts.raffle.id,
+        slot,
+        winner: ctx.accounts.winner.key(),
+        nft_mint: asset_id,
+        destination: ctx.accounts.winner.key(),
+        distributor: ctx.accounts.authority.key(),
+    });
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::constants::{SEASON_SEED, SUPER_DRAW_SEED, SUPER_RAFFLE_SEED, SUPER_RESULT_SEED, USER_STATS_SEED};
+use crate::model::{RaffleError, SuperRaffleError};
+use crate::state::{Season, SuperDraw, SuperRaffleResult, SuperVault, UserStats};
+use crate::utils::{commitment_for, hash_entropy, points_winner, slot_hash_after};
+
+#[derive(Accounts)]
+pub struct DrawSuperRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [SUPER_DRAW_SEED.as_ref()],
+        bump = super_draw.bump
+    )]
+    pub super_draw: Account<'info, SuperDraw>,
+    #[account(
+        init,
+        payer = cranker,
+        space = SuperRaffleResult::get_space(),
+        seeds = [
+            SUPER_RESULT_SEED.as_ref(),
+            &super_draw.draw_id().to_le_bytes(),
+        ],
+        bump
+    )]
+    pub result: Account<'info, SuperRaffleResult>,
+    #[account(mut, seeds = [SUPER_RAFFLE_SEED.as_ref()], bump = super_vault.bump)]
+    pub super_vault: Account<'info, SuperVault>,
+    #[account(seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    /// Anyone, see below
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    /// Checked against the drawn winner below
+    #[account(mut)]
+    pub winner: SystemAccount<'info>,
+    #[account(
+        seeds = [USER_STATS_SEED.as_ref(), winner.key().as_ref()],
+        bump = winner_stats.bump
+    )]
+    pub winner_stats: Account<'info, UserStats>,
+    /// CHECK: SlotHashes is too large to deserialize, read raw (address checked)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Pays the super vault balance (above rent) to a user, weighted by the
+/// points frozen at commit, and records the draw. Requires a complete
+/// `tally_super_raffle`. Like `reveal_and_pick_winner`, no signer is checked:
+/// only the authority knows its secret, and after `recommit_super_raffle`
+/// anyone can reveal `PUBLIC_REVEAL_SECRET`.
+pub fn draw_super_raffle(ctx: Context<DrawSuperRaffle>, secret: [u8; 32]) -> Result<()> {
+    let super_draw = &mut ctx.accounts.super_draw;
+    let super_vault = &ctx.accounts.super_vault;
+    let season = &ctx.accounts.season;
+    let winner = &ctx.accounts.winner;
+    let winner_stats = &ctx.accounts.winner_stats;
+    let result = &mut ctx.accounts.result;
+
+    let commitment = super_draw.commitment.ok_or(SuperRaffleError::DrawNotCommitted)?;
+    require!(commitment_for(&secret) == commitment, RaffleError::InvalidReveal);
+    require!(super_draw.is_tallied(), SuperRaffleError::TallyIncomplete);
+
+    // Mix in the first slot hash produced after the commitment
+    let slot_hash_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+    let slot_hash = slot_hash_after(&slot_hash_data, super_draw.commit_slot)?;
+    let draw_id = super_draw.draw_id();
+    let entropy = hash_entropy(&[&secret, &slot_hash, &draw_id.to_le_bytes()]);
+
+    let drawn = points_winner(super_draw.total_points, draw_id, season, winner_stats, entropy)?;
+
+    // Everything above rent goes to the winner
+    let rent_required = Rent::get()?.minimum_balance(SuperVault::get_space());
+    let vault_lamports = super_vault.to_account_info().lamports();
+    let amount = vault_lamports.saturating_sub(rent_required);
+    require!(amount > 0, SuperRaffleError::EmptyVault);
+    **super_vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+    **winner.to_account_info().try_borrow_mut_lamports()? += amount;
+
+    result.set_inner(SuperRaffleResult {
+        id: draw_id,
+        bump: *ctx.bumps.get("result").unwrap(),
+        winner: drawn,
+        amount,
+        winner_points: winner_stats.closed_points(season),
+        total_points: super_draw.total_points,
+        time: Clock::get()?.unix_timestamp,
+        season_reset: super_draw.reset_season,
+    });
+    super_draw.complete();
+
+    msg!("Super raffle draw# {} won by {} for {} lamports", draw_id, drawn, amount);
+    Ok(())
+}
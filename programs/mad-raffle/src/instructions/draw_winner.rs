@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+
+use crate::model::{RaffleError, WinnerPicked};
+use crate::state::{Raffle, ProgramConfig, TicketReceipt, UserStats};
+use crate::constants::{RAFFLE_SEED, CONFIG_SEED, USER_STATS_SEED};
+use crate::utils::pay_crank_reward;
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct DrawWinner<'info> {
+    #[account(
+        mut, 
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),  
+        ],
+        constraint = raffle.id == raffle_id,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Receipt holding the drawn ticket
+    #[account(constraint = receipt.raffle_id == raffle_id @ RaffleError::WrongTicketReceipt)]
+    pub receipt: Account<'info, TicketReceipt>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_ref(), receipt.user.as_ref()],
+        bump = winner_stats.bump
+    )]
+    pub winner_stats: Account<'info, UserStats>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// Paid `raffle.crank_reward` when a winner is drawn
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+}
+
+/// Draws the winner of the next prize of a multi-prize raffle once the first
+/// one was picked, including prizes re-drawn by `redraw_unclaimed` or rolled
+/// over from an earlier raffle. Permissionless, since the outcome is fixed by the seed the
+/// first pick stored. A ticket of a holder who already won is skipped, the
+/// next draw is then made with the next receipt.
+pub fn draw_winner(ctx: Context<DrawWinner>) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    require!(!raffle.active, RaffleError::StillActive);
+    require!(!raffle.cancelled, RaffleError::RaffleCancelled);
+
+    let now = Clock::get()?.unix_timestamp;
+    if let Some((slot, winner)) = raffle.draw_winner(&ctx.accounts.receipt, now)? {
+        ctx.accounts.winner_stats.raffles_won += 1;
+        emit!(WinnerPicked {
+            raffle_id: raffle.id,
+            winner,
+            slot,
+            receipt: ctx.accounts.receipt.key(),
+            total_tickets: raffle.get_ticket_count(),
+        });
+        pay_crank_reward(raffle, &ctx.accounts.cranker, &ctx.accounts.config)?;
+    }
+    Ok(())
+}
@@ -1,33 +1,36 @@
-use std::str::FromStr;
-
 use anchor_lang::prelude::*;
 use anchor_spl::metadata::MetadataAccount;
 use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::{Token, TokenAccount},
+    token_interface::{self, Mint, TokenInterface},
 };
 
-use crate::constants::{RAFFLE_SEED, TRACKER_SEED, COLLECTION_ADDRESS, NEW_RAFFLE_COST, POINTS_FOR_SELLING};
-use crate::model::{RaffleError, PnftError};
-use crate::state::{Raffle, RaffleTracker};
-use crate::utils::{send_pnft, AuthorizationDataLocal};
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED, USER_STATS_SEED, SEASON_SEED, LISTING_BOOK_SEED};
+use crate::model::{RaffleError, RaffleEnded, PrizeAdded};
+use crate::state::{Raffle, RaffleTracker, ProgramConfig, UserStats, Season, CollectionSettings, ListingBook};
+use crate::utils::{send_nft, pay_out_pot, prize_share, AuthorizationDataLocal};
 
 #[derive(Accounts)]
 pub struct EndRaffle<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     #[account(mut)]
-    pub src: Box<Account<'info, TokenAccount>>,
+    pub src: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// CHECK: the raffle's associated token account, created by `send_nft`
     #[account(
-        init,
-        payer = owner,
-        associated_token::mint = nft_mint,
-        associated_token::authority = raffle,
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &raffle.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
     )]
-    pub dest: Box<Account<'info, TokenAccount>>,
-    pub nft_mint: Box<Account<'info, Mint>>,
+    pub dest: UncheckedAccount<'info>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    pub token_program: Program<'info, Token>,
+    /// Token program of `nft_mint`, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -43,8 +46,6 @@ pub struct EndRaffle<'info> {
         ],
         seeds::program = mpl_token_metadata::id(),
         bump,
-        constraint = nft_metadata.collection.as_ref().unwrap().verified == true @ PnftError::NotVerifiedByCollection,
-        constraint = nft_metadata.collection.as_ref().unwrap().key == Pubkey::from_str(COLLECTION_ADDRESS).unwrap() @ PnftError::InvalidCollectionAddress    
     )]
     pub nft_metadata: Account<'info,MetadataAccount>,
     //note that MASTER EDITION and EDITION share the same seeds, and so it's valid to check them here
@@ -90,8 +91,7 @@ pub struct EndRaffle<'info> {
     //
     // remaining accounts could be passed, in this order:
     // - rules account
-    // - mint_whitelist_proof
-    // - creator_whitelist_proof
+    // - creator token accounts for `raffle.payment_mint`, any order
     #[account(
         mut, 
         seeds = [
@@ -101,10 +101,11 @@ pub struct EndRaffle<'info> {
         bump = raffle.bump
     )]
     pub raffle: Box<Account<'info, Raffle>>,
+    /// Created by the first sale, initialized once the raffle ends
     #[account(
-        init, 
+        init_if_needed,
         payer = owner, 
-        space = Raffle::get_space(0),
+        space = Raffle::get_space(),
         seeds = [
             RAFFLE_SEED.as_ref(),
             &(tracker.current_raffle + 1).to_le_bytes(),  
@@ -115,12 +116,21 @@ pub struct EndRaffle<'info> {
     #[account(
         mut,
         seeds = [TRACKER_SEED.as_ref()],
-        bump = tracker.bump,
-        realloc = RaffleTracker::get_space((tracker.scoreboard.len() + 1 ) as usize),
-        realloc::payer = owner,
-        realloc::zero = false
+        bump = tracker.bump
     )]
     pub tracker: Box<Account<'info, RaffleTracker>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
     #[account(mut)]
     pub creator1: Option<AccountInfo<'info>>,
     #[account(mut)]
@@ -131,13 +141,31 @@ pub struct EndRaffle<'info> {
     pub creator4: Option<AccountInfo<'info>>,
     #[account(mut)]
     pub creator5: Option<AccountInfo<'info>>,
+    /// Required when the raffle escrowed SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// Required along with `pot_escrow`
+    pub payment_token_program: Option<Program<'info, Token>>,
+    /// CHECK: the `[COLLECTION_SEED, collection]` PDA of the NFT's collection, even
+    /// before `set_collection` creates it, checked by `CollectionSettings`
+    pub collection_settings: UncheckedAccount<'info>,
+    /// CHECK: seeds below, may not exist yet, read by `ListingBook::require_none_met`
+    #[account(seeds = [LISTING_BOOK_SEED.as_ref()], bump)]
+    pub listing_book: UncheckedAccount<'info>,
 }
 
 
+/// `min_payment` is the fewest lamports the seller accepts, and
+/// `min_token_payment` the fewest tokens of an SPL paid raffle, so a pot that
+/// shrinks before the transaction lands fails it instead.
 pub fn end_raffle<'info>(
     ctx: Context<'_, '_, '_, 'info, EndRaffle<'info>>,
     authorization_data: Option<AuthorizationDataLocal>,
     rules_acc_present: bool,
+    min_payment: u64,
+    min_token_payment: u64,
 ) -> Result<()> {
     let raffle = &mut ctx.accounts.raffle;
     let seller = &mut ctx.accounts.owner;
@@ -146,9 +174,21 @@ pub fn end_raffle<'info>(
     let nft_mint = &ctx.accounts.nft_mint;
     let ata = &ctx.accounts.dest;
     // Verify raffle is active and has sold some tickets
-    let total_tickets: u32 = raffle.tickets.iter().map(|holder| holder.qty as u32).sum();
+    let total_tickets: u32 = raffle.get_ticket_count();
     require!(raffle.active, RaffleError::NotActive);
     require!(total_tickets > 0, RaffleError::NoTickets);
+    raffle.check_can_end(Clock::get()?.unix_timestamp)?;
+    require!(raffle.has_randomness_source(), RaffleError::RandomnessNotCommitted);
+    // Listed NFTs the pot can pay for are sold first, through `settle_listing`
+    ListingBook::require_none_met(
+        &ctx.accounts.listing_book,
+        prize_share(raffle, &ctx.accounts.config)?,
+    )?;
+    let collection = CollectionSettings::for_nft(
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.config,
+        &ctx.accounts.collection_settings,
+    )?;
 
     let rem_acc = &mut ctx.remaining_accounts.iter();
     let auth_rules = if rules_acc_present {
@@ -156,7 +196,7 @@ pub fn end_raffle<'info>(
     } else {
         None
     };
-    send_pnft(
+    send_nft(
         &seller.to_account_info(),
         &seller.to_account_info(),
         &ctx.accounts.src,
@@ -177,69 +217,68 @@ pub fn end_raffle<'info>(
         None,
     )?;
 
-    let metadata = &mut ctx.accounts.nft_metadata;
-    let empty_vec = vec![];
-    let creators = metadata.data.creators.as_ref().unwrap_or(&empty_vec);
-
-    // Calculate payment amount and royalty amount
-    // Leave enough lamports in the account to cover rent
-    let rent_required = Rent::get()?.minimum_balance(Raffle::get_space(raffle.tickets.len() + 1 as usize));
-    let current_raffle_lamports: u64 = raffle.to_account_info().lamports();
-    let available_balance: u64 = current_raffle_lamports.saturating_sub(rent_required + NEW_RAFFLE_COST);
-    let seller_fee_basis_points = metadata.data.seller_fee_basis_points as u64;
-    let total_rate: u64 = 10000; // BPS 100% rate
-    
-    let payment_to_seller = (available_balance * total_rate) / (total_rate + seller_fee_basis_points);
-    let royalties_payment: u64 = available_balance - payment_to_seller;
-
-    let creator_accounts = [
-        ctx.accounts.creator1.as_ref(),
-        ctx.accounts.creator2.as_ref(),
-        ctx.accounts.creator3.as_ref(),
-        ctx.accounts.creator4.as_ref(),
-        ctx.accounts.creator5.as_ref(),
-    ];
-    let mut royalties_paid = 0;
-    let min_creator_rent = Rent::get()?.minimum_balance(0);
-
-    for creator in creators.iter() {
-        // Find the account that matches the current creator
-        if let Some(creator_account) = creator_accounts.iter().find(|account| {
-            account.map_or(false, |account| account.key() == creator.address)
-        }) {
-            let creator_payment = (royalties_payment * creator.share as u64) / 100;
-    
-            // Create a let binding for the AccountInfo object
-            let creator_account_info = creator_account.unwrap().to_account_info();
-            let mut creator_lamports = creator_account_info.try_borrow_mut_lamports()?;
-            let creator_balance = **creator_lamports;
-    
-            // Don't send if the creator's balance is too low to pay rent (cause tx to fail)
-            if (creator_balance + creator_payment) > min_creator_rent {
-                royalties_paid += creator_payment;
-                **creator_lamports += creator_payment;
-            }
-        }
-    }
-    
-    // Raffle pays for the SOL to the NFT seller, royalties to creator
-    **raffle.to_account_info().try_borrow_mut_lamports()? -= payment_to_seller + royalties_paid;
-    **seller.to_account_info().try_borrow_mut_lamports()? += payment_to_seller;
+    let payout = pay_out_pot(
+        raffle,
+        &seller.to_account_info(),
+        nft_mint.key(),
+        ctx.accounts.nft_metadata.data.creators.as_deref().unwrap_or_default(),
+        collection.royalty_bps(ctx.accounts.nft_metadata.data.seller_fee_basis_points),
+        &ctx.accounts.config,
+        [
+            ctx.accounts.creator1.as_ref(),
+            ctx.accounts.creator2.as_ref(),
+            ctx.accounts.creator3.as_ref(),
+            ctx.accounts.creator4.as_ref(),
+            ctx.accounts.creator5.as_ref(),
+        ],
+        ctx.accounts.pot_escrow.as_deref(),
+        ctx.accounts.seller_token_account.as_deref(),
+        rem_acc.as_slice(),
+        ctx.accounts.payment_token_program.as_ref(),
+        min_payment,
+        min_token_payment,
+    )?;
 
     // Update raffle state
-    raffle.end_raffle(
-        nft_mint.key(),
-        ata.key()
-    );
-    // Increment the raffle counter and initialize the new raffle
-    tracker.increment();
-    msg!("New raffle to be created: {}", tracker.current_raffle);
-    new_raffle.initialize(
-        tracker.current_raffle,
-        *ctx.bumps.get("new_raffle").unwrap(),
-    );
+    let slot = raffle.prizes.len() as u8;
+    if raffle.add_prize(nft_mint.key(), ata.key())? {
+        // Increment the raffle counter and initialize the new raffle
+        tracker.increment();
+        msg!("New raffle to be created: {}", tracker.current_raffle);
+        new_raffle.initialize(
+            tracker.current_raffle,
+            *ctx.bumps.get("new_raffle").unwrap(),
+            &ctx.accounts.config,
+        )?;
+        emit!(RaffleEnded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: nft_mint.key(),
+            total_tickets,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            payment_mint: raffle.payment_mint,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+            next_raffle_id: tracker.current_raffle,
+        });
+    } else {
+        emit!(PrizeAdded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: nft_mint.key(),
+            slot,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+        });
+    }
     // Add bonus points to the seller
-    tracker.add_points(&seller.key, POINTS_FOR_SELLING);
+    let seller_stats = &mut ctx.accounts.seller_stats;
+    seller_stats.initialize(seller.key, *ctx.bumps.get("seller_stats").unwrap());
+    seller_stats.nfts_sold += 1;
+    tracker.add_points(&mut ctx.accounts.season, seller_stats, collection.points_for_selling);
 
     Ok(())
 }
@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED, USER_STATS_SEED, SEASON_SEED, LISTING_BOOK_SEED};
+use crate::model::{RaffleError, RaffleEnded, PrizeAdded};
+use crate::state::{Raffle, RaffleTracker, ProgramConfig, UserStats, Season, CollectionSettings, ListingBook};
+use crate::utils::{
+    transfer_cnft, pay_out_pot, prize_share, CompressedAsset, account_compression, bubblegum, noop,
+};
+
+#[derive(Accounts)]
+pub struct EndRaffleCnft<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// CHECK: the owner unless they delegated the leaf, hashed into the leaf by Bubblegum
+    pub leaf_delegate: UncheckedAccount<'info>,
+    /// CHECK: the proof is checked against it by Bubblegum
+    #[account(mut, owner = account_compression::ID)]
+    pub merkle_tree: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(
+        seeds = [merkle_tree.key().as_ref()],
+        seeds::program = bubblegum::ID,
+        bump
+    )]
+    pub tree_authority: UncheckedAccount<'info>,
+    pub cnft_shared: CnftShared<'info>,
+    pub system_program: Program<'info, System>,
+    //
+    // remaining accounts, in this order:
+    // - `proof_len` proof nodes
+    // - creator token accounts for `raffle.payment_mint`, any order
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+    /// Created by the first sale, initialized once the raffle ends
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Raffle::get_space(),
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle + 1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_raffle: Box<Account<'info, Raffle>>,
+    #[account(
+        mut,
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump
+    )]
+    pub tracker: Box<Account<'info, RaffleTracker>>,
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
+    #[account(mut)]
+    pub creator1: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator2: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator3: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator4: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator5: Option<AccountInfo<'info>>,
+    /// Required when the raffle escrowed SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// Required along with `pot_escrow`
+    pub payment_token_program: Option<Program<'info, Token>>,
+    /// CHECK: the `[COLLECTION_SEED, collection]` PDA of the NFT's collection, even
+    /// before `set_collection` creates it, checked by `CollectionSettings`
+    pub collection_settings: UncheckedAccount<'info>,
+    /// CHECK: seeds below, may not exist yet, read by `ListingBook::require_none_met`
+    #[account(seeds = [LISTING_BOOK_SEED.as_ref()], bump)]
+    pub listing_book: UncheckedAccount<'info>,
+}
+
+/// Same as `end_raffle`, selling the compressed NFT `asset` instead. The raffle
+/// becomes the leaf owner until the prize is distributed.
+pub fn end_raffle_cnft<'info>(
+    ctx: Context<'_, '_, '_, 'info, EndRaffleCnft<'info>>,
+    asset: CompressedAsset,
+    proof_len: u8,
+    min_payment: u64,
+    min_token_payment: u64,
+) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let seller = &mut ctx.accounts.owner;
+    let tracker = &mut ctx.accounts.tracker;
+    let new_raffle = &mut ctx.accounts.new_raffle;
+    // Verify raffle is active and has sold some tickets
+    let total_tickets: u32 = raffle.get_ticket_count();
+    require!(raffle.active, RaffleError::NotActive);
+    require!(total_tickets > 0, RaffleError::NoTickets);
+    raffle.check_can_end(Clock::get()?.unix_timestamp)?;
+    require!(raffle.has_randomness_source(), RaffleError::RandomnessNotCommitted);
+    // Listed NFTs the pot can pay for are sold first, through `settle_listing`
+    ListingBook::require_none_met(
+        &ctx.accounts.listing_book,
+        prize_share(raffle, &ctx.accounts.config)?,
+    )?;
+    // Trusted once Bubblegum accepts the transfer below, which hashes it into the leaf
+    let collection = CollectionSettings::for_compressed_nft(
+        &asset,
+        &ctx.accounts.config,
+        &ctx.accounts.collection_settings,
+    )?;
+
+    let proof_len = proof_len as usize;
+    require!(
+        ctx.remaining_accounts.len() >= proof_len,
+        ErrorCode::AccountNotEnoughKeys
+    );
+    let (proof, creator_token_accounts) = ctx.remaining_accounts.split_at(proof_len);
+    transfer_cnft(
+        &ctx.accounts.tree_authority,
+        &seller.to_account_info(),
+        &ctx.accounts.leaf_delegate,
+        &raffle.to_account_info(),
+        &ctx.accounts.merkle_tree,
+        &ctx.accounts.cnft_shared.log_wrapper,
+        &ctx.accounts.cnft_shared.compression_program,
+        &ctx.accounts.system_program,
+        &ctx.accounts.cnft_shared.bubblegum_program,
+        proof,
+        &asset,
+        None,
+    )?;
+
+    let asset_id = asset.asset_id(ctx.accounts.merkle_tree.key);
+    let payout = pay_out_pot(
+        raffle,
+        &seller.to_account_info(),
+        asset_id,
+        &asset.creators(),
+        collection.royalty_bps(asset.metadata.seller_fee_basis_points),
+        &ctx.accounts.config,
+        [
+            ctx.accounts.creator1.as_ref(),
+            ctx.accounts.creator2.as_ref(),
+            ctx.accounts.creator3.as_ref(),
+            ctx.accounts.creator4.as_ref(),
+            ctx.accounts.creator5.as_ref(),
+        ],
+        ctx.accounts.pot_escrow.as_deref(),
+        ctx.accounts.seller_token_account.as_deref(),
+        creator_token_accounts,
+        ctx.accounts.payment_token_program.as_ref(),
+        min_payment,
+        min_token_payment,
+    )?;
+
+    // Update raffle state
+    let slot = raffle.prizes.len() as u8;
+    if raffle.add_compressed_prize(asset_id)? {
+        // Increment the raffle counter and initialize the new raffle
+        tracker.increment();
+        msg!("New raffle to be created: {}", tracker.current_raffle);
+        new_raffle.initialize(
+            tracker.current_raffle,
+            *ctx.bumps.get("new_raffle").unwrap(),
+            &ctx.accounts.config,
+        )?;
+        emit!(RaffleEnded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: asset_id,
+            total_tickets,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            payment_mint: raffle.payment_mint,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+            next_raffle_id: tracker.current_raffle,
+        });
+    } else {
+        emit!(PrizeAdded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: asset_id,
+            slot,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+        });
+    }
+    // Add bonus points to the seller
+    let seller_stats = &mut ctx.accounts.seller_stats;
+    seller_stats.initialize(seller.key, *ctx.bumps.get("seller_stats").unwrap());
+    seller_stats.nfts_sold += 1;
+    tracker.add_points(&mut ctx.accounts.season, seller_stats, collection.points_for_selling);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CnftShared<'info> {
+    /// CHECK: address below
+    #[account(address = bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+
+    /// CHECK: address below
+    #[account(address = account_compression::ID)]
+    pub compression_program: UncheckedAccount<'info>,
+
+    /// CHECK: address below
+    #[account(address = noop::ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+}
@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+
+use crate::constants::{AUTHORITY, CONFIG_SEED, SEASON_SEED};
+use crate::model::RaffleError;
+use crate::state::{ProgramConfig, Season};
+
+#[derive(Accounts)]
+pub struct InitConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ProgramConfig::get_space(),
+        seeds = [CONFIG_SEED.as_ref()],
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    // May already exist if `migrate_user_points` ran first
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = Season::get_space(),
+        seeds = [SEASON_SEED.as_ref()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+    #[account(
+        mut,
+        address = Pubkey::from_str(AUTHORITY).unwrap() @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Creates the ProgramConfig and points Season on a program deployed before
+/// they existed, where `initialize` can't run again since the tracker, super
+/// vault and first raffle are already there.
+pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+    ctx.accounts.config.initialize(*ctx.bumps.get("config").unwrap());
+    ctx.accounts.season.initialize(*ctx.bumps.get("season").unwrap());
+    msg!("Program config initialized");
+    Ok(())
+}
@@ -2,9 +2,9 @@ use std::str::FromStr;
 
 use anchor_lang::{prelude::*, system_program};
 
-use crate::constants::{TRACKER_SEED, SUPER_RAFFLE_SEED, AUTHORITY, RAFFLE_SEED};
+use crate::constants::{TRACKER_SEED, SUPER_RAFFLE_SEED, AUTHORITY, RAFFLE_SEED, CONFIG_SEED, SEASON_SEED};
 use crate::model::RaffleError;
-use crate::state::{RaffleTracker, SuperVault, Raffle};
+use crate::state::{RaffleTracker, SuperVault, Raffle, ProgramConfig, Season};
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -27,15 +27,31 @@ pub struct Initialize<'info> {
     #[account(
         init, 
         payer = authority, 
-        space = Raffle::get_space(0),
+        space = Raffle::get_space(),
         seeds = [
             RAFFLE_SEED.as_ref(),
             // 1 because we are creating the 1st raffle only
-            &(1 as u64).to_le_bytes(), 
+            &1u64.to_le_bytes(), 
         ],
         bump
     )]
     pub raffle: Account<'info, Raffle>,
+    #[account(
+        init, 
+        payer = authority, 
+        space = ProgramConfig::get_space(), 
+        seeds = [CONFIG_SEED.as_ref()], 
+        bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        init, 
+        payer = authority, 
+        space = Season::get_space(), 
+        seeds = [SEASON_SEED.as_ref()], 
+        bump
+    )]
+    pub season: Account<'info, Season>,
     #[account(
         mut,
         address = Pubkey::from_str(AUTHORITY).unwrap() @ RaffleError::UnauthorizedSigner
@@ -49,7 +65,12 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     let tracker = &mut ctx.accounts.tracker;
     let super_vault = &mut ctx.accounts.super_vault;
     let raffle: &mut Account<Raffle> = &mut ctx.accounts.raffle;
+    let config = &mut ctx.accounts.config;
 
+    // Initialize ProgramConfig with the default constants
+    config.initialize(*ctx.bumps.get("config").unwrap());
+    // Initialize points Season
+    ctx.accounts.season.initialize(*ctx.bumps.get("season").unwrap());
     // Initialize Supervault
     super_vault.bump = *ctx.bumps.get("super_vault").unwrap();
     // Initialize RaffleTracker
@@ -62,6 +83,7 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     raffle.initialize(
         tracker.current_raffle,
         *ctx.bumps.get("raffle").unwrap(),
-    );
+        config,
+    )?;
     Ok(())
 }
@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::{CONFIG_SEED, LISTING_SEED, LISTING_BOOK_SEED};
+// ProgNftShared (and its generated client modules) lives in end_raffle
+use crate::instructions::end_raffle::*;
+use crate::model::{NftListed, ListingEvicted};
+use crate::state::{Listing, ListingBook, ProgramConfig, CollectionSettings};
+use crate::utils::{send_nft, AuthorizationDataLocal};
+
+#[derive(Accounts)]
+pub struct ListNft<'info> {
+    #[account(mut)]
+    pub seller: Signer<'info>,
+    #[account(mut)]
+    pub src: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: the listing's associated token account, created by `send_nft`
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &listing.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub escrow: UncheckedAccount<'info>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        init,
+        payer = seller,
+        space = Listing::get_space(),
+        seeds = [LISTING_SEED.as_ref(), nft_mint.key().as_ref()],
+        bump
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(
+        init_if_needed,
+        payer = seller,
+        space = ListingBook::get_space(),
+        seeds = [LISTING_BOOK_SEED.as_ref()],
+        bump
+    )]
+    pub listing_book: Box<Account<'info, ListingBook>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
+
+    /// Token program of `nft_mint`, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // pfnt
+    /// CHECK: assert_decode_metadata + seeds below
+    #[account(
+        mut,
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+    /// CHECK: seeds below
+    #[account(
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            mpl_token_metadata::state::EDITION.as_bytes(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump
+    )]
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                src.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub owner_token_record: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                escrow.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub dest_token_record: UncheckedAccount<'info>,
+    pub pnft_shared: ProgNftShared<'info>,
+    //
+    // remaining accounts could be passed, in this order:
+    // - rules account
+    /// CHECK: the `[COLLECTION_SEED, collection]` PDA of the NFT's collection, even
+    /// before `set_collection` creates it, checked by `CollectionSettings`
+    pub collection_settings: UncheckedAccount<'info>,
+}
+
+/// Escrows a collection NFT to be sold into a future pot by `settle_listing`,
+/// once the pot pays the seller at least `ask` lamports after royalties.
+pub fn list_nft<'info>(
+    ctx: Context<'_, '_, '_, 'info, ListNft<'info>>,
+    ask: u64,
+    authorization_data: Option<AuthorizationDataLocal>,
+    rules_acc_present: bool,
+) -> Result<()> {
+    let collection = CollectionSettings::for_nft(
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.config,
+        &ctx.accounts.collection_settings,
+    )?;
+    let royalty_bps = collection.royalty_bps(ctx.accounts.nft_metadata.data.seller_fee_basis_points);
+
+    let rem_acc = &mut ctx.remaining_accounts.iter();
+    let auth_rules = if rules_acc_present {
+        Some(next_account_info(rem_acc)?)
+    } else {
+        None
+    };
+    send_nft(
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.seller.to_account_info(),
+        &ctx.accounts.src,
+        &ctx.accounts.escrow,
+        &ctx.accounts.listing.to_account_info(),
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.edition,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.pnft_shared.instructions,
+        &ctx.accounts.owner_token_record,
+        &ctx.accounts.dest_token_record,
+        &ctx.accounts.pnft_shared.authorization_rules_program,
+        auth_rules,
+        authorization_data,
+        None,
+    )?;
+
+    let listing_book = &mut ctx.accounts.listing_book;
+    listing_book.bump = *ctx.bumps.get("listing_book").unwrap();
+    let nft_mint = ctx.accounts.nft_mint.key();
+    let (seq, evicted) = listing_book.add(nft_mint, ask, royalty_bps)?;
+    if let Some(evicted) = evicted {
+        msg!("{} evicted from the listing book", evicted);
+        emit!(ListingEvicted { nft_mint: evicted });
+    }
+    let seller = ctx.accounts.seller.key();
+    ctx.accounts.listing.set_inner(Listing {
+        bump: *ctx.bumps.get("listing").unwrap(),
+        seller,
+        nft_mint,
+        ask,
+        seq,
+        royalty_bps,
+    });
+
+    msg!("{} listed {} asking {} lamports", seller, nft_mint, ask);
+    emit!(NftListed {
+        seller,
+        nft_mint,
+        ask,
+        seq,
+    });
+    Ok(())
+}
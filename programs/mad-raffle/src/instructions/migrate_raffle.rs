@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Allocate, Assign, Transfer};
+use anchor_lang::Discriminator;
+
+use crate::constants::{CONFIG_SEED, RAFFLE_SEED, TICKET_HOLDER_SEED, TICKET_PRICE, TICKET_RECEIPT_SEED};
+use crate::model::RaffleError;
+use crate::state::{LegacyRaffle, ProgramConfig, Raffle, TicketHolder, TicketReceipt};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct MigrateRaffle<'info> {
+    /// CHECK: Raffle in the legacy layout, parsed below
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub raffle: UncheckedAccount<'info>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    // remaining accounts: if the raffle has no winner yet, the TicketReceipt
+    // then TicketHolder PDA of each legacy ticket holder, in order
+}
+
+/// Rewrites a raffle created before `Raffle::RAFFLE_VERSION` in the current
+/// layout, resized to `Raffle::get_space()`. Its inline tickets become one
+/// receipt and holder PDA per buyer, unless a winner was already drawn,
+/// so every legacy holder has to fit in this transaction. An ended raffle
+/// without a winner has no randomness, `recommit_randomness` gives it some.
+pub fn migrate_raffle<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, MigrateRaffle<'info>>,
+    raffle_id: u64,
+) -> Result<()> {
+    let raffle_info = ctx.accounts.raffle.to_account_info();
+    let authority = &ctx.accounts.authority;
+    let legacy = {
+        let data = raffle_info.try_borrow_data()?;
+        require!(data[..8] == Raffle::discriminator(), ErrorCode::AccountDiscriminatorMismatch);
+        require!(
+            data[LegacyRaffle::VERSION_OFFSET] == LegacyRaffle::VERSION,
+            RaffleError::AlreadyMigrated
+        );
+        LegacyRaffle::deserialize(&mut &data[8..])?
+    };
+    let mut raffle = legacy.migrate();
+    if raffle.active {
+        raffle.apply_config(&ctx.accounts.config);
+    }
+
+    if legacy.needs_receipts() {
+        require!(
+            ctx.remaining_accounts.len() == 2 * legacy.tickets.len(),
+            RaffleError::LegacyTicketsMismatch
+        );
+        let mut start = 0u32;
+        for (index, (holder, accounts)) in legacy
+            .tickets
+            .iter()
+            .zip(ctx.remaining_accounts.chunks(2))
+            .enumerate()
+        {
+            let index = index as u32;
+            let receipt_seeds: &[&[u8]] = &[
+                TICKET_RECEIPT_SEED.as_ref(),
+                &raffle_id.to_le_bytes(),
+                &index.to_le_bytes(),
+            ];
+            let bump = create_pda(&accounts[0], receipt_seeds, TicketReceipt::get_space(), &ctx)?;
+            let receipt = TicketReceipt {
+                raffle_id,
+                bump,
+                user: holder.user,
+                start,
+                qty: holder.qty,
+            };
+            receipt.try_serialize(&mut &mut accounts[0].try_borrow_mut_data()?[..])?;
+
+            let holder_seeds: &[&[u8]] = &[
+                TICKET_HOLDER_SEED.as_ref(),
+                &raffle_id.to_le_bytes(),
+                holder.user.as_ref(),
+            ];
+            let bump = create_pda(&accounts[1], holder_seeds, TicketHolder::get_space(), &ctx)?;
+            let ticket_holder = TicketHolder {
+                raffle_id,
+                bump,
+                user: holder.user,
+                qty: holder.qty,
+                // Legacy tickets were sold at the compiled in price
+                lamports_paid: TICKET_PRICE.checked_mul(holder.qty as u64).ok_or(RaffleError::Overflow)?,
+                tokens_paid: 0,
+            };
+            ticket_holder.try_serialize(&mut &mut accounts[1].try_borrow_mut_data()?[..])?;
+            start += holder.qty as u32;
+        }
+    }
+
+    // Top up rent for the new size, a shrink leaves the excess in the pot
+    let space = Raffle::get_space();
+    let rent_required = Rent::get()?.minimum_balance(space);
+    let top_up = rent_required.saturating_sub(raffle_info.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: authority.to_account_info(),
+                    to: raffle_info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    raffle_info.realloc(space, false)?;
+    raffle.try_serialize(&mut &mut raffle_info.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Raffle# {} migrated with {} receipts",
+        raffle_id,
+        raffle.receipt_count
+    );
+    Ok(())
+}
+
+/// Creates the program owned PDA `account` for `seeds`, like Anchor's `init`
+/// so lamports sent to the address beforehand don't block it. Returns its bump.
+fn create_pda<'info>(
+    account: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    ctx: &Context<'_, '_, '_, 'info, MigrateRaffle<'info>>,
+) -> Result<u8> {
+    let (address, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
+    require_keys_eq!(account.key(), address, RaffleError::LegacyTicketsMismatch);
+    let bump_seed = [bump];
+    let signer_seeds = [seeds, &[&bump_seed[..]]].concat();
+    let signer = &[&signer_seeds[..]];
+    let system_program = ctx.accounts.system_program.to_account_info();
+
+    let rent_required = Rent::get()?.minimum_balance(space);
+    let top_up = rent_required.saturating_sub(account.lamports());
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            Allocate {
+                account_to_allocate: account.clone(),
+            },
+            signer,
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program,
+            Assign {
+                account_to_assign: account.clone(),
+            },
+            signer,
+        ),
+        ctx.program_id,
+    )?;
+    Ok(bump)
+}
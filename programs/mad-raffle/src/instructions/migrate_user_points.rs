@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{SEASON_SEED, TRACKER_SEED, USER_STATS_SEED};
+use crate::model::SuperRaffleError;
+use crate::state::{RaffleTracker, Season, UserStats};
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey)]
+pub struct MigrateUserPoints<'info> {
+    // Shrinks by one entry, refunding the freed rent to the payer
+    #[account(
+        mut,
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump,
+        realloc = RaffleTracker::get_space(tracker.scoreboard.len().saturating_sub(1)),
+        realloc::payer = payer,
+        realloc::zero = false
+    )]
+    pub tracker: Account<'info, RaffleTracker>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), user.as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    // Created here for programs initialized before seasons existed
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Season::get_space(),
+        seeds = [SEASON_SEED.as_ref()],
+        bump
+    )]
+    pub season: Account<'info, Season>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves one user's points from the legacy `RaffleTracker::scoreboard` into
+/// their `UserStats`. Permissionless, since points only move to their owner.
+pub fn migrate_user_points(ctx: Context<MigrateUserPoints>, user: Pubkey) -> Result<()> {
+    let tracker = &mut ctx.accounts.tracker;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let season = &mut ctx.accounts.season;
+
+    let position = tracker
+        .scoreboard
+        .iter()
+        .position(|score| score.user == user)
+        .ok_or(SuperRaffleError::NotOnScoreboard)?;
+    let score = tracker.scoreboard.swap_remove(position);
+
+    season.initialize(*ctx.bumps.get("season").unwrap());
+    user_stats.initialize(&user, *ctx.bumps.get("user_stats").unwrap());
+    // Multiplier was applied when the points were earned
+    user_stats.add_points(season, score.points);
+
+    msg!("Migrated {} points for {}", score.points, user);
+    Ok(())
+}
@@ -1,14 +1,108 @@
 pub mod buy_ticket;
 pub use buy_ticket::*;
 
+pub mod buy_ticket_spl;
+pub use buy_ticket_spl::*;
+
 pub mod initialize;
 pub use initialize::*;
 
+pub mod init_config;
+pub use init_config::*;
+
+pub mod migrate_raffle;
+pub use migrate_raffle::*;
+
 pub mod end_raffle;
 pub use end_raffle::*;
 
 pub mod distribute_prize;
 pub use distribute_prize::*;
 
-pub mod pick_winner;
-pub use pick_winner::*;
\ No newline at end of file
+pub mod end_raffle_cnft;
+pub use end_raffle_cnft::*;
+
+pub mod distribute_prize_cnft;
+pub use distribute_prize_cnft::*;
+
+pub mod commit_randomness;
+pub use commit_randomness::*;
+
+pub mod reveal_and_pick_winner;
+pub use reveal_and_pick_winner::*;
+
+pub mod recommit_randomness;
+pub use recommit_randomness::*;
+
+pub mod request_randomness;
+pub use request_randomness::*;
+
+pub mod consume_randomness;
+pub use consume_randomness::*;
+
+pub mod draw_winner;
+pub use draw_winner::*;
+
+pub mod redraw_unclaimed;
+pub use redraw_unclaimed::*;
+
+pub mod sync_work_queue;
+pub use sync_work_queue::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod propose_authority;
+pub use propose_authority::*;
+
+pub mod accept_authority;
+pub use accept_authority::*;
+
+pub mod recommit_super_raffle;
+pub use recommit_super_raffle::*;
+
+pub mod close_super_raffle_epoch;
+pub use close_super_raffle_epoch::*;
+
+pub mod commit_super_raffle;
+pub use commit_super_raffle::*;
+
+pub mod draw_super_raffle;
+pub use draw_super_raffle::*;
+
+pub mod cancel_raffle;
+pub use cancel_raffle::*;
+
+pub mod claim_refund;
+pub use claim_refund::*;
+
+pub mod close_receipt;
+pub use close_receipt::*;
+
+pub mod close_holder;
+pub use close_holder::*;
+
+pub mod close_expired_raffle;
+pub use close_expired_raffle::*;
+
+pub mod tally_super_raffle;
+pub use tally_super_raffle::*;
+
+pub mod migrate_user_points;
+pub use migrate_user_points::*;
+
+
+pub mod set_payment_mint;
+pub use set_payment_mint::*;
+
+pub mod set_collection;
+pub use set_collection::*;
+
+pub mod list_nft;
+pub use list_nft::*;
+
+pub mod cancel_listing;
+pub use cancel_listing::*;
+
+pub mod settle_listing;
+pub use settle_listing::*;
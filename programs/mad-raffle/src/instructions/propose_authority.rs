@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::CONFIG_SEED;
+use crate::model::RaffleError;
+use crate::state::ProgramConfig;
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [CONFIG_SEED.as_ref()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+}
+
+/// Starts an authority transfer, which the new authority has to accept.
+/// Passing None cancels a pending transfer.
+pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+    ctx.accounts.config.propose_authority(new_authority);
+    msg!("Proposed authority: {:?}", new_authority);
+    Ok(())
+}
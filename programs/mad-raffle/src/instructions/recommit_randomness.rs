@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::constants::RAFFLE_SEED;
+use crate::model::{RaffleError, RandomnessRecommitted};
+use crate::state::Raffle;
+use crate::utils::{commitment_for, slot_hash_expired, PUBLIC_REVEAL_SECRET};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct RecommitRandomness<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Anyone, `REVEAL_TIMEOUT` after the raffle ended
+    pub signer: Signer<'info>,
+
+    /// CHECK: SlotHashes is too large to deserialize, read raw (address checked)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Recovers a raffle whose winner was not revealed before the slot hash it
+/// was committed against left the SlotHashes sysvar (~512 slots), which
+/// would otherwise keep its pot and prizes locked. `REVEAL_TIMEOUT` after the
+/// raffle ended, anyone, the authority included, can commit it to
+/// `PUBLIC_REVEAL_SECRET`, so the draw is decided by the next slot hash alone.
+/// The operator can't commit a new secret of its own, or it could hold back
+/// reveals it doesn't like until they expire and draw again.
+pub fn recommit_randomness(ctx: Context<RecommitRandomness>) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let now = Clock::get()?;
+
+    let slot_hash_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+    require!(
+        slot_hash_expired(&slot_hash_data, raffle.end_slot),
+        RaffleError::RandomnessNotExpired
+    );
+    raffle.recommit_randomness(commitment_for(&PUBLIC_REVEAL_SECRET), now.unix_timestamp, now.slot)?;
+    msg!("Randomness recommitted for raffle# {} after slot {}", raffle.id, now.slot);
+    emit!(RandomnessRecommitted {
+        raffle_id: raffle.id,
+        end_slot: now.slot,
+    });
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::constants::SUPER_DRAW_SEED;
+use crate::model::RaffleError;
+use crate::state::SuperDraw;
+use crate::utils::slot_hash_expired;
+
+#[derive(Accounts)]
+pub struct RecommitSuperRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [SUPER_DRAW_SEED.as_ref()],
+        bump = super_draw.bump
+    )]
+    pub super_draw: Account<'info, SuperDraw>,
+    /// Anyone, `REVEAL_TIMEOUT` after the draw was committed
+    pub signer: Signer<'info>,
+    /// CHECK: SlotHashes is too large to deserialize, read raw (address checked)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Recovers a super raffle draw whose slot hash left the SlotHashes sysvar
+/// (~512 slots) before `draw_super_raffle` revealed it, which would otherwise
+/// block every later draw. Same as `recommit_randomness`: the draw falls back
+/// to `PUBLIC_REVEAL_SECRET` over the same frozen points, never to a new
+/// operator secret.
+pub fn recommit_super_raffle(ctx: Context<RecommitSuperRaffle>) -> Result<()> {
+    let super_draw = &mut ctx.accounts.super_draw;
+    let now = Clock::get()?;
+
+    let slot_hash_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+    require!(
+        slot_hash_expired(&slot_hash_data, super_draw.commit_slot),
+        RaffleError::RandomnessNotExpired
+    );
+    super_draw.recommit_public(now.unix_timestamp, now.slot)?;
+    msg!("Super raffle draw# {} recommitted after slot {}", super_draw.draw_id(), now.slot);
+    Ok(())
+}
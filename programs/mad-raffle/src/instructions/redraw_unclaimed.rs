@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::RAFFLE_SEED;
+use crate::model::{RaffleError, PrizeUnclaimed};
+use crate::state::Raffle;
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct RedrawUnclaimed<'info> {
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        bump = raffle.bump,
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+    )]
+    pub raffle: Account<'info, Raffle>,
+}
+
+/// Takes the prize of `slot` from a winner who did not claim it before its
+/// claim deadline. It is then either drawn again with `draw_winner` among
+/// the holders not drawn yet, or sent into the active raffle with
+/// `distribute_prize`. Permissionless, since the deadline is on chain.
+pub fn redraw_unclaimed(ctx: Context<RedrawUnclaimed>, slot: u8) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let (lapsed_winner, rolled_over) = raffle.lapse_winner(slot, Clock::get()?.unix_timestamp)?;
+    if rolled_over {
+        msg!("Prize {} of raffle# {} rolls over to the active raffle", slot, raffle.id);
+    } else {
+        msg!("Prize {} of raffle# {} will be drawn again", slot, raffle.id);
+    }
+    emit!(PrizeUnclaimed {
+        raffle_id: raffle.id,
+        slot,
+        lapsed_winner,
+        rolled_over,
+    });
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED};
+use crate::model::{RaffleError, OracleError};
+use crate::state::{Raffle, RaffleTracker, ProgramConfig, OracleProvider};
+use crate::utils::verify_oracle_request;
+
+#[derive(Accounts)]
+pub struct RequestRandomness<'info> {
+    #[account(
+        mut, 
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),  
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    #[account(
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump,
+    )]
+    pub tracker: Account<'info, RaffleTracker>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    /// CHECK: verified by the selected oracle provider
+    pub oracle: UncheckedAccount<'info>,
+}
+
+/// Records a pending oracle request on the current raffle. Like a commitment,
+/// this must happen before the raffle ends, and the oracle result is only
+/// accepted if it was fulfilled after the raffle ended.
+///
+/// Test only: no production oracle is supported yet, so builds without the
+/// `mock-oracle` feature reject it and draws use `commit_randomness`.
+pub fn request_randomness(ctx: Context<RequestRandomness>, provider: OracleProvider) -> Result<()> {
+    require!(cfg!(feature = "mock-oracle"), OracleError::UnsupportedOracle);
+    let raffle = &mut ctx.accounts.raffle;
+    let oracle = &ctx.accounts.oracle;
+    require!(raffle.active, RaffleError::NotActive);
+    require!(!raffle.has_randomness_source(), RaffleError::RandomnessAlreadyCommitted);
+
+    verify_oracle_request(provider, oracle, &raffle.key())?;
+    raffle.request_randomness(provider, oracle.key())?;
+    msg!("Randomness requested for raffle# {} from {:?}", raffle.id, provider);
+    Ok(())
+}
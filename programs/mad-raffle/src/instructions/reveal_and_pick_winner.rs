@@ -0,0 +1,77 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::model::{RaffleError, WinnerPicked};
+use crate::state::{Raffle, ProgramConfig, TicketReceipt, UserStats};
+use crate::constants::{RAFFLE_SEED, CONFIG_SEED, USER_STATS_SEED};
+use crate::utils::{commitment_for, pay_crank_reward, reveal_seed, slot_hash_after};
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct RevealAndPickWinner<'info> {
+    #[account(
+        mut, 
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),  
+        ],
+        constraint = raffle.id == raffle_id,
+        bump = raffle.bump
+    )]
+    pub raffle: Account<'info, Raffle>,
+    /// Receipt holding the winning ticket
+    #[account(constraint = receipt.raffle_id == raffle_id @ RaffleError::WrongTicketReceipt)]
+    pub receipt: Account<'info, TicketReceipt>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_ref(), receipt.user.as_ref()],
+        bump = winner_stats.bump
+    )]
+    pub winner_stats: Account<'info, UserStats>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    /// Whoever holds the committed secret, paid `raffle.crank_reward`
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: SlotHashes is too large to deserialize, read raw (address checked)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Operator-only in practice: no signer is checked, since the outcome is
+/// fixed by the commitment and the slot hash, but only the authority knows
+/// the secret it committed. If it never reveals, `recommit_randomness` lets
+/// anyone fall back to `PUBLIC_REVEAL_SECRET` after `REVEAL_TIMEOUT`, which
+/// anyone can then reveal here.
+pub fn reveal_and_pick_winner(ctx: Context<RevealAndPickWinner>, secret: [u8; 32]) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let total_tickets: u32 = raffle.get_ticket_count();
+    require!(!raffle.active, RaffleError::StillActive);
+    require!(!raffle.cancelled, RaffleError::RaffleCancelled);
+    require!(total_tickets > 0, RaffleError::NoTickets);
+
+    // Secret must match what was committed before the raffle ended
+    let commitment = raffle.commitment.ok_or(RaffleError::RandomnessNotCommitted)?;
+    require!(commitment_for(&secret) == commitment, RaffleError::InvalidReveal);
+
+    // Mix in the first slot hash produced after the raffle ended
+    let slot_hash_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+    let slot_hash = slot_hash_after(&slot_hash_data, raffle.end_slot)?;
+
+    raffle.seed_draws(reveal_seed(secret, slot_hash))?;
+    let now = Clock::get()?.unix_timestamp;
+    // The first draw never repeats a winner, the others are made by `draw_winner`
+    if let Some((slot, winner)) = raffle.draw_winner(&ctx.accounts.receipt, now)? {
+        ctx.accounts.winner_stats.raffles_won += 1;
+        emit!(WinnerPicked {
+            raffle_id: raffle.id,
+            winner,
+            slot,
+            receipt: ctx.accounts.receipt.key(),
+            total_tickets,
+        });
+        pay_crank_reward(raffle, &ctx.accounts.cranker, &ctx.accounts.config)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::Mint;
+
+use crate::constants::{CONFIG_SEED, COLLECTION_SEED};
+use crate::model::{RaffleError, ConfigError};
+use crate::state::{CollectionSettings, ProgramConfig, RoyaltyPolicy};
+
+#[derive(Accounts)]
+pub struct SetCollection<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = CollectionSettings::get_space(),
+        seeds = [COLLECTION_SEED.as_ref(), collection.key().as_ref()],
+        bump
+    )]
+    pub collection_settings: Account<'info, CollectionSettings>,
+    pub collection: Account<'info, Mint>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Lets NFTs of `collection` be sold into raffles, with their own selling
+/// points and royalty policy. Disabling only stops new sales, prizes already
+/// won still go out.
+pub fn set_collection(
+    ctx: Context<SetCollection>,
+    enabled: bool,
+    points_for_selling: u32,
+    royalty_policy: RoyaltyPolicy,
+) -> Result<()> {
+    if let RoyaltyPolicy::Fixed { basis_points } = royalty_policy {
+        require!(basis_points <= 10000, ConfigError::InvalidRoyalty);
+    }
+    let collection = ctx.accounts.collection.key();
+    ctx.accounts.collection_settings.set_inner(CollectionSettings {
+        bump: *ctx.bumps.get("collection_settings").unwrap(),
+        collection,
+        enabled,
+        points_for_selling,
+        royalty_policy,
+    });
+    msg!("Collection {} set, enabled: {}", collection, enabled);
+    Ok(())
+}
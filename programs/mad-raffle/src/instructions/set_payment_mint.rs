@@ -0,0 +1,42 @@
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token::Mint;
+
+use crate::constants::{CONFIG_SEED, PAYMENT_MINT_SEED};
+use crate::model::RaffleError;
+use crate::state::{PaymentMint, ProgramConfig};
+
+#[derive(Accounts)]
+pub struct SetPaymentMint<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = PaymentMint::get_space(),
+        seeds = [PAYMENT_MINT_SEED.as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub payment_config: Account<'info, PaymentMint>,
+    pub mint: Account<'info, Mint>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        mut,
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    #[account(address = system_program::ID)]
+    pub system_program: Program<'info, System>,
+}
+
+/// Whitelists `mint` for `buy_ticket_spl`, priced by `price_feed`.
+/// Disabling only stops new purchases, escrowed tokens still pay out.
+pub fn set_payment_mint(ctx: Context<SetPaymentMint>, price_feed: Pubkey, enabled: bool) -> Result<()> {
+    let mint = ctx.accounts.mint.key();
+    ctx.accounts.payment_config.set_inner(PaymentMint {
+        bump: *ctx.bumps.get("payment_config").unwrap(),
+        mint,
+        price_feed,
+        enabled,
+    });
+    msg!("Payment mint {} set, enabled: {}", mint, enabled);
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use anchor_spl::{
+    associated_token::{get_associated_token_address_with_program_id, AssociatedToken},
+    token::{Token, TokenAccount},
+    token_interface::{self, Mint, TokenInterface},
+};
+
+use crate::constants::{
+    RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED, USER_STATS_SEED, SEASON_SEED,
+    LISTING_SEED, LISTING_BOOK_SEED,
+};
+// ProgNftShared (and its generated client modules) lives in end_raffle
+use crate::instructions::end_raffle::*;
+use crate::model::{RaffleError, ListingError, RaffleEnded, PrizeAdded, ListingSettled};
+use crate::state::{
+    Raffle, RaffleTracker, ProgramConfig, UserStats, Season, Listing, ListingBook, CollectionSettings,
+};
+use crate::utils::{send_nft, pay_out_pot, prize_share, AuthorizationDataLocal};
+
+#[derive(Accounts)]
+pub struct SettleListing<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: paid the pot, address checked against the listing
+    #[account(mut, address = listing.seller @ RaffleError::UnauthorizedSigner)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = seller,
+        seeds = [LISTING_SEED.as_ref(), nft_mint.key().as_ref()],
+        bump = listing.bump,
+    )]
+    pub listing: Box<Account<'info, Listing>>,
+    #[account(mut, seeds = [LISTING_BOOK_SEED.as_ref()], bump = listing_book.bump)]
+    pub listing_book: Box<Account<'info, ListingBook>>,
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &listing.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub src: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// CHECK: the raffle's associated token account, created by `send_nft`
+    #[account(
+        mut,
+        address = get_associated_token_address_with_program_id(
+            &raffle.key(),
+            &nft_mint.key(),
+            &token_program.key(),
+        ),
+    )]
+    pub dest: UncheckedAccount<'info>,
+    pub nft_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Token program of `nft_mint`, SPL Token or Token-2022
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    // pfnt
+    /// CHECK: assert_decode_metadata + seeds below
+    #[account(
+        mut,
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump,
+    )]
+    pub nft_metadata: Box<Account<'info, MetadataAccount>>,
+    /// CHECK: seeds below
+    #[account(
+        seeds=[
+            mpl_token_metadata::state::PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            nft_mint.key().as_ref(),
+            mpl_token_metadata::state::EDITION.as_bytes(),
+        ],
+        seeds::program = mpl_token_metadata::id(),
+        bump
+    )]
+    pub edition: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                src.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub owner_token_record: UncheckedAccount<'info>,
+    /// CHECK: seeds below
+    #[account(mut,
+            seeds=[
+                mpl_token_metadata::state::PREFIX.as_bytes(),
+                mpl_token_metadata::id().as_ref(),
+                nft_mint.key().as_ref(),
+                mpl_token_metadata::state::TOKEN_RECORD_SEED.as_bytes(),
+                dest.key().as_ref()
+            ],
+            seeds::program = mpl_token_metadata::id(),
+            bump
+        )]
+    pub dest_token_record: UncheckedAccount<'info>,
+    pub pnft_shared: ProgNftShared<'info>,
+    //
+    // remaining accounts could be passed, in this order:
+    // - rules account
+    // - creator token accounts for `raffle.payment_mint`, any order
+    #[account(
+        mut,
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle).to_le_bytes(),
+        ],
+        bump = raffle.bump
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+    /// Created by the first sale, initialized once the raffle ends
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Raffle::get_space(),
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(tracker.current_raffle + 1).to_le_bytes(),
+        ],
+        bump
+    )]
+    pub new_raffle: Box<Account<'info, Raffle>>,
+    #[account(
+        mut,
+        seeds = [TRACKER_SEED.as_ref()],
+        bump = tracker.bump
+    )]
+    pub tracker: Box<Account<'info, RaffleTracker>>,
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = UserStats::get_space(),
+        seeds = [USER_STATS_SEED.as_ref(), seller.key().as_ref()],
+        bump
+    )]
+    pub seller_stats: Box<Account<'info, UserStats>>,
+    #[account(mut, seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Box<Account<'info, Season>>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Box<Account<'info, ProgramConfig>>,
+    #[account(mut)]
+    pub creator1: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator2: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator3: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator4: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub creator5: Option<AccountInfo<'info>>,
+    /// Required when the raffle escrowed SPL tokens (`raffle.payment_mint`)
+    #[account(mut)]
+    pub pot_escrow: Option<Box<Account<'info, TokenAccount>>>,
+    #[account(mut)]
+    pub seller_token_account: Option<Box<Account<'info, TokenAccount>>>,
+    /// Required along with `pot_escrow`
+    pub payment_token_program: Option<Program<'info, Token>>,
+    /// CHECK: the `[COLLECTION_SEED, collection]` PDA of the NFT's collection, even
+    /// before `set_collection` creates it, checked by `CollectionSettings`
+    pub collection_settings: UncheckedAccount<'info>,
+}
+
+/// Permissionless `end_raffle` against a listed NFT. The pot goes to the
+/// cheapest listing whose ask it meets after royalties, the earliest on ties.
+pub fn settle_listing<'info>(
+    ctx: Context<'_, '_, '_, 'info, SettleListing<'info>>,
+    authorization_data: Option<AuthorizationDataLocal>,
+    rules_acc_present: bool,
+) -> Result<()> {
+    let raffle = &mut ctx.accounts.raffle;
+    let seller = &ctx.accounts.seller;
+    let listing = &ctx.accounts.listing;
+    let tracker = &mut ctx.accounts.tracker;
+    let new_raffle = &mut ctx.accounts.new_raffle;
+    let nft_mint = &ctx.accounts.nft_mint;
+    let ata = &ctx.accounts.dest;
+    // Verify raffle is active and has sold some tickets
+    let total_tickets: u32 = raffle.get_ticket_count();
+    require!(raffle.active, RaffleError::NotActive);
+    require!(total_tickets > 0, RaffleError::NoTickets);
+    raffle.check_can_end(Clock::get()?.unix_timestamp)?;
+    require!(raffle.has_randomness_source(), RaffleError::RandomnessNotCommitted);
+    let collection = CollectionSettings::for_listed_nft(
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.config,
+        &ctx.accounts.collection_settings,
+    )?;
+
+    // Only the best listing the pot can pay for may be settled
+    let available_balance = prize_share(raffle, &ctx.accounts.config)?;
+    let best = ctx.accounts.listing_book
+        .best(available_balance)
+        .ok_or(ListingError::NoListingSatisfied)?;
+    require_keys_eq!(best.nft_mint, nft_mint.key(), ListingError::NotBestListing);
+
+    let bump = [listing.bump];
+    let nft_mint_key = nft_mint.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[LISTING_SEED.as_ref(), nft_mint_key.as_ref(), &bump]];
+
+    let rem_acc = &mut ctx.remaining_accounts.iter();
+    let auth_rules = if rules_acc_present {
+        Some(next_account_info(rem_acc)?)
+    } else {
+        None
+    };
+    send_nft(
+        &listing.to_account_info(),
+        &ctx.accounts.payer.to_account_info(),
+        &ctx.accounts.src,
+        &ctx.accounts.dest,
+        &raffle.to_account_info(),
+        &ctx.accounts.nft_mint,
+        &ctx.accounts.nft_metadata,
+        &ctx.accounts.edition,
+        &ctx.accounts.system_program,
+        &ctx.accounts.token_program,
+        &ctx.accounts.associated_token_program,
+        &ctx.accounts.pnft_shared.instructions,
+        &ctx.accounts.owner_token_record,
+        &ctx.accounts.dest_token_record,
+        &ctx.accounts.pnft_shared.authorization_rules_program,
+        auth_rules,
+        authorization_data,
+        Some(signer_seeds),
+    )?;
+
+    let payout = pay_out_pot(
+        raffle,
+        &seller.to_account_info(),
+        nft_mint.key(),
+        ctx.accounts.nft_metadata.data.creators.as_deref().unwrap_or_default(),
+        listing.royalty_bps,
+        &ctx.accounts.config,
+        [
+            ctx.accounts.creator1.as_ref(),
+            ctx.accounts.creator2.as_ref(),
+            ctx.accounts.creator3.as_ref(),
+            ctx.accounts.creator4.as_ref(),
+            ctx.accounts.creator5.as_ref(),
+        ],
+        ctx.accounts.pot_escrow.as_deref(),
+        ctx.accounts.seller_token_account.as_deref(),
+        rem_acc.as_slice(),
+        ctx.accounts.payment_token_program.as_ref(),
+        listing.ask,
+        0,
+    )?;
+    ctx.accounts.listing_book.remove(&nft_mint.key());
+
+    // Update raffle state
+    let slot = raffle.prizes.len() as u8;
+    if raffle.add_prize(nft_mint.key(), ata.key())? {
+        // Increment the raffle counter and initialize the new raffle
+        tracker.increment();
+        msg!("New raffle to be created: {}", tracker.current_raffle);
+        new_raffle.initialize(
+            tracker.current_raffle,
+            *ctx.bumps.get("new_raffle").unwrap(),
+            &ctx.accounts.config,
+        )?;
+        emit!(RaffleEnded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: nft_mint.key(),
+            total_tickets,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            payment_mint: raffle.payment_mint,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+            next_raffle_id: tracker.current_raffle,
+        });
+    } else {
+        emit!(PrizeAdded {
+            raffle_id: raffle.id,
+            seller: seller.key(),
+            nft_mint: nft_mint.key(),
+            slot,
+            payment_to_seller: payout.payment_to_seller,
+            royalties_paid: payout.royalties_paid,
+            token_payment_to_seller: payout.token_payment_to_seller,
+            token_royalties_paid: payout.token_royalties_paid,
+        });
+    }
+    emit!(ListingSettled {
+        raffle_id: raffle.id,
+        seller: seller.key(),
+        nft_mint: nft_mint.key(),
+        ask: listing.ask,
+        payment_to_seller: payout.payment_to_seller,
+        settler: ctx.accounts.payer.key(),
+    });
+    // Add bonus points to the seller
+    let seller_stats = &mut ctx.accounts.seller_stats;
+    seller_stats.initialize(seller.key, *ctx.bumps.get("seller_stats").unwrap());
+    seller_stats.nfts_sold += 1;
+    tracker.add_points(&mut ctx.accounts.season, seller_stats, collection.points_for_selling);
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar::slot_hashes;
+
+use crate::constants::{RAFFLE_SEED, WORK_QUEUE_SEED};
+use crate::model::RaffleError;
+use crate::state::{Raffle, WorkQueue};
+use crate::utils::slot_hash_expired;
+
+#[derive(Accounts)]
+#[instruction(raffle_id: u64)]
+pub struct SyncWorkQueue<'info> {
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        space = WorkQueue::get_space(),
+        seeds = [WORK_QUEUE_SEED.as_ref()],
+        bump
+    )]
+    pub work_queue: Box<Account<'info, WorkQueue>>,
+    #[account(
+        seeds = [
+            RAFFLE_SEED.as_ref(),
+            &(raffle_id).to_le_bytes(),
+        ],
+        bump = raffle.bump,
+        constraint = raffle.id == raffle_id @ RaffleError::RafflePdaMismatch,
+    )]
+    pub raffle: Box<Account<'info, Raffle>>,
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: SlotHashes is too large to deserialize, read raw (address checked)
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+}
+
+/// Records the work left on raffle `raffle_id` in the work queue, dropping
+/// what was done since the last sync. Permissionless, since the work follows
+/// from the raffle's state. A scheduler syncs `tracker.current_raffle - 1`
+/// once it ends, then each raffle in the queue after working on it. Every
+/// queued item can be run, so re-syncing a raffle whose draw expired swaps
+/// its stale `DrawWinner` for `RecommitRandomness` instead of holding a slot.
+pub fn sync_work_queue(ctx: Context<SyncWorkQueue>, raffle_id: u64) -> Result<()> {
+    let work_queue = &mut ctx.accounts.work_queue;
+    work_queue.bump = *ctx.bumps.get("work_queue").unwrap();
+    let raffle = &ctx.accounts.raffle;
+    let slot_hash_data = ctx.accounts.slot_hashes.try_borrow_data()?;
+    let randomness_expired = slot_hash_expired(&slot_hash_data, raffle.end_slot);
+    let tasks = raffle.pending_work(Clock::get()?.unix_timestamp, randomness_expired);
+    msg!("Raffle# {} has {} pending work items", raffle_id, tasks.len());
+    work_queue.sync(raffle_id, tasks)
+}
@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::{CONFIG_SEED, SEASON_SEED, SUPER_DRAW_SEED};
+use crate::model::{RaffleError, SuperRaffleError};
+use crate::state::{ProgramConfig, Season, SuperDraw, UserStats};
+
+#[derive(Accounts)]
+pub struct TallySuperRaffle<'info> {
+    #[account(
+        mut,
+        seeds = [SUPER_DRAW_SEED.as_ref()],
+        bump = super_draw.bump
+    )]
+    pub super_draw: Account<'info, SuperDraw>,
+    #[account(seeds = [SEASON_SEED.as_ref()], bump = season.bump)]
+    pub season: Account<'info, Season>,
+    #[account(seeds = [CONFIG_SEED.as_ref()], bump = config.bump)]
+    pub config: Account<'info, ProgramConfig>,
+    #[account(
+        address = config.authority @ RaffleError::UnauthorizedSigner
+    )]
+    pub authority: Signer<'info>,
+    // remaining accounts: writable UserStats in ascending user order
+}
+
+/// Assigns each user a range of the next draw's frozen points, over as many
+/// transactions as needed. Runs after `close_super_raffle_epoch`, so the
+/// tally can finish before the commit starts the reveal window. `draw_super_raffle` only pays out once every
+/// frozen point is covered, so skipping a user leaves the tally short.
+/// `restart` starts over, e.g. after a user was skipped.
+pub fn tally_super_raffle<'a, 'b, 'c, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, TallySuperRaffle<'info>>,
+    restart: bool,
+) -> Result<()> {
+    let super_draw = &mut ctx.accounts.super_draw;
+    let season = &ctx.accounts.season;
+    require!(super_draw.epoch_closed, SuperRaffleError::EpochNotClosed);
+
+    if restart {
+        super_draw.restart_tally();
+    }
+    for account in ctx.remaining_accounts.iter() {
+        let mut stats: Account<UserStats> = Account::try_from(account)?;
+        super_draw.tally(season, &mut stats)?;
+        stats.exit(ctx.program_id)?;
+    }
+    msg!(
+        "Super raffle draw# {} tallied {} of {} points",
+        super_draw.draw_id(),
+        super_draw.tallied_points,
+        super_draw.total_points
+    );
+    Ok(())
+}
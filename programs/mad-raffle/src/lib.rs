@@ -1,3 +1,5 @@
+#![allow(clippy::result_large_err)]
+
 pub mod constants;
 pub mod id;
 pub mod instructions;
@@ -7,7 +9,8 @@ pub mod utils;
 
 use anchor_lang::prelude::*;
 use instructions::*;
-use utils::AuthorizationDataLocal;
+use utils::{AuthorizationDataLocal, CompressedAsset};
+use state::{OracleProvider, ConfigUpdate, RoyaltyPolicy};
 pub use id::ID;
 
 #[program]
@@ -18,29 +21,199 @@ pub mod mad_raffle {
         instructions::initialize(ctx)
     }
 
-    pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
-        instructions::buy_ticket(ctx)
+    pub fn update_config(ctx: Context<UpdateConfig>, update: ConfigUpdate) -> Result<()> {
+        instructions::update_config(ctx, update)
+    }
+
+    pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+        instructions::propose_authority(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        instructions::accept_authority(ctx)
+    }
+
+    pub fn init_config(ctx: Context<InitConfig>) -> Result<()> {
+        instructions::init_config(ctx)
+    }
+
+    pub fn migrate_raffle<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, MigrateRaffle<'info>>,
+        raffle_id: u64,
+    ) -> Result<()> {
+        instructions::migrate_raffle(ctx, raffle_id)
+    }
+
+    pub fn buy_ticket(ctx: Context<BuyTicket>, quantity: u8) -> Result<()> {
+        instructions::buy_ticket(ctx, quantity)
+    }
+
+    pub fn buy_ticket_spl(ctx: Context<BuyTicketSpl>, quantity: u8) -> Result<()> {
+        instructions::buy_ticket_spl(ctx, quantity)
+    }
+
+    pub fn set_payment_mint(ctx: Context<SetPaymentMint>, price_feed: Pubkey, enabled: bool) -> Result<()> {
+        instructions::set_payment_mint(ctx, price_feed, enabled)
+    }
+
+    pub fn set_collection(
+        ctx: Context<SetCollection>,
+        enabled: bool,
+        points_for_selling: u32,
+        royalty_policy: RoyaltyPolicy,
+    ) -> Result<()> {
+        instructions::set_collection(ctx, enabled, points_for_selling, royalty_policy)
     }
 
     pub fn end_raffle<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, EndRaffle<'info>>,
         authorization_data: Option<AuthorizationDataLocal>,
         rules_acc_present: bool,
+        min_payment: u64,
+        min_token_payment: u64,
     ) -> Result<()> {
-        instructions::end_raffle(ctx, authorization_data, rules_acc_present)
+        instructions::end_raffle(ctx, authorization_data, rules_acc_present, min_payment, min_token_payment)
     }
 
-    pub fn pick_winner(ctx: Context<PickWinner>, _raffle_id: u64) -> Result<()> {
-        instructions::pick_winner(ctx)
+    pub fn end_raffle_cnft<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, EndRaffleCnft<'info>>,
+        asset: CompressedAsset,
+        proof_len: u8,
+        min_payment: u64,
+        min_token_payment: u64,
+    ) -> Result<()> {
+        instructions::end_raffle_cnft(ctx, asset, proof_len, min_payment, min_token_payment)
+    }
+
+    pub fn list_nft<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, ListNft<'info>>,
+        ask: u64,
+        authorization_data: Option<AuthorizationDataLocal>,
+        rules_acc_present: bool,
+    ) -> Result<()> {
+        instructions::list_nft(ctx, ask, authorization_data, rules_acc_present)
+    }
+
+    pub fn cancel_listing<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, CancelListing<'info>>,
+        authorization_data: Option<AuthorizationDataLocal>,
+        rules_acc_present: bool,
+    ) -> Result<()> {
+        instructions::cancel_listing(ctx, authorization_data, rules_acc_present)
+    }
+
+    pub fn settle_listing<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SettleListing<'info>>,
+        authorization_data: Option<AuthorizationDataLocal>,
+        rules_acc_present: bool,
+    ) -> Result<()> {
+        instructions::settle_listing(ctx, authorization_data, rules_acc_present)
+    }
+
+    pub fn cancel_raffle(ctx: Context<CancelRaffle>) -> Result<()> {
+        instructions::cancel_raffle(ctx)
+    }
+
+    pub fn close_expired_raffle(ctx: Context<CloseExpiredRaffle>) -> Result<()> {
+        instructions::close_expired_raffle(ctx)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>, _raffle_id: u64) -> Result<()> {
+        instructions::claim_refund(ctx)
+    }
+
+    pub fn close_receipt(ctx: Context<CloseReceipt>, _raffle_id: u64, _index: u32) -> Result<()> {
+        instructions::close_receipt(ctx)
+    }
+
+    pub fn close_holder(ctx: Context<CloseHolder>, _raffle_id: u64) -> Result<()> {
+        instructions::close_holder(ctx)
+    }
+
+    pub fn commit_randomness(ctx: Context<CommitRandomness>, commitment: [u8; 32]) -> Result<()> {
+        instructions::commit_randomness(ctx, commitment)
+    }
+
+    pub fn reveal_and_pick_winner(
+        ctx: Context<RevealAndPickWinner>,
+        _raffle_id: u64,
+        secret: [u8; 32],
+    ) -> Result<()> {
+        instructions::reveal_and_pick_winner(ctx, secret)
+    }
+
+    pub fn recommit_randomness(ctx: Context<RecommitRandomness>, _raffle_id: u64) -> Result<()> {
+        instructions::recommit_randomness(ctx)
+    }
+
+    pub fn request_randomness(ctx: Context<RequestRandomness>, provider: OracleProvider) -> Result<()> {
+        instructions::request_randomness(ctx, provider)
+    }
+
+    pub fn consume_randomness(ctx: Context<ConsumeRandomness>, _raffle_id: u64) -> Result<()> {
+        instructions::consume_randomness(ctx)
+    }
+
+    pub fn draw_winner(ctx: Context<DrawWinner>, _raffle_id: u64) -> Result<()> {
+        instructions::draw_winner(ctx)
+    }
+
+    pub fn redraw_unclaimed(ctx: Context<RedrawUnclaimed>, _raffle_id: u64, slot: u8) -> Result<()> {
+        instructions::redraw_unclaimed(ctx, slot)
+    }
+
+    pub fn sync_work_queue(ctx: Context<SyncWorkQueue>, raffle_id: u64) -> Result<()> {
+        instructions::sync_work_queue(ctx, raffle_id)
+    }
+
+    pub fn close_super_raffle_epoch(ctx: Context<CloseSuperRaffleEpoch>, reset_season: bool) -> Result<()> {
+        instructions::close_super_raffle_epoch(ctx, reset_season)
+    }
+
+    pub fn commit_super_raffle(
+        ctx: Context<CommitSuperRaffle>,
+        commitment: [u8; 32],
+        reset_season: bool,
+    ) -> Result<()> {
+        instructions::commit_super_raffle(ctx, commitment, reset_season)
+    }
+
+    pub fn tally_super_raffle<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, TallySuperRaffle<'info>>,
+        restart: bool,
+    ) -> Result<()> {
+        instructions::tally_super_raffle(ctx, restart)
+    }
+
+    pub fn recommit_super_raffle(ctx: Context<RecommitSuperRaffle>) -> Result<()> {
+        instructions::recommit_super_raffle(ctx)
+    }
+
+    pub fn draw_super_raffle(ctx: Context<DrawSuperRaffle>, secret: [u8; 32]) -> Result<()> {
+        instructions::draw_super_raffle(ctx, secret)
+    }
+
+    pub fn migrate_user_points(ctx: Context<MigrateUserPoints>, user: Pubkey) -> Result<()> {
+        instructions::migrate_user_points(ctx, user)
     }
 
     pub fn distribute_prize<'a, 'b, 'c, 'info>(
         ctx: Context<'a, 'b, 'c, 'info, DistributePrize<'info>>,
         raffle_id: u64,
+        slot: u8,
         authorization_data: Option<AuthorizationDataLocal>,
         rules_acc_present: bool,
     ) -> Result<()> {
-        instructions::distribute_prize(ctx, raffle_id, authorization_data, rules_acc_present)
+        instructions::distribute_prize(ctx, raffle_id, slot, authorization_data, rules_acc_present)
+    }
+
+    pub fn distribute_prize_cnft<'a, 'b, 'c, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DistributePrizeCnft<'info>>,
+        raffle_id: u64,
+        slot: u8,
+        asset: CompressedAsset,
+    ) -> Result<()> {
+        instructions::distribute_prize_cnft(ctx, raffle_id, slot, asset)
     }
     
 }
@@ -26,6 +26,54 @@ pub enum RaffleError {
     WinnerNotSelected,
     #[msg("Raffle PDA does not match ID")]
     RafflePdaMismatch,
+    #[msg("Must buy at least one ticket")]
+    InvalidTicketQuantity,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Randomness already committed")]
+    RandomnessAlreadyCommitted,
+    #[msg("Randomness not committed")]
+    RandomnessNotCommitted,
+    #[msg("Revealed secret does not match commitment")]
+    InvalidReveal,
+    #[msg("Slot hash not yet available")]
+    SlotHashNotAvailable,
+    #[msg("Slot hash no longer available")]
+    RandomnessExpired,
+    #[msg("Raffle was cancelled")]
+    RaffleCancelled,
+    #[msg("Raffle is not cancelled")]
+    NotCancelled,
+    #[msg("No refund available")]
+    NoRefund,
+    #[msg("Ticket receipt does not hold the winning ticket")]
+    WrongTicketReceipt,
+    #[msg("No winner can be chosen from a raffle without tickets")]
+    NoEligibleTickets,
+    #[msg("Raffle has not been open for the minimum duration")]
+    MinDurationNotReached,
+    #[msg("Raffle has not sold the minimum number of tickets")]
+    NotEnoughTickets,
+    #[msg("Raffle has not reached its end time")]
+    NotExpired,
+    #[msg("Payment to seller is below the requested minimum")]
+    PaymentBelowMinimum,
+    #[msg("Raffle already holds prizes")]
+    HasPrizes,
+    #[msg("Raffle randomness not yet revealed")]
+    RandomnessNotRevealed,
+    #[msg("Slot hash for the raffle's randomness is still available")]
+    RandomnessNotExpired,
+    #[msg("Reveal timeout has not passed yet")]
+    RevealTimeoutNotReached,
+    #[msg("Raffle is already in the current layout")]
+    AlreadyMigrated,
+    #[msg("Legacy ticket accounts missing or invalid")]
+    LegacyTicketsMismatch,
+    #[msg("Tickets can still be drawn")]
+    TicketsInUse,
+    #[msg("Expired randomness can only be recommitted to the public reveal secret")]
+    SecretRecommitted,
 }
 
 #[error_code]
@@ -38,6 +86,24 @@ pub enum PnftError {
     InvalidCollectionAddress,
     #[msg("Not Verified by the Collection")]
     NotVerifiedByCollection,
+    #[msg("NFT has no collection")]
+    MissingCollection,
+    #[msg("Ruleset account required but not passed")]
+    MissingRulesAccount,
+    #[msg("Bad Authorization Data")]
+    BadAuthorizationData,
+    #[msg("Could not build the transfer instruction")]
+    BadTransferInstruction,
+    #[msg("Collection is not enabled")]
+    CollectionDisabled,
+    #[msg("Only single NFTs can be sent as prizes")]
+    UnsupportedAsset,
+    #[msg("Mint has a Token-2022 extension prizes can't be sent with")]
+    UnsupportedMintExtension,
+    #[msg("Token program does not own the mint")]
+    WrongTokenProgram,
+    #[msg("Collection settings account is not the collection's PDA")]
+    InvalidCollectionSettings,
 }
 
 #[error_code]
@@ -50,10 +116,109 @@ pub enum PrizeError {
     NoPrizeInRaffle,
     #[msg("Unauthorized to distribute prize")]
     UnauthorizedDistributor,
+    #[msg("Asset is not the raffle's compressed prize")]
+    InvalidPrizeAsset,
+    #[msg("Prize already sent")]
+    PrizeAlreadySent,
+    #[msg("Prize can still be claimed by its winner")]
+    ClaimWindowOpen,
+    #[msg("Raffle cannot take more prizes")]
+    TooManyPrizes,
 }
 
 #[error_code]
 pub enum FeedError {
     #[msg("Invalid Price Feed")]
     InvalidPriceFeed,
-}
\ No newline at end of file
+    #[msg("Price feed is stale")]
+    StalePrice,
+    #[msg("Price confidence interval is too wide")]
+    PriceUncertain,
+    #[msg("Price must be positive")]
+    InvalidPrice,
+}
+
+#[error_code]
+pub enum OracleError {
+    #[msg("Oracle provider not supported")]
+    UnsupportedOracle,
+    #[msg("Invalid oracle account")]
+    InvalidOracleAccount,
+    #[msg("Randomness not yet fulfilled")]
+    RandomnessNotFulfilled,
+    #[msg("No randomness requested")]
+    NoRandomnessRequest,
+}
+
+#[error_code]
+pub enum ConfigError {
+    #[msg("Ticket price must be greater than zero")]
+    InvalidTicketPrice,
+    #[msg("Max tickets per user must be greater than zero")]
+    InvalidMaxTickets,
+    #[msg("Max raffle duration cannot be negative")]
+    InvalidMaxDuration,
+    #[msg("No authority transfer pending")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Max price confidence must be between 1 and 10000 bps")]
+    InvalidConfidence,
+    #[msg("Raffle durations must be non-negative, with the minimum within the duration")]
+    InvalidDuration,
+    #[msg("Min tickets must be greater than zero")]
+    InvalidMinTickets,
+    #[msg("Royalty basis points must be at most 10000")]
+    InvalidRoyalty,
+    #[msg("Prizes per raffle must be between 1 and the maximum")]
+    InvalidPrizeCount,
+    #[msg("Claim window cannot be negative")]
+    InvalidClaimWindow,
+    #[msg("Crank reward is above the maximum")]
+    InvalidCrankReward,
+}
+
+#[error_code]
+pub enum SuperRaffleError {
+    #[msg("Super raffle draw already committed")]
+    DrawAlreadyCommitted,
+    #[msg("Super raffle draw not committed")]
+    DrawNotCommitted,
+    #[msg("No points to draw from")]
+    NoPoints,
+    #[msg("Super vault has nothing to pay out")]
+    EmptyVault,
+    #[msg("User stats must be tallied in ascending user order")]
+    TallyOutOfOrder,
+    #[msg("Super raffle tally is incomplete")]
+    TallyIncomplete,
+    #[msg("User is not on the legacy scoreboard")]
+    NotOnScoreboard,
+    #[msg("Super raffle points epoch not closed")]
+    EpochNotClosed,
+}
+#[error_code]
+pub enum PaymentError {
+    #[msg("Payment mint is not whitelisted")]
+    MintNotWhitelisted,
+    #[msg("Raffle already takes a different payment mint")]
+    WrongPaymentMint,
+    #[msg("Payment accounts missing or invalid")]
+    InvalidPaymentAccounts,
+}
+#[error_code]
+pub enum ListingError {
+    #[msg("Listing book is full")]
+    BookFull,
+    #[msg("No listing's ask is met by the pot")]
+    NoListingSatisfied,
+    #[msg("A cheaper or earlier listing is met by the pot")]
+    NotBestListing,
+    #[msg("A listing's ask is met by the pot, settle it instead")]
+    ListingMet,
+}
+#[error_code]
+pub enum WorkError {
+    #[msg("Work queue is full")]
+    QueueFull,
+}
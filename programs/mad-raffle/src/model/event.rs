@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct TicketPurchased {
+    pub raffle_id: u64,
+    pub buyer: Pubkey,
+    pub receipt: Pubkey,
+    pub quantity: u8,
+    /// Index of the first ticket in this purchase
+    pub start: u32,
+    /// Mint paid in, None for lamports
+    pub payment_mint: Option<Pubkey>,
+    /// Amount added to the raffle pot, in `payment_mint` units or lamports
+    pub ticket_price: u64,
+    pub ticket_fee: u64,
+    pub super_raffle_fee: u64,
+    pub total_tickets: u32,
+}
+
+#[event]
+pub struct RaffleEnded {
+    pub raffle_id: u64,
+    pub seller: Pubkey,
+    /// Asset id for a compressed NFT
+    pub nft_mint: Pubkey,
+    pub total_tickets: u32,
+    pub payment_to_seller: u64,
+    pub royalties_paid: u64,
+    /// Escrowed SPL mint paid out alongside the lamports
+    pub payment_mint: Option<Pubkey>,
+    pub token_payment_to_seller: u64,
+    pub token_royalties_paid: u64,
+    pub next_raffle_id: u64,
+}
+
+#[event]
+pub struct RoyaltyPaid {
+    pub raffle_id: u64,
+    pub nft_mint: Pubkey,
+    pub creator: Pubkey,
+    /// Mint paid in, None for lamports
+    pub payment_mint: Option<Pubkey>,
+    pub amount: u64,
+}
+
+/// Paid from a raffle to the keeper of a permissionless instruction.
+#[event]
+pub struct CrankRewardPaid {
+    pub raffle_id: u64,
+    pub cranker: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct WinnerPicked {
+    pub raffle_id: u64,
+    pub winner: Pubkey,
+    /// Index of the prize won in `raffle.prizes`
+    pub slot: u8,
+    /// Receipt holding the winning ticket
+    pub receipt: Pubkey,
+    pub total_tickets: u32,
+}
+
+/// An NFT sold into a raffle that still collects more prizes. The last one
+/// emits `RaffleEnded` instead.
+#[event]
+pub struct PrizeAdded {
+    pub raffle_id: u64,
+    pub seller: Pubkey,
+    /// Asset id for a compressed NFT
+    pub nft_mint: Pubkey,
+    /// Index of the prize in `raffle.prizes`
+    pub slot: u8,
+    pub payment_to_seller: u64,
+    pub royalties_paid: u64,
+    pub token_payment_to_seller: u64,
+    pub token_royalties_paid: u64,
+}
+
+#[event]
+pub struct PrizeDistributed {
+    pub raffle_id: u64,
+    /// Index of the prize in `raffle.prizes`
+    pub slot: u8,
+    pub winner: Pubkey,
+    /// Asset id for a compressed NFT
+    pub nft_mint: Pubkey,
+    /// Token account the prize went to, or the winner for a compressed NFT
+    pub destination: Pubkey,
+    /// Signer that sent the prize, the winner or the program authority
+    pub distributor: Pubkey,
+}
+
+/// A winner let the claim deadline of a prize pass, see `redraw_unclaimed`.
+#[event]
+pub struct PrizeUnclaimed {
+    pub raffle_id: u64,
+    /// Index of the prize in `raffle.prizes`
+    pub slot: u8,
+    pub lapsed_winner: Pubkey,
+    /// Whether the prize goes to the active raffle instead of being re-drawn
+    pub rolled_over: bool,
+}
+
+#[event]
+pub struct PointsAwarded {
+    pub user: Pubkey,
+    /// Points after the early raffle multiplier
+    pub points: u32,
+    pub multiplier: u32,
+    pub epoch: u32,
+    /// The user's points for the current season
+    pub season_points: u32,
+}
+
+#[event]
+pub struct RandomnessRecommitted {
+    pub raffle_id: u64,
+    /// Winners are drawn from the hash of the first slot after this one,
+    /// revealed by anyone with `PUBLIC_REVEAL_SECRET`
+    pub end_slot: u64,
+}
+
+#[event]
+pub struct NftListed {
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+    pub ask: u64,
+    pub seq: u64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+}
+
+/// A listing pushed out of the full book by a lower ask. Its NFT stays
+/// escrowed until the seller cancels the listing.
+#[event]
+pub struct ListingEvicted {
+    pub nft_mint: Pubkey,
+}
+
+#[event]
+pub struct ListingSettled {
+    pub raffle_id: u64,
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+    pub ask: u64,
+    pub payment_to_seller: u64,
+    /// Signer that settled the listing
+    pub settler: Pubkey,
+}
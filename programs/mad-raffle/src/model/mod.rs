@@ -1,2 +1,4 @@
 pub mod error;
-pub use error::*;
\ No newline at end of file
+pub mod event;
+pub use error::*;
+pub use event::*;
\ No newline at end of file
@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::MetadataAccount;
+use mpl_token_metadata::state::Collection;
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::{COLLECTION_SEED, POINTS_FOR_SELLING};
+use crate::model::PnftError;
+use crate::state::ProgramConfig;
+use crate::utils::CompressedAsset;
+
+/// A collection whose NFTs can be sold into raffles, one PDA per collection.
+/// Set by the authority via `set_collection`. `config.collection_address`
+/// is accepted with the default settings unless it has its own.
+///
+/// Instructions take the `[COLLECTION_SEED, collection]` PDA of the NFT's
+/// collection whether or not it exists, so its settings can't be skipped.
+#[account]
+pub struct CollectionSettings {
+    pub bump: u8,
+    pub collection: Pubkey,
+    pub enabled: bool,
+    /// Points the seller earns for selling an NFT of the collection
+    pub points_for_selling: u32,
+    pub royalty_policy: RoyaltyPolicy,
+}
+
+/// How much of the pot goes to the creators of a sold NFT.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RoyaltyPolicy {
+    /// The NFT's `seller_fee_basis_points`
+    Metadata,
+    /// A flat rate for the collection, 0 waives royalties
+    Fixed { basis_points: u16 },
+}
+
+impl CollectionSettings {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // collection
+        1 + // enabled
+        4 + // points for selling
+        1 + 2 // royalty policy
+    }
+    /// Settings of the default collection when it has no account of its own.
+    pub fn default_for(collection: Pubkey) -> Self {
+        CollectionSettings {
+            bump: 0,
+            collection,
+            enabled: true,
+            points_for_selling: POINTS_FOR_SELLING,
+            royalty_policy: RoyaltyPolicy::Metadata,
+        }
+    }
+    /// Settings for the verified collection of `metadata`, failing unless the
+    /// collection is the default one or enabled by `settings`.
+    pub fn for_nft(
+        metadata: &MetadataAccount,
+        config: &ProgramConfig,
+        settings: &AccountInfo,
+    ) -> Result<CollectionSettings> {
+        let collection = metadata.collection.as_ref();
+        Self::for_collection(collection, config.collection_address, Self::load(collection, settings)?.as_ref())
+    }
+    /// Same as `for_nft`, for a compressed NFT.
+    pub fn for_compressed_nft(
+        asset: &CompressedAsset,
+        config: &ProgramConfig,
+        settings: &AccountInfo,
+    ) -> Result<CollectionSettings> {
+        let collection = asset.collection();
+        Self::for_collection(collection.as_ref(), config.collection_address, Self::load(collection.as_ref(), settings)?.as_ref())
+    }
+    /// Same as `for_nft`, but also for a disabled collection, so NFTs listed
+    /// before it was disabled can still be settled.
+    pub fn for_listed_nft(
+        metadata: &MetadataAccount,
+        config: &ProgramConfig,
+        settings: &AccountInfo,
+    ) -> Result<CollectionSettings> {
+        let collection = metadata.collection.as_ref();
+        Self::matching(collection, config.collection_address, Self::load(collection, settings)?.as_ref())
+    }
+    /// The settings stored at `settings`, which must be the PDA of `collection`.
+    /// `None` until `set_collection` creates it.
+    fn load(collection: Option<&Collection>, settings: &AccountInfo) -> Result<Option<CollectionSettings>> {
+        let collection = match collection {
+            Some(collection) => collection,
+            // `matching` fails without a collection anyway
+            None => return Ok(None),
+        };
+        let (pda, _) = Pubkey::find_program_address(
+            &[COLLECTION_SEED.as_ref(), collection.key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(settings.key(), pda, PnftError::InvalidCollectionSettings);
+        if settings.owner != &crate::ID || settings.data_is_empty() {
+            return Ok(None);
+        }
+        let data = settings.try_borrow_data()?;
+        Ok(Some(CollectionSettings::try_deserialize(&mut data.as_ref())?))
+    }
+    fn for_collection(
+        collection: Option<&Collection>,
+        default_collection: Pubkey,
+        settings: Option<&CollectionSettings>,
+    ) -> Result<CollectionSettings> {
+        let settings = Self::matching(collection, default_collection, settings)?;
+        require!(settings.enabled, PnftError::CollectionDisabled);
+        Ok(settings)
+    }
+    fn matching(
+        collection: Option<&Collection>,
+        default_collection: Pubkey,
+        settings: Option<&CollectionSettings>,
+    ) -> Result<CollectionSettings> {
+        let collection = collection.ok_or(PnftError::MissingCollection)?;
+        require!(collection.verified, PnftError::NotVerifiedByCollection);
+        let settings = match settings {
+            Some(settings) => settings.clone(),
+            None => Self::default_for(default_collection),
+        };
+        require_keys_eq!(settings.collection, collection.key, PnftError::InvalidCollectionAddress);
+        Ok(settings)
+    }
+    /// Royalty rate paid out of the pot for an NFT with `seller_fee_basis_points`.
+    pub fn royalty_bps(&self, seller_fee_basis_points: u16) -> u16 {
+        match self.royalty_policy {
+            RoyaltyPolicy::Metadata => seller_fee_basis_points,
+            RoyaltyPolicy::Fixed { basis_points } => basis_points,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verified(key: Pubkey) -> Collection {
+        Collection { verified: true, key }
+    }
+
+    fn partner(collection: Pubkey, enabled: bool) -> CollectionSettings {
+        CollectionSettings {
+            bump: 0,
+            collection,
+            enabled,
+            points_for_selling: 25,
+            royalty_policy: RoyaltyPolicy::Fixed { basis_points: 0 },
+        }
+    }
+
+    #[test]
+    fn default_collection_needs_no_settings() {
+        let default = Pubkey::new_unique();
+        let settings = CollectionSettings::for_collection(Some(&verified(default)), default, None).unwrap();
+        assert_eq!(settings.points_for_selling, POINTS_FOR_SELLING);
+        assert_eq!(settings.royalty_bps(500), 500);
+
+        let unverified = Collection { verified: false, key: default };
+        assert_eq!(
+            CollectionSettings::for_collection(Some(&unverified), default, None).err(),
+            Some(PnftError::NotVerifiedByCollection.into())
+        );
+        assert_eq!(
+            CollectionSettings::for_collection(None, default, None).err(),
+            Some(PnftError::MissingCollection.into())
+        );
+    }
+
+    #[test]
+    fn other_collections_need_enabled_settings() {
+        let default = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            CollectionSettings::for_collection(Some(&verified(other)), default, None).err(),
+            Some(PnftError::InvalidCollectionAddress.into())
+        );
+        assert_eq!(
+            CollectionSettings::for_collection(Some(&verified(other)), default, Some(&partner(default, true))).err(),
+            Some(PnftError::InvalidCollectionAddress.into())
+        );
+        assert_eq!(
+            CollectionSettings::for_collection(Some(&verified(other)), default, Some(&partner(other, false))).err(),
+            Some(PnftError::CollectionDisabled.into())
+        );
+
+        let settings = CollectionSettings::for_collection(Some(&verified(other)), default, Some(&partner(other, true))).unwrap();
+        assert_eq!(settings.points_for_selling, 25);
+        assert_eq!(settings.royalty_bps(500), 0);
+    }
+
+    #[test]
+    fn settings_must_be_the_collection_pda() {
+        let collection = verified(Pubkey::new_unique());
+        let (pda, _) = Pubkey::find_program_address(&[COLLECTION_SEED.as_ref(), collection.key.as_ref()], &crate::ID);
+        let (mut lamports, mut data) = (0, vec![]);
+        let system = Pubkey::default();
+        let missing = AccountInfo::new(&pda, false, false, &mut lamports, &mut data, &system, false, 0);
+        assert!(CollectionSettings::load(Some(&collection), &missing).unwrap().is_none());
+
+        let mut stored = vec![];
+        partner(collection.key, false).try_serialize(&mut stored).unwrap();
+        let mut lamports = 1;
+        let existing = AccountInfo::new(&pda, false, false, &mut lamports, &mut stored, &crate::ID, false, 0);
+        let settings = CollectionSettings::load(Some(&collection), &existing).unwrap().unwrap();
+        assert!(!settings.enabled);
+
+        let other = Pubkey::new_unique();
+        let (mut lamports, mut data) = (0, vec![]);
+        let wrong = AccountInfo::new(&other, false, false, &mut lamports, &mut data, &system, false, 0);
+        assert_eq!(
+            CollectionSettings::load(Some(&collection), &wrong).err(),
+            Some(PnftError::InvalidCollectionSettings.into())
+        );
+    }
+}
@@ -0,0 +1,297 @@
+use std::str::FromStr;
+
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::{
+    AUTHORITY, COLLECTION_ADDRESS, FEE_VAULT, MAX_RAFFLE_DURATION, MAX_TICKETS_PER_USER, NEW_RAFFLE_COST,
+    RAFFLE_DURATION, MIN_RAFFLE_DURATION, MIN_TICKETS, PRIZES_PER_RAFFLE, MAX_PRIZES, CLAIM_WINDOW, CRANK_REWARD,
+    MAX_CRANK_REWARD,
+    SOL_PRICE_FEED, STALENESS_THRESHOLD, MAX_PRICE_CONFIDENCE_BPS, USD_TICKET_PRICE, SUPER_RAFFLE_FEE, TICKET_FEE, TICKET_PRICE,
+};
+use crate::model::ConfigError;
+
+/// Program wide settings, editable by the authority via `update_config`.
+/// The authority only needs to sign, so it can be a PDA (e.g. a multisig
+/// vault) signing through CPI. It is rotated with `propose_authority` and
+/// `accept_authority`.
+#[account]
+pub struct ProgramConfig {
+    pub bump: u8,
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub fee_vault: Pubkey,
+    pub collection_address: Pubkey,
+    pub sol_price_feed: Pubkey,
+    pub ticket_price: u64,
+    pub ticket_fee: u64,
+    pub super_raffle_fee: u64,
+    pub new_raffle_cost: u64,
+    pub max_tickets_per_user: u8,
+    pub staleness_threshold: u64,
+    pub max_raffle_duration: i64,
+    pub max_confidence_bps: u16,
+    pub price_mode: PriceMode,
+    /// Ticket price in USD cents, charged in lamports at the SOL feed price
+    /// when `price_mode` is `UsdCents`
+    pub usd_ticket_price: u64,
+    /// Seconds after creation new raffles stop selling tickets, 0 for open ended
+    pub raffle_duration: i64,
+    /// Seconds after creation before new raffles can be ended
+    pub min_raffle_duration: i64,
+    pub min_tickets: u32,
+    /// Prizes new raffles collect before they end, each drawn a winner
+    pub prizes_per_raffle: u8,
+    /// Seconds winners of new raffles have to claim a prize before it can be
+    /// re-drawn, 0 for no deadline
+    pub claim_window: i64,
+    /// Lamports new raffles hold back from the pot to pay whoever draws or
+    /// sends each of their prizes
+    pub crank_reward: u64,
+}
+
+/// How `ticket_price` is denominated for new raffles.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PriceMode {
+    /// Flat `ticket_price` in lamports
+    Lamports,
+    /// `usd_ticket_price` in cents, converted with `sol_price_feed`
+    UsdCents,
+}
+
+/// Fields to change in `update_config`, None leaves the current value.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigUpdate {
+    pub fee_vault: Option<Pubkey>,
+    pub collection_address: Option<Pubkey>,
+    pub sol_price_feed: Option<Pubkey>,
+    pub ticket_price: Option<u64>,
+    pub ticket_fee: Option<u64>,
+    pub super_raffle_fee: Option<u64>,
+    pub new_raffle_cost: Option<u64>,
+    pub max_tickets_per_user: Option<u8>,
+    pub staleness_threshold: Option<u64>,
+    pub max_raffle_duration: Option<i64>,
+    pub max_confidence_bps: Option<u16>,
+    pub price_mode: Option<PriceMode>,
+    pub usd_ticket_price: Option<u64>,
+    pub raffle_duration: Option<i64>,
+    pub min_raffle_duration: Option<i64>,
+    pub min_tickets: Option<u32>,
+    pub prizes_per_raffle: Option<u8>,
+    pub claim_window: Option<i64>,
+    pub crank_reward: Option<u64>,
+}
+
+impl ProgramConfig {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // authority
+        1 + // option
+        32 + // pending authority
+        32 + // fee vault
+        32 + // collection address
+        32 + // sol price feed
+        8 + // ticket price
+        8 + // ticket fee
+        8 + // super raffle fee
+        8 + // new raffle cost
+        1 + // max tickets per user
+        8 + // staleness threshold
+        8 + // max raffle duration
+        2 + // max confidence bps
+        1 + // price mode
+        8 + // usd ticket price
+        8 + // raffle duration
+        8 + // min raffle duration
+        4 + // min tickets
+        1 + // prizes per raffle
+        8 + // claim window
+        8   // crank reward
+    }
+    /// Defaults to the compiled in constants.
+    pub fn initialize(&mut self, bump: u8) {
+        *self = ProgramConfig {
+            bump,
+            authority: Pubkey::from_str(AUTHORITY).unwrap(),
+            pending_authority: None,
+            fee_vault: Pubkey::from_str(FEE_VAULT).unwrap(),
+            collection_address: Pubkey::from_str(COLLECTION_ADDRESS).unwrap(),
+            sol_price_feed: Pubkey::from_str(SOL_PRICE_FEED).unwrap(),
+            ticket_price: TICKET_PRICE,
+            ticket_fee: TICKET_FEE,
+            super_raffle_fee: SUPER_RAFFLE_FEE,
+            new_raffle_cost: NEW_RAFFLE_COST,
+            max_tickets_per_user: MAX_TICKETS_PER_USER,
+            staleness_threshold: STALENESS_THRESHOLD,
+            max_raffle_duration: MAX_RAFFLE_DURATION,
+            max_confidence_bps: MAX_PRICE_CONFIDENCE_BPS,
+            price_mode: PriceMode::Lamports,
+            usd_ticket_price: USD_TICKET_PRICE,
+            raffle_duration: RAFFLE_DURATION,
+            min_raffle_duration: MIN_RAFFLE_DURATION,
+            min_tickets: MIN_TICKETS,
+            prizes_per_raffle: PRIZES_PER_RAFFLE,
+            claim_window: CLAIM_WINDOW,
+            crank_reward: CRANK_REWARD,
+        };
+    }
+    pub fn update(&mut self, update: ConfigUpdate) -> Result<()> {
+        if let Some(fee_vault) = update.fee_vault {
+            self.fee_vault = fee_vault;
+        }
+        if let Some(collection_address) = update.collection_address {
+            self.collection_address = collection_address;
+        }
+        if let Some(sol_price_feed) = update.sol_price_feed {
+            self.sol_price_feed = sol_price_feed;
+        }
+        if let Some(ticket_price) = update.ticket_price {
+            require!(ticket_price > 0, ConfigError::InvalidTicketPrice);
+            self.ticket_price = ticket_price;
+        }
+        if let Some(ticket_fee) = update.ticket_fee {
+            self.ticket_fee = ticket_fee;
+        }
+        if let Some(super_raffle_fee) = update.super_raffle_fee {
+            self.super_raffle_fee = super_raffle_fee;
+        }
+        if let Some(new_raffle_cost) = update.new_raffle_cost {
+            self.new_raffle_cost = new_raffle_cost;
+        }
+        if let Some(max_tickets_per_user) = update.max_tickets_per_user {
+            require!(max_tickets_per_user > 0, ConfigError::InvalidMaxTickets);
+            self.max_tickets_per_user = max_tickets_per_user;
+        }
+        if let Some(staleness_threshold) = update.staleness_threshold {
+            self.staleness_threshold = staleness_threshold;
+        }
+        if let Some(max_raffle_duration) = update.max_raffle_duration {
+            require!(max_raffle_duration >= 0, ConfigError::InvalidMaxDuration);
+            self.max_raffle_duration = max_raffle_duration;
+        }
+        if let Some(max_confidence_bps) = update.max_confidence_bps {
+            require!(
+                max_confidence_bps > 0 && max_confidence_bps <= 10_000,
+                ConfigError::InvalidConfidence
+            );
+            self.max_confidence_bps = max_confidence_bps;
+        }
+        if let Some(price_mode) = update.price_mode {
+            self.price_mode = price_mode;
+        }
+        if let Some(usd_ticket_price) = update.usd_ticket_price {
+            require!(usd_ticket_price > 0, ConfigError::InvalidTicketPrice);
+            self.usd_ticket_price = usd_ticket_price;
+        }
+        if let Some(raffle_duration) = update.raffle_duration {
+            self.raffle_duration = raffle_duration;
+        }
+        if let Some(min_raffle_duration) = update.min_raffle_duration {
+            self.min_raffle_duration = min_raffle_duration;
+        }
+        if let Some(min_tickets) = update.min_tickets {
+            require!(min_tickets > 0, ConfigError::InvalidMinTickets);
+            self.min_tickets = min_tickets;
+        }
+        if let Some(prizes_per_raffle) = update.prizes_per_raffle {
+            require!(
+                prizes_per_raffle > 0 && prizes_per_raffle as usize <= MAX_PRIZES,
+                ConfigError::InvalidPrizeCount
+            );
+            self.prizes_per_raffle = prizes_per_raffle;
+        }
+        if let Some(claim_window) = update.claim_window {
+            require!(claim_window >= 0, ConfigError::InvalidClaimWindow);
+            self.claim_window = claim_window;
+        }
+        if let Some(crank_reward) = update.crank_reward {
+            require!(crank_reward <= MAX_CRANK_REWARD, ConfigError::InvalidCrankReward);
+            self.crank_reward = crank_reward;
+        }
+        // Raffles must be endable before they stop selling
+        require!(
+            self.raffle_duration >= 0
+                && self.min_raffle_duration >= 0
+                && (self.raffle_duration == 0 || self.min_raffle_duration <= self.raffle_duration),
+            ConfigError::InvalidDuration
+        );
+        Ok(())
+    }
+    /// The USD price new raffles are pegged to, None when priced in lamports.
+    pub fn usd_ticket_price(&self) -> Option<u64> {
+        match self.price_mode {
+            PriceMode::Lamports => None,
+            PriceMode::UsdCents => Some(self.usd_ticket_price),
+        }
+    }
+    pub fn propose_authority(&mut self, new_authority: Option<Pubkey>) {
+        self.pending_authority = new_authority;
+    }
+    pub fn accept_authority(&mut self, new_authority: &Pubkey) -> Result<()> {
+        match self.pending_authority {
+            Some(pending) if pending == *new_authority => {
+                self.authority = pending;
+                self.pending_authority = None;
+                Ok(())
+            }
+            Some(_) => err!(ConfigError::NotPendingAuthority),
+            None => err!(ConfigError::NoPendingAuthority),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> ProgramConfig {
+        // Zeroed account data, as the `initialize` instruction gets it
+        let mut config = ProgramConfig::deserialize(&mut &[0; 512][..]).unwrap();
+        config.initialize(0);
+        config
+    }
+
+    #[test]
+    fn updates_only_given_fields() {
+        let mut config = config();
+        config.update(ConfigUpdate { crank_reward: Some(MAX_CRANK_REWARD), ..Default::default() }).unwrap();
+        assert_eq!(config.crank_reward, MAX_CRANK_REWARD);
+        assert_eq!(config.ticket_price, TICKET_PRICE);
+        assert_eq!(config.prizes_per_raffle, PRIZES_PER_RAFFLE);
+    }
+
+    #[test]
+    fn rejects_crank_reward_above_max() {
+        let update = ConfigUpdate { crank_reward: Some(MAX_CRANK_REWARD + 1), ..Default::default() };
+        assert_eq!(config().update(update).err(), Some(ConfigError::InvalidCrankReward.into()));
+    }
+
+    #[test]
+    fn rejects_invalid_updates() {
+        let rejected = [
+            (ConfigUpdate { ticket_price: Some(0), ..Default::default() }, ConfigError::InvalidTicketPrice),
+            (ConfigUpdate { usd_ticket_price: Some(0), ..Default::default() }, ConfigError::InvalidTicketPrice),
+            (ConfigUpdate { max_tickets_per_user: Some(0), ..Default::default() }, ConfigError::InvalidMaxTickets),
+            (ConfigUpdate { max_raffle_duration: Some(-1), ..Default::default() }, ConfigError::InvalidMaxDuration),
+            (ConfigUpdate { max_confidence_bps: Some(0), ..Default::default() }, ConfigError::InvalidConfidence),
+            (ConfigUpdate { max_confidence_bps: Some(10_001), ..Default::default() }, ConfigError::InvalidConfidence),
+            (ConfigUpdate { min_tickets: Some(0), ..Default::default() }, ConfigError::InvalidMinTickets),
+            (ConfigUpdate { prizes_per_raffle: Some(0), ..Default::default() }, ConfigError::InvalidPrizeCount),
+            (
+                ConfigUpdate { prizes_per_raffle: Some(MAX_PRIZES as u8 + 1), ..Default::default() },
+                ConfigError::InvalidPrizeCount,
+            ),
+            (ConfigUpdate { claim_window: Some(-1), ..Default::default() }, ConfigError::InvalidClaimWindow),
+            (ConfigUpdate { raffle_duration: Some(-1), ..Default::default() }, ConfigError::InvalidDuration),
+            (
+                ConfigUpdate { raffle_duration: Some(60), min_raffle_duration: Some(61), ..Default::default() },
+                ConfigError::InvalidDuration,
+            ),
+        ];
+        for (update, error) in rejected {
+            assert_eq!(config().update(update).err(), Some(error.into()));
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::MAX_LISTINGS;
+use crate::model::ListingError;
+use crate::utils::seller_share;
+
+/// An NFT escrowed by `list_nft`, one PDA per mint.
+/// The PDA owns the escrow token account until the listing is settled or cancelled.
+#[account]
+pub struct Listing {
+    pub bump: u8,
+    pub seller: Pubkey,
+    pub nft_mint: Pubkey,
+    /// Fewest lamports the seller accepts from the pot
+    pub ask: u64,
+    /// Position in the listing queue, breaks ties between equal asks
+    pub seq: u64,
+    /// Royalty rate of the NFT when listed, taken out of the pot before the seller
+    pub royalty_bps: u16,
+}
+
+impl Listing {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // seller
+        32 + // nft mint
+        8 + // ask
+        8 + // seq
+        2 // royalty bps
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ListingEntry {
+    pub nft_mint: Pubkey,
+    pub ask: u64,
+    pub royalty_bps: u16,
+    pub seq: u64,
+}
+
+/// Open listings, so `settle_listing` can check it was given the best one.
+#[account]
+pub struct ListingBook {
+    pub bump: u8,
+    pub next_seq: u64,
+    pub listings: Vec<ListingEntry>,
+}
+
+impl ListingBook {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        8 + // next seq
+        4 + // listings vec length
+        MAX_LISTINGS * (
+            32 + // nft mint
+            8 + // ask
+            2 + // royalty bps
+            8 // seq
+        )
+    }
+    /// Queues a listing and returns its sequence number. A full book makes
+    /// room by evicting its highest ask, the latest on ties, as long as `ask`
+    /// is lower, and returns the evicted mint, which can then only be cancelled.
+    pub fn add(&mut self, nft_mint: Pubkey, ask: u64, royalty_bps: u16) -> Result<(u64, Option<Pubkey>)> {
+        let mut evicted = None;
+        if self.listings.len() >= MAX_LISTINGS {
+            let (worst, worst_ask) = self
+                .listings
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, entry)| (entry.ask, entry.seq))
+                .map(|(i, entry)| (i, entry.ask))
+                .ok_or(ListingError::BookFull)?;
+            require!(ask < worst_ask, ListingError::BookFull);
+            evicted = Some(self.listings.swap_remove(worst).nft_mint);
+        }
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.listings.push(ListingEntry {
+            nft_mint,
+            ask,
+            royalty_bps,
+            seq,
+        });
+        Ok((seq, evicted))
+    }
+    pub fn remove(&mut self, nft_mint: &Pubkey) {
+        self.listings.retain(|entry| entry.nft_mint != *nft_mint);
+    }
+    /// The cheapest listing whose ask is met by a pot of `available_balance`
+    /// after royalties, the earliest one on ties.
+    pub fn best(&self, available_balance: u64) -> Option<&ListingEntry> {
+        self.listings
+            .iter()
+            .filter(|entry| seller_share(available_balance, entry.royalty_bps) >= entry.ask)
+            .min_by_key(|entry| (entry.ask, entry.seq))
+    }
+    /// Fails if a listing in the book at `listing_book` takes a pot of
+    /// `available_balance`, which must then be settled with `settle_listing`.
+    /// The book does not exist until the first `list_nft`.
+    pub fn require_none_met(listing_book: &AccountInfo, available_balance: u64) -> Result<()> {
+        if listing_book.owner != &crate::ID || listing_book.data_is_empty() {
+            return Ok(());
+        }
+        let data = listing_book.try_borrow_data()?;
+        let book = ListingBook::try_deserialize(&mut data.as_ref())?;
+        require!(book.best(available_balance).is_none(), ListingError::ListingMet);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(asks: &[(u64, u16)]) -> (ListingBook, Vec<Pubkey>) {
+        let mut book = ListingBook { bump: 0, next_seq: 0, listings: vec![] };
+        let mints = asks
+            .iter()
+            .map(|(ask, bps)| {
+                let mint = Pubkey::new_unique();
+                book.add(mint, *ask, *bps).unwrap();
+                mint
+            })
+            .collect();
+        (book, mints)
+    }
+
+    #[test]
+    fn best_listing_is_cheapest_then_earliest() {
+        let (mut book, mints) = book(&[(300, 0), (100, 0), (100, 0), (50, 0)]);
+        assert_eq!(book.best(1_000).unwrap().nft_mint, mints[3]);
+        assert_eq!(book.best(75).unwrap().nft_mint, mints[3]);
+
+        book.remove(&mints[3]);
+        assert_eq!(book.best(1_000).unwrap().nft_mint, mints[1]);
+        assert_eq!(book.best(75).map(|entry| entry.nft_mint), None);
+    }
+
+    #[test]
+    fn best_listing_accounts_for_royalties() {
+        // A 10% royalty leaves the seller 1_000 of a 1_100 pot
+        let (book, mints) = book(&[(1_000, 1_000), (1_050, 0)]);
+        assert_eq!(book.best(1_100).unwrap().nft_mint, mints[0]);
+        assert_eq!(book.best(1_099).unwrap().nft_mint, mints[1]);
+        assert_eq!(book.best(1_049).map(|entry| entry.nft_mint), None);
+    }
+
+    #[test]
+    fn book_is_capped() {
+        let (mut book, _) = book(&vec![(1, 0); MAX_LISTINGS]);
+        assert_eq!(book.add(Pubkey::new_unique(), 1, 0).err(), Some(ListingError::BookFull.into()));
+    }
+
+    #[test]
+    fn full_book_evicts_highest_ask() {
+        let mut asks = vec![(100, 0); MAX_LISTINGS];
+        asks[3] = (500, 0);
+        asks[7] = (500, 0);
+        let (mut book, mints) = book(&asks);
+        assert_eq!(book.add(Pubkey::new_unique(), 500, 0).err(), Some(ListingError::BookFull.into()));
+
+        // The latest of the highest asks goes first
+        let (_, evicted) = book.add(Pubkey::new_unique(), 499, 0).unwrap();
+        assert_eq!(evicted, Some(mints[7]));
+        let (_, evicted) = book.add(Pubkey::new_unique(), 499, 0).unwrap();
+        assert_eq!(evicted, Some(mints[3]));
+        assert_eq!(book.listings.len(), MAX_LISTINGS);
+        assert!(book.listings.iter().all(|entry| entry.ask < 500));
+    }
+}
@@ -2,4 +2,27 @@ pub mod raffle;
 pub use raffle::*;
 
 pub mod tracker;
-pub use tracker::*;
\ No newline at end of file
+pub use tracker::*;
+
+pub mod config;
+pub use config::*;
+
+pub mod super_raffle;
+pub use super_raffle::*;
+
+pub mod ticket;
+pub use ticket::*;
+
+pub mod user_stats;
+pub use user_stats::*;
+
+pub mod payment_mint;
+pub use payment_mint::*;
+pub mod listing;
+pub use listing::*;
+
+pub mod collection;
+pub use collection::*;
+
+pub mod work_queue;
+pub use work_queue::*;
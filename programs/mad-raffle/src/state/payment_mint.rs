@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+/// An SPL mint tickets can be bought with, one PDA per mint.
+/// Set by the authority via `set_payment_mint`.
+#[account]
+pub struct PaymentMint {
+    pub bump: u8,
+    pub mint: Pubkey,
+    /// Pyth USD price feed for the mint
+    pub price_feed: Pubkey,
+    pub enabled: bool,
+}
+
+impl PaymentMint {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        32 + // mint
+        32 + // price feed
+        1 // enabled
+    }
+}
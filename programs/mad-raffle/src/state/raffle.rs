@@ -1,6 +1,9 @@
 use anchor_lang::prelude::*;
 use solana_program::{pubkey::Pubkey};
-use crate::utils::{select_winner, pick_winner};
+use crate::constants::{MAX_PRIZES, REVEAL_TIMEOUT, TICKET_PRICE};
+use crate::model::{RaffleError, PaymentError, PrizeError};
+use crate::state::{ProgramConfig, TicketReceipt, WorkTask};
+use crate::utils::{commitment_for, draw_entropy, winner_from_entropy, PUBLIC_REVEAL_SECRET};
 
 #[account]
 pub struct Raffle {
@@ -8,106 +11,449 @@ pub struct Raffle {
     pub version: u8,
     pub bump: u8,
     pub active: bool,
-    pub tickets: Vec<TicketHolder>,
+    pub ticket_count: u32,
+    pub receipt_count: u32,
     pub start_time: i64,
+    /// Scheduled end while active (0 is open ended), then when it actually ended
     pub end_time: i64,
-    pub prize: Option<Prize>,
-    pub winner: Option<Pubkey>
+    /// Prizes sold into the raffle so far, up to `prize_count`, and their winners
+    pub prizes: Vec<PrizeSlot>,
+    pub commitment: Option<[u8; 32]>,
+    pub end_slot: u64,
+    pub randomness_request: Option<RandomnessRequest>,
+    pub ticket_price: u64,
+    pub cancelled: bool,
+    /// SPL mint escrowed alongside lamports, set by the first `buy_ticket_spl`
+    pub payment_mint: Option<Pubkey>,
+    /// When set, tickets cost this many USD cents at the SOL feed price
+    /// instead of `ticket_price`
+    pub usd_ticket_price: Option<u64>,
+    /// Earliest time `end_raffle` is allowed
+    pub min_end_time: i64,
+    /// Tickets needed before `end_raffle` is allowed
+    pub min_tickets: u32,
+    /// Prizes the raffle collects, it ends when the last one is sold into it
+    pub prize_count: u8,
+    /// Distinct ticket buyers
+    pub holder_count: u32,
+    /// Randomness all winners are drawn from, set by the first draw
+    pub draw_seed: Option<[u8; 32]>,
+    /// Draws made from `draw_seed`, including ones skipped for repeat winners
+    pub draw_nonce: u32,
+    /// Seconds a winner has to claim a prize before it can be re-drawn, 0 for
+    /// no deadline
+    pub claim_window: i64,
+    /// Lamports paid to whoever draws or sends a prize, held back from the pot
+    pub crank_reward: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
 pub struct Prize {
     pub mint: Pubkey,
     pub ata: Pubkey,
-    pub sent: bool
+    /// Bubblegum asset id of a compressed NFT prize, which has no mint or
+    /// token account
+    pub asset_id: Option<Pubkey>,
 }
 
+/// A prize and the winner drawn for it, distributed independently of the others.
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
-pub struct TicketHolder {
-    pub user: Pubkey,
-    pub qty: u8,
+pub struct PrizeSlot {
+    pub prize: Prize,
+    /// The raffle the prize was sent to once `rolled_over`
+    pub winner: Option<Pubkey>,
+    pub sent: bool,
+    /// When `redraw_unclaimed` is allowed if the prize is not sent, 0 for never
+    pub claim_deadline: i64,
+    /// Last winner who let the claim deadline pass
+    pub lapsed_winner: Option<Pubkey>,
+    /// Whether the prize goes to the active raffle instead of a ticket holder
+    pub rolled_over: bool,
+}
+
+/// Oracle programs that can supply randomness for a raffle. Only the test
+/// mock so far, deployed builds draw from `commit_randomness` alone.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OracleProvider {
+    /// Local `mock_oracle` program, only accepted with the `mock-oracle` feature.
+    Mock,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RandomnessRequest {
+    pub provider: OracleProvider,
+    pub oracle: Pubkey,
+    pub request_slot: u64,
 }
 
 impl Raffle {
-    const RAFFLE_VERSION:u8 = 1;
-    pub fn get_space(ticket_holder_count: usize) -> usize {
+    const RAFFLE_VERSION:u8 = 11;
+    pub fn get_space() -> usize {
         8 + // discriminator
         8 + // id
         1 + // version
         1 + // bump
         1 + // active
-        4 + // vec minimium 
-        (TicketHolder::get_space() * (ticket_holder_count)) + // tickets
+        4 + // ticket count
+        4 + // receipt count
         8 + // start time
         8 + // end time
+        4 + MAX_PRIZES * PrizeSlot::get_space() + // prizes
+        1 + // option
+        32 + // commitment
+        8 + // end slot
         1 + // option
-        (Prize::get_space()) + // prize
+        RandomnessRequest::get_space() + // randomness request
+        8 + // ticket price
+        1 + // cancelled
         1 + // option
-        32  // winner
+        32 + // payment mint
+        1 + // option
+        8 + // usd ticket price
+        8 + // min end time
+        4 + // min tickets
+        1 + // prize count
+        4 + // holder count
+        1 + // option
+        32 + // draw seed
+        4 + // draw nonce
+        8 + // claim window
+        8   // crank reward
     }
-    pub fn initialize(&mut self, raffle_id: u64, bump: u8) {
+    pub fn initialize(&mut self, raffle_id: u64, bump: u8, config: &ProgramConfig) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
         self.id = raffle_id;
         self.active = true;
-        self.start_time = Clock::get().unwrap().unix_timestamp;
-        self.tickets = Vec::new();
-        self.end_time = 0;
+        self.start_time = now;
+        self.ticket_count = 0;
+        self.receipt_count = 0;
+        self.payment_mint = None;
         self.version = Raffle::RAFFLE_VERSION;
         self.bump = bump;
+        self.apply_config(config);
+        Ok(())
+    }
+    /// Prices and schedule are fixed for the life of the raffle, even if
+    /// the config changes mid-raffle. The schedule runs from `start_time`.
+    pub fn apply_config(&mut self, config: &ProgramConfig) {
+        self.ticket_price = config.ticket_price;
+        self.usd_ticket_price = config.usd_ticket_price();
+        self.end_time = if config.raffle_duration > 0 {
+            self.start_time.saturating_add(config.raffle_duration)
+        } else {
+            0
+        };
+        self.min_end_time = self.start_time.saturating_add(config.min_raffle_duration);
+        self.min_tickets = config.min_tickets;
+        self.prize_count = config.prizes_per_raffle;
+        self.claim_window = config.claim_window;
+        self.crank_reward = config.crank_reward;
+    }
+    /// Adds the NFT the raffle now holds in `ata` as a prize. Returns whether
+    /// it was the last one, which ends the raffle.
+    pub fn add_prize(&mut self, mint: Pubkey, ata: Pubkey) -> Result<bool> {
+        self.add_prize_slot(Prize {
+            mint,
+            ata,
+            asset_id: None,
+        })
     }
-    pub fn end_raffle(&mut self, mint: Pubkey, ata: Pubkey) {
+    /// Same as `add_prize`, for the compressed NFT `asset_id` now owned by the raffle.
+    pub fn add_compressed_prize(&mut self, asset_id: Pubkey) -> Result<bool> {
+        self.add_prize_slot(Prize {
+            asset_id: Some(asset_id),
+            ..Prize::default()
+        })
+    }
+    fn add_prize_slot(&mut self, prize: Prize) -> Result<bool> {
+        require!(self.open_prize_slots() > 0, RaffleError::NotActive);
+        self.prizes.push(PrizeSlot {
+            prize,
+            ..PrizeSlot::default()
+        });
+        if self.open_prize_slots() > 0 {
+            return Ok(false);
+        }
         self.active = false;
-        self.end_time = Clock::get().unwrap().unix_timestamp;
-        self.prize = Some(Prize {
+        let clock = Clock::get()?;
+        self.end_time = clock.unix_timestamp;
+        self.end_slot = clock.slot;
+        Ok(true)
+    }
+    /// Adds a prize rolled over from an earlier raffle, now held in `ata`, on
+    /// top of the ones the raffle collects, so it takes no part of the pot.
+    pub fn add_rolled_over_prize(&mut self, mint: Pubkey, ata: Pubkey) -> Result<()> {
+        self.add_bonus_slot(Prize {
             mint,
             ata,
-            sent:false
+            asset_id: None,
+        })
+    }
+    /// Same as `add_rolled_over_prize`, for a compressed NFT.
+    pub fn add_rolled_over_compressed_prize(&mut self, asset_id: Pubkey) -> Result<()> {
+        self.add_bonus_slot(Prize {
+            asset_id: Some(asset_id),
+            ..Prize::default()
+        })
+    }
+    fn add_bonus_slot(&mut self, prize: Prize) -> Result<()> {
+        require!(self.active, RaffleError::NotActive);
+        require!(self.prizes.len() < MAX_PRIZES, PrizeError::TooManyPrizes);
+        self.prize_count += 1;
+        self.prizes.push(PrizeSlot {
+            prize,
+            ..PrizeSlot::default()
         });
+        Ok(())
     }
-    pub fn buy_ticket(&mut self, buyer: &Pubkey) {
-        match self
-            .tickets
-            .iter_mut()
-            .find(|ticket_holder| ticket_holder.user == *buyer)
-        {
-            Some(ticket_holder) => ticket_holder.qty += 1,
-            None => self.tickets.push(TicketHolder {
-                user: *buyer,
-                qty: 1,
-            }),
-        }
+    /// Lamports the pot holds back for the crank rewards, one draw and one
+    /// send per prize.
+    pub fn crank_reserve(&self) -> u64 {
+        self.crank_reward.saturating_mul(2 * self.prize_count as u64)
     }
-    pub fn select_winner(&mut self, random: Pubkey) {
-        match select_winner(self, random) {
-            Ok(winner) => {
-                self.winner = Some(winner);
-                msg!("The winner is {:?}", self.winner);
-            }
-            Err(e) => {
-                msg!("Error selecting winner: {:?}", e);
+    /// Prizes still to be sold into the raffle, each due an equal part of what
+    /// is left of the pot.
+    pub fn open_prize_slots(&self) -> u64 {
+        (self.prize_count as u64).saturating_sub(self.prizes.len() as u64)
+    }
+    pub fn cancel(&mut self) -> Result<()> {
+        require!(self.prizes.is_empty(), RaffleError::HasPrizes);
+        self.cancelled = true;
+        self.close_without_prize()
+    }
+    /// Ends the raffle without a prize.
+    pub fn close_without_prize(&mut self) -> Result<()> {
+        require!(self.prizes.is_empty(), RaffleError::HasPrizes);
+        self.active = false;
+        self.end_time = Clock::get()?.unix_timestamp;
+        Ok(())
+    }
+    /// Ends a raffle that stopped short of `prize_count` with the prizes sold
+    /// into it so far, each drawn as usual. With no tickets to draw from they
+    /// roll over to the next raffle instead.
+    pub fn end_with_prizes(&mut self, now: i64, slot: u64) -> Result<()> {
+        require!(!self.prizes.is_empty(), PrizeError::NoPrizeInRaffle);
+        self.prize_count = self.prizes.len() as u8;
+        self.active = false;
+        self.end_time = now;
+        self.end_slot = slot;
+        if self.ticket_count == 0 {
+            for prize_slot in self.prizes.iter_mut() {
+                prize_slot.rolled_over = true;
             }
         }
+        Ok(())
+    }
+    /// Whether the raffle ended and no prize can be drawn again: each one
+    /// was sent, rolled over or won without a claim deadline to lapse.
+    pub fn draws_settled(&self) -> bool {
+        !self.active
+            && !self.prizes.is_empty()
+            && self.prizes.iter().all(|slot| {
+                slot.sent || slot.rolled_over || (slot.winner.is_some() && slot.claim_deadline == 0)
+            })
     }
-    pub fn pick_winner(&mut self, random: Pubkey, randomness: i64) {
-        match pick_winner(self, random, randomness) {
-            Ok(winner) => {
-                self.winner = Some(winner);
-                msg!("The winner is {:?}", self.winner);
+    /// Whether the scheduled end time has passed, after which no tickets are sold.
+    pub fn is_past_end_time(&self, now: i64) -> bool {
+        self.end_time > 0 && now >= self.end_time
+    }
+    /// Whether `end_raffle` is allowed, checking the minimum duration and tickets.
+    pub fn check_can_end(&self, now: i64) -> Result<()> {
+        require!(now >= self.min_end_time, RaffleError::MinDurationNotReached);
+        require!(self.ticket_count >= self.min_tickets.max(1), RaffleError::NotEnoughTickets);
+        Ok(())
+    }
+    /// A raffle open for `max_duration` seconds or more can be cancelled by anyone.
+    /// A `max_duration` of 0 disables this.
+    pub fn is_expired(&self, max_duration: i64, now: i64) -> bool {
+        max_duration > 0 && now.saturating_sub(self.start_time) >= max_duration
+    }
+    /// A raffle escrows at most one SPL mint, so `end_raffle` and refunds
+    /// only ever need one set of token accounts.
+    pub fn accept_payment_mint(&mut self, mint: Pubkey) -> Result<()> {
+        match self.payment_mint {
+            Some(payment_mint) => require_keys_eq!(payment_mint, mint, PaymentError::WrongPaymentMint),
+            None => self.payment_mint = Some(mint),
+        }
+        Ok(())
+    }
+    /// Issues `quantity` tickets and returns the index of the first one.
+    /// `new_holder` when the buyer had no tickets in the raffle yet.
+    pub fn buy_ticket(&mut self, quantity: u8, new_holder: bool) -> Result<u32> {
+        let start = self.ticket_count;
+        self.ticket_count = start
+            .checked_add(quantity as u32)
+            .ok_or(RaffleError::Overflow)?;
+        self.receipt_count = self
+            .receipt_count
+            .checked_add(1)
+            .ok_or(RaffleError::Overflow)?;
+        if new_holder {
+            self.holder_count = self.holder_count.checked_add(1).ok_or(RaffleError::Overflow)?;
+        }
+        Ok(start)
+    }
+    pub fn commit_randomness(&mut self, commitment: [u8; 32]) {
+        self.commitment = Some(commitment);
+    }
+    pub fn request_randomness(&mut self, provider: OracleProvider, oracle: Pubkey) -> Result<()> {
+        self.randomness_request = Some(RandomnessRequest {
+            provider,
+            oracle,
+            request_slot: Clock::get()?.slot,
+        });
+        Ok(())
+    }
+    /// Replaces a commitment or oracle request that can no longer be drawn
+    /// from, the SlotHashes entry after `end_slot` being evicted, with
+    /// `commitment` against the hash of a slot after `slot`. Only the public
+    /// secret's commitment is taken, `REVEAL_TIMEOUT` after the raffle ended:
+    /// a new operator secret, committed once the expired slot hash showed who
+    /// would have won, would let the operator re-roll the draw.
+    pub fn recommit_randomness(&mut self, commitment: [u8; 32], now: i64, slot: u64) -> Result<()> {
+        require!(!self.active, RaffleError::StillActive);
+        require!(!self.cancelled, RaffleError::RaffleCancelled);
+        require!(self.draw_seed.is_none(), RaffleError::WinnerAlreadySelected);
+        require!(
+            commitment == commitment_for(&PUBLIC_REVEAL_SECRET),
+            RaffleError::SecretRecommitted
+        );
+        require!(
+            now >= self.end_time.saturating_add(REVEAL_TIMEOUT),
+            RaffleError::RevealTimeoutNotReached
+        );
+        self.commitment = Some(commitment);
+        self.randomness_request = None;
+        self.end_slot = slot;
+        Ok(())
+    }
+    pub fn has_randomness_source(&self) -> bool {
+        self.commitment.is_some() || self.randomness_request.is_some()
+    }
+    /// Sets the randomness winners are drawn from, once.
+    pub fn seed_draws(&mut self, seed: [u8; 32]) -> Result<()> {
+        require!(self.draw_seed.is_none(), RaffleError::WinnerAlreadySelected);
+        self.draw_seed = Some(seed);
+        Ok(())
+    }
+    /// Draws the winner of the next prize from the ticket `receipt` holds.
+    /// Tickets are drawn with replacement, but a holder who already won a prize
+    /// (or let one lapse) is skipped, returning None, until every holder has,
+    /// so winners are distinct whenever there are enough of them. Fails the
+    /// transaction on a wrong receipt so the draw can be retried.
+    pub fn draw_winner(&mut self, receipt: &TicketReceipt, now: i64) -> Result<Option<(u8, Pubkey)>> {
+        let seed = self.draw_seed.ok_or(RaffleError::RandomnessNotRevealed)?;
+        let slot = self
+            .prizes
+            .iter()
+            .position(|slot| slot.winner.is_none() && !slot.rolled_over)
+            .ok_or(RaffleError::WinnerAlreadySelected)?;
+        let entropy = draw_entropy(&seed, self.id, self.draw_nonce);
+        let winner = winner_from_entropy(self.ticket_count, receipt, entropy).map_err(|e| {
+            msg!("Error selecting winner: {:?}", e);
+            e
+        })?;
+        self.draw_nonce = self.draw_nonce.checked_add(1).ok_or(RaffleError::Overflow)?;
+        if self.was_drawn(&winner) && self.drawn_holders() < self.holder_count {
+            msg!("{} already drawn, drawing again", winner);
+            return Ok(None);
+        }
+        let prize_slot = &mut self.prizes[slot];
+        prize_slot.winner = Some(winner);
+        prize_slot.claim_deadline = if self.claim_window > 0 {
+            now.saturating_add(self.claim_window)
+        } else {
+            0
+        };
+        msg!("The winner of prize {} is {:?}", slot, winner);
+        Ok(Some((slot as u8, winner)))
+    }
+    /// Takes the prize of `slot` from a winner who let the claim deadline pass.
+    /// It is drawn again among holders who have not been drawn yet, or rolls
+    /// over to the active raffle if there are none or it already lapsed once.
+    /// Returns the lapsed winner and whether the prize rolled over.
+    pub fn lapse_winner(&mut self, slot: u8, now: i64) -> Result<(Pubkey, bool)> {
+        let holder_count = self.holder_count;
+        let drawn_holders = self.drawn_holders();
+        let prize_slot = self
+            .prizes
+            .get_mut(slot as usize)
+            .ok_or(PrizeError::NoPrizeInRaffle)?;
+        let winner = prize_slot.winner.ok_or(RaffleError::WinnerNotSelected)?;
+        require!(!prize_slot.sent, PrizeError::PrizeAlreadySent);
+        require!(
+            prize_slot.claim_deadline > 0 && now >= prize_slot.claim_deadline,
+            PrizeError::ClaimWindowOpen
+        );
+        // The lapsed winner stays drawn, so nobody is left once all holders were
+        let rolled_over = prize_slot.lapsed_winner.is_some() || drawn_holders >= holder_count;
+        prize_slot.winner = None;
+        prize_slot.lapsed_winner = Some(winner);
+        prize_slot.claim_deadline = 0;
+        prize_slot.rolled_over = rolled_over;
+        Ok((winner, rolled_over))
+    }
+    /// Whether `user` won a prize or let one lapse.
+    pub fn was_drawn(&self, user: &Pubkey) -> bool {
+        self.prizes.iter().any(|slot| {
+            (!slot.rolled_over && slot.winner == Some(*user)) || slot.lapsed_winner == Some(*user)
+        })
+    }
+    fn drawn_holders(&self) -> u32 {
+        let mut drawn: Vec<Pubkey> = self
+            .prizes
+            .iter()
+            .flat_map(|slot| [slot.winner.filter(|_| !slot.rolled_over), slot.lapsed_winner])
+            .flatten()
+            .collect();
+        drawn.sort();
+        drawn.dedup();
+        drawn.len() as u32
+    }
+    /// What is left to do for the raffle at `now`: drawing its next winner,
+    /// then sending each drawn or rolled over prize, or re-drawing it once
+    /// the claim deadline passed. A first draw whose slot hash is gone
+    /// (`randomness_expired`) can't run, so it is recommitted instead.
+    pub fn pending_work(&self, now: i64, randomness_expired: bool) -> Vec<WorkTask> {
+        if self.active || self.cancelled {
+            return vec![];
+        }
+        let mut tasks = vec![];
+        if self.prizes.iter().any(|slot| slot.winner.is_none() && !slot.rolled_over) {
+            if self.draw_seed.is_none() && randomness_expired {
+                tasks.push(WorkTask::RecommitRandomness);
+            } else {
+                tasks.push(WorkTask::DrawWinner);
             }
-            Err(e) => {
-                msg!("Error selecting winner: {:?}", e);
+        }
+        for (slot, prize_slot) in self.prizes.iter().enumerate() {
+            let slot = slot as u8;
+            if prize_slot.sent {
+                continue;
+            }
+            if prize_slot.rolled_over {
+                tasks.push(WorkTask::DistributePrize { slot });
+            } else if prize_slot.winner.is_some() {
+                if prize_slot.claim_deadline > 0 && now >= prize_slot.claim_deadline {
+                    tasks.push(WorkTask::RedrawUnclaimed { slot });
+                } else {
+                    tasks.push(WorkTask::DistributePrize { slot });
+                }
             }
         }
+        tasks
     }
     pub fn get_ticket_count(&self) -> u32 {
-        self.tickets.iter().map(|holder| holder.qty as u32).sum()
+        self.ticket_count
     }
     
 }
 
-impl TicketHolder {
+impl RandomnessRequest {
     pub fn get_space() -> usize {
-        32 + // user (Pubkey)
-        1 // qty (u8)
+        1 + // provider (enum)
+        32 + // oracle (Pubkey)
+        8    // request slot (u64)
     }
 }
 
@@ -115,6 +461,409 @@ impl Prize {
     pub fn get_space() -> usize {
         32 + // mint (Pubkey)
         32 + // ata (Pubkey)
-        1    // sent (bool)
+        1 + 32 // asset id (Option<Pubkey>)
+    }
+}
+
+impl PrizeSlot {
+    pub fn get_space() -> usize {
+        Prize::get_space() + // prize
+        1 + 32 + // winner (Option<Pubkey>)
+        1 + // sent (bool)
+        8 + // claim deadline (i64)
+        1 + 32 + // lapsed winner (Option<Pubkey>)
+        1 // rolled over (bool)
     }
-}
\ No newline at end of file
+}
+/// Raffle layout before `Raffle::RAFFLE_VERSION`, read by `migrate_raffle`.
+/// Tickets were held inline instead of in receipt PDAs.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyRaffle {
+    pub id: u64,
+    pub version: u8,
+    pub bump: u8,
+    pub active: bool,
+    pub tickets: Vec<LegacyTicketHolder>,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub prize: Option<LegacyPrize>,
+    pub winner: Option<Pubkey>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyTicketHolder {
+    pub user: Pubkey,
+    pub qty: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct LegacyPrize {
+    pub mint: Pubkey,
+    pub ata: Pubkey,
+    pub sent: bool,
+}
+
+impl LegacyRaffle {
+    pub const VERSION: u8 = 1;
+    /// Offset of `version`, after the discriminator and id
+    pub const VERSION_OFFSET: usize = 16;
+    /// Whether `tickets` still need receipts, i.e. no winner was drawn.
+    pub fn needs_receipts(&self) -> bool {
+        self.winner.is_none() && !self.tickets.is_empty()
+    }
+    /// The raffle in the current layout. Legacy tickets were sold at the
+    /// compiled in price and get one receipt each, in order, if
+    /// `needs_receipts`. An active raffle still takes its prices and schedule
+    /// from `Raffle::apply_config`.
+    pub fn migrate(&self) -> Raffle {
+        Raffle {
+            id: self.id,
+            version: Raffle::RAFFLE_VERSION,
+            bump: self.bump,
+            active: self.active,
+            ticket_count: self.tickets.iter().map(|holder| holder.qty as u32).sum(),
+            receipt_count: if self.needs_receipts() { self.tickets.len() as u32 } else { 0 },
+            start_time: self.start_time,
+            end_time: self.end_time,
+            prizes: self
+                .prize
+                .iter()
+                .map(|prize| PrizeSlot {
+                    prize: Prize {
+                        mint: prize.mint,
+                        ata: prize.ata,
+                        asset_id: None,
+                    },
+                    winner: self.winner,
+                    sent: prize.sent,
+                    ..PrizeSlot::default()
+                })
+                .collect(),
+            commitment: None,
+            end_slot: 0,
+            randomness_request: None,
+            ticket_price: TICKET_PRICE,
+            cancelled: false,
+            payment_mint: None,
+            usd_ticket_price: None,
+            min_end_time: self.start_time,
+            min_tickets: 0,
+            prize_count: 1,
+            holder_count: self.tickets.len() as u32,
+            draw_seed: None,
+            draw_nonce: 0,
+            claim_window: 0,
+            crank_reward: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::winning_ticket_index;
+
+    fn raffle(prize_count: u8, holders: &[u8]) -> (Raffle, Vec<TicketReceipt>) {
+        let mut raffle = Raffle {
+            id: 3,
+            version: Raffle::RAFFLE_VERSION,
+            bump: 255,
+            active: false,
+            ticket_count: 0,
+            receipt_count: 0,
+            start_time: 0,
+            end_time: 0,
+            prizes: vec![PrizeSlot::default(); prize_count as usize],
+            commitment: None,
+            end_slot: 0,
+            randomness_request: None,
+            ticket_price: 0,
+            cancelled: false,
+            payment_mint: None,
+            usd_ticket_price: None,
+            min_end_time: 0,
+            min_tickets: 0,
+            prize_count,
+            holder_count: 0,
+            draw_seed: None,
+            draw_nonce: 0,
+            claim_window: 100,
+            crank_reward: 0,
+        };
+        let receipts = holders
+            .iter()
+            .map(|qty| TicketReceipt {
+                raffle_id: raffle.id,
+                bump: 255,
+                user: Pubkey::new_unique(),
+                start: raffle.buy_ticket(*qty, true).unwrap(),
+                qty: *qty,
+            })
+            .collect();
+        (raffle, receipts)
+    }
+
+    /// Draws every prize the way a client would, finding the receipt off chain.
+    fn draw_all(raffle: &mut Raffle, receipts: &[TicketReceipt]) -> Vec<Pubkey> {
+        if raffle.draw_seed.is_none() {
+            raffle.seed_draws([9; 32]).unwrap();
+        }
+        let mut draws = 0;
+        let mut skipped = 0;
+        let nonce = raffle.draw_nonce;
+        while raffle.prizes.iter().any(|slot| slot.winner.is_none() && !slot.rolled_over) {
+            let entropy = draw_entropy(&[9; 32], raffle.id, raffle.draw_nonce);
+            let index = winning_ticket_index(raffle.ticket_count, entropy).unwrap();
+            let receipt = receipts.iter().find(|r| r.contains(index)).unwrap();
+            match raffle.draw_winner(receipt, 1_000).unwrap() {
+                Some(_) => draws += 1,
+                None => skipped += 1,
+            }
+        }
+        assert_eq!(raffle.draw_nonce, nonce + draws + skipped);
+        raffle.prizes.iter().filter_map(|slot| slot.winner).collect()
+    }
+
+    #[test]
+    fn winners_are_distinct_when_there_are_enough_holders() {
+        let (mut raffle, receipts) = raffle(3, &[20, 1, 1, 1]);
+        let mut winners = draw_all(&mut raffle, &receipts);
+        winners.sort();
+        winners.dedup();
+        assert_eq!(winners.len(), 3);
+    }
+
+    #[test]
+    fn holders_win_again_once_all_have_won() {
+        let (mut raffle, receipts) = raffle(3, &[1, 1]);
+        let winners = draw_all(&mut raffle, &receipts);
+        assert_ne!(winners[0], winners[1]);
+        assert!(receipts.iter().any(|r| r.user == winners[2]));
+        assert_eq!(
+            raffle.draw_winner(&receipts[0], 0).err(),
+            Some(RaffleError::WinnerAlreadySelected.into())
+        );
+    }
+
+    #[test]
+    fn draws_need_the_seed_and_the_drawn_receipt() {
+        let (mut raffle, receipts) = raffle(1, &[1, 1]);
+        assert_eq!(
+            raffle.draw_winner(&receipts[0], 0).err(),
+            Some(RaffleError::RandomnessNotRevealed.into())
+        );
+        raffle.seed_draws([9; 32]).unwrap();
+        assert_eq!(raffle.seed_draws([8; 32]).err(), Some(RaffleError::WinnerAlreadySelected.into()));
+
+        let index = winning_ticket_index(2, draw_entropy(&[9; 32], raffle.id, 0)).unwrap();
+        let wrong = &receipts[1 - index as usize];
+        assert_eq!(raffle.draw_winner(wrong, 0).err(), Some(RaffleError::WrongTicketReceipt.into()));
+        assert_eq!(raffle.draw_nonce, 0);
+    }
+
+    #[test]
+    fn expired_draws_are_recommitted_to_a_later_slot() {
+        let (mut raffle, receipts) = raffle(1, &[1, 1]);
+        let public = commitment_for(&PUBLIC_REVEAL_SECRET);
+        raffle.commit_randomness([1; 32]);
+        raffle.end_slot = 100;
+        let timeout = raffle.end_time + REVEAL_TIMEOUT;
+        assert_eq!(
+            raffle.recommit_randomness(public, timeout - 1, 1_000).err(),
+            Some(RaffleError::RevealTimeoutNotReached.into())
+        );
+        raffle.recommit_randomness(public, timeout, 1_000).unwrap();
+        assert_eq!((raffle.commitment, raffle.end_slot), (Some(public), 1_000));
+
+        draw_all(&mut raffle, &receipts);
+        assert_eq!(
+            raffle.recommit_randomness(public, timeout, 2_000).err(),
+            Some(RaffleError::WinnerAlreadySelected.into())
+        );
+        raffle.active = true;
+        assert_eq!(raffle.recommit_randomness(public, timeout, 2_000).err(), Some(RaffleError::StillActive.into()));
+    }
+
+    #[test]
+    fn expired_draws_cannot_be_recommitted_to_a_new_secret() {
+        let (mut raffle, _) = raffle(1, &[1, 1]);
+        raffle.commit_randomness([1; 32]);
+        let timeout = raffle.end_time + REVEAL_TIMEOUT;
+        for now in [raffle.end_time, timeout] {
+            assert_eq!(
+                raffle.recommit_randomness(commitment_for(&[2; 32]), now, 1_000).err(),
+                Some(RaffleError::SecretRecommitted.into())
+            );
+        }
+        assert_eq!(raffle.commitment, Some([1; 32]));
+    }
+
+    #[test]
+    fn prize_slots_close_as_prizes_are_added() {
+        let (mut raffle, _) = raffle(3, &[]);
+        raffle.prizes.truncate(1);
+        assert_eq!(raffle.open_prize_slots(), 2);
+        raffle.prizes.push(PrizeSlot::default());
+        raffle.prizes.push(PrizeSlot::default());
+        assert_eq!(raffle.open_prize_slots(), 0);
+        assert_eq!(raffle.cancel().err(), Some(RaffleError::HasPrizes.into()));
+    }
+
+    #[test]
+    fn expired_raffles_end_with_the_prizes_they_have() {
+        let (mut raffle, receipts) = raffle(3, &[1, 1]);
+        raffle.active = true;
+        raffle.prizes.clear();
+        assert_eq!(raffle.end_with_prizes(50, 7).err(), Some(PrizeError::NoPrizeInRaffle.into()));
+        assert!(!raffle.add_prize(Pubkey::new_unique(), Pubkey::new_unique()).unwrap());
+
+        raffle.end_with_prizes(50, 7).unwrap();
+        assert!(!raffle.active);
+        assert_eq!((raffle.prize_count, raffle.open_prize_slots()), (1, 0));
+        assert_eq!((raffle.end_time, raffle.end_slot), (50, 7));
+        assert_eq!(draw_all(&mut raffle, &receipts).len(), 1);
+    }
+
+    #[test]
+    fn expired_raffles_without_tickets_roll_their_prizes_over() {
+        let (mut raffle, _) = raffle(2, &[]);
+        raffle.active = true;
+        raffle.prizes.truncate(1);
+        raffle.end_with_prizes(50, 7).unwrap();
+        assert!(raffle.prizes[0].rolled_over);
+        assert_eq!(raffle.pending_work(50, false), vec![WorkTask::DistributePrize { slot: 0 }]);
+    }
+
+    #[test]
+    fn unclaimed_prizes_are_drawn_again_then_rolled_over() {
+        let (mut raffle, receipts) = raffle(1, &[1, 1, 1]);
+        let first = draw_all(&mut raffle, &receipts)[0];
+        assert_eq!(raffle.prizes[0].claim_deadline, 1_100);
+        assert_eq!(raffle.lapse_winner(0, 1_099).err(), Some(PrizeError::ClaimWindowOpen.into()));
+
+        assert_eq!(raffle.lapse_winner(0, 1_100).unwrap(), (first, false));
+        let second = draw_all(&mut raffle, &receipts)[0];
+        assert_ne!(second, first);
+        assert!(raffle.was_drawn(&first));
+
+        assert_eq!(raffle.lapse_winner(0, 1_100).unwrap(), (second, true));
+        let slot = &raffle.prizes[0];
+        assert!(slot.rolled_over && slot.winner.is_none());
+        assert_eq!(slot.lapsed_winner, Some(second));
+        assert_eq!(raffle.lapse_winner(0, 2_000).err(), Some(RaffleError::WinnerNotSelected.into()));
+    }
+
+    #[test]
+    fn unclaimed_prizes_roll_over_once_every_holder_was_drawn() {
+        let (mut raffle, receipts) = raffle(1, &[3]);
+        raffle.claim_window = 0;
+        let winner = draw_all(&mut raffle, &receipts)[0];
+        assert_eq!(raffle.lapse_winner(0, i64::MAX).err(), Some(PrizeError::ClaimWindowOpen.into()));
+
+        raffle.prizes[0].claim_deadline = 1;
+        assert_eq!(raffle.lapse_winner(0, 1).unwrap(), (winner, true));
+        assert!(draw_all(&mut raffle, &receipts).is_empty());
+    }
+
+    #[test]
+    fn tickets_are_released_once_no_prize_can_be_drawn_again() {
+        let (mut raffle, receipts) = raffle(2, &[1, 1, 1]);
+        assert!(!raffle.draws_settled());
+        draw_all(&mut raffle, &receipts);
+        // Either winner can still let the claim deadline lapse
+        assert!(!raffle.draws_settled());
+
+        raffle.prizes[0].sent = true;
+        assert!(!raffle.lapse_winner(1, 1_100).unwrap().1);
+        assert!(!raffle.draws_settled());
+        draw_all(&mut raffle, &receipts);
+        assert!(raffle.lapse_winner(1, 2_000).unwrap().1);
+        assert!(raffle.draws_settled());
+    }
+
+    #[test]
+    fn tickets_are_released_once_won_without_a_claim_window() {
+        let (mut raffle, receipts) = raffle(1, &[1]);
+        raffle.claim_window = 0;
+        draw_all(&mut raffle, &receipts);
+        assert!(raffle.draws_settled());
+    }
+
+    #[test]
+    fn rolled_over_prizes_are_added_on_top() {
+        let (mut raffle, _) = raffle(2, &[]);
+        raffle.prizes.clear();
+        assert_eq!(
+            raffle.add_rolled_over_prize(Pubkey::new_unique(), Pubkey::new_unique()).err(),
+            Some(RaffleError::NotActive.into())
+        );
+        raffle.active = true;
+        raffle.crank_reward = 1_000;
+        assert_eq!(raffle.crank_reserve(), 4_000);
+        raffle.add_rolled_over_compressed_prize(Pubkey::new_unique()).unwrap();
+        assert_eq!((raffle.prize_count, raffle.open_prize_slots()), (3, 2));
+        // Its draw and send are rewarded out of the raffle's pot too
+        assert_eq!(raffle.crank_reserve(), 6_000);
+    }
+
+    #[test]
+    fn pending_work_follows_the_prizes() {
+        let (mut raffle, receipts) = raffle(2, &[1, 1, 1]);
+        raffle.active = true;
+        assert!(raffle.pending_work(0, false).is_empty());
+        raffle.active = false;
+        assert_eq!(raffle.pending_work(0, false), vec![WorkTask::DrawWinner]);
+        assert_eq!(raffle.pending_work(0, true), vec![WorkTask::RecommitRandomness]);
+
+        draw_all(&mut raffle, &receipts);
+        assert_eq!(
+            raffle.pending_work(1_099, false),
+            vec![WorkTask::DistributePrize { slot: 0 }, WorkTask::DistributePrize { slot: 1 }]
+        );
+        raffle.prizes[0].sent = true;
+        assert_eq!(raffle.pending_work(1_100, false), vec![WorkTask::RedrawUnclaimed { slot: 1 }]);
+
+        raffle.lapse_winner(1, 1_100).unwrap();
+        // Re-draws only need the revealed seed
+        assert_eq!(raffle.pending_work(1_100, true), vec![WorkTask::DrawWinner]);
+        raffle.prizes[1].rolled_over = true;
+        assert_eq!(raffle.pending_work(1_100, false), vec![WorkTask::DistributePrize { slot: 1 }]);
+        raffle.prizes[1].sent = true;
+        assert!(raffle.pending_work(1_100, false).is_empty());
+    }
+
+    #[test]
+    fn legacy_raffles_migrate_to_receipts_and_prize_slots() {
+        let holders = vec![
+            LegacyTicketHolder { user: Pubkey::new_unique(), qty: 2 },
+            LegacyTicketHolder { user: Pubkey::new_unique(), qty: 3 },
+        ];
+        let mut legacy = LegacyRaffle {
+            id: 7,
+            version: LegacyRaffle::VERSION,
+            bump: 254,
+            active: true,
+            tickets: holders.clone(),
+            start_time: 100,
+            end_time: 0,
+            prize: None,
+            winner: None,
+        };
+        let data = [[0u8; 8].to_vec(), legacy.try_to_vec().unwrap()].concat();
+        assert_eq!(data[LegacyRaffle::VERSION_OFFSET], LegacyRaffle::VERSION);
+
+        let raffle = legacy.migrate();
+        assert_eq!((raffle.ticket_count, raffle.receipt_count, raffle.holder_count), (5, 2, 2));
+        assert_eq!(raffle.version, Raffle::RAFFLE_VERSION);
+        assert!(raffle.active && raffle.prizes.is_empty());
+
+        // An ended raffle whose winner was drawn only has its prize left to send
+        legacy.active = false;
+        legacy.prize = Some(LegacyPrize { mint: Pubkey::new_unique(), ata: Pubkey::new_unique(), sent: false });
+        legacy.winner = Some(holders[1].user);
+        let raffle = legacy.migrate();
+        assert_eq!(raffle.receipt_count, 0);
+        assert_eq!(raffle.prizes[0].winner, Some(holders[1].user));
+        assert_eq!(raffle.pending_work(0, false), vec![WorkTask::DistributePrize { slot: 0 }]);
+    }
+}
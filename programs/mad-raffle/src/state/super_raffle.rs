@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+use crate::constants::REVEAL_TIMEOUT;
+use crate::model::{RaffleError, SuperRaffleError};
+use crate::state::{Season, UserStats};
+use crate::utils::{commitment_for, PUBLIC_REVEAL_SECRET};
+
+/// Tracks the pending and past super raffle ("skull") draws.
+/// Kept apart from `SuperVault` so the vault layout never changes.
+#[account]
+pub struct SuperDraw {
+    pub bump: u8,
+    pub draw_count: u64,
+    pub commitment: Option<[u8; 32]>,
+    pub commit_slot: u64,
+    /// When the pending draw was first committed, for `REVEAL_TIMEOUT`
+    pub commit_time: i64,
+    pub reset_season: bool,
+    /// Points frozen when the draw's epoch was closed
+    pub total_points: u64,
+    /// Points covered so far by `tally_super_raffle`
+    pub tallied_points: u64,
+    pub last_tallied: Option<Pubkey>,
+    /// The pending draw's epoch is closed and `total_points` frozen, so a
+    /// commit after `close_super_raffle_epoch` doesn't freeze them twice
+    pub epoch_closed: bool,
+}
+
+/// History entry for a single super raffle draw.
+#[account]
+pub struct SuperRaffleResult {
+    pub id: u64,
+    pub bump: u8,
+    pub winner: Pubkey,
+    pub amount: u64,
+    pub winner_points: u32,
+    pub total_points: u64,
+    pub time: i64,
+    pub season_reset: bool,
+}
+
+impl SuperDraw {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        8 + // draw count
+        1 + // option
+        32 + // commitment
+        8 + // commit slot
+        8 + // commit time
+        1 + // reset season
+        8 + // total points
+        8 + // tallied points
+        1 + // option
+        32 + // last tallied
+        1   // epoch closed
+    }
+    /// Closes the season's epoch and freezes its points for the next draw,
+    /// unless that was already done, e.g. so the tally could run first.
+    pub fn freeze_points(&mut self, season: &mut Season, reset_season: bool) {
+        if !self.epoch_closed {
+            self.total_points = season.close_epoch(reset_season);
+            self.reset_season = reset_season;
+            self.epoch_closed = true;
+            self.restart_tally();
+        }
+    }
+    /// Keeps the tally, it only depends on the frozen points. The commitment
+    /// stays until the draw completes, so the operator can't swap in another
+    /// secret once the slot hash shows who the first one picks.
+    pub fn commit(&mut self, commitment: [u8; 32], now: i64, slot: u64) -> Result<()> {
+        require!(self.commitment.is_none(), SuperRaffleError::DrawAlreadyCommitted);
+        self.commitment = Some(commitment);
+        self.commit_time = now;
+        self.commit_slot = slot;
+        Ok(())
+    }
+    /// Replaces a commitment that can no longer be revealed, the SlotHashes
+    /// entry after `commit_slot` being evicted, with the commitment to
+    /// `PUBLIC_REVEAL_SECRET` against a slot after `slot`, `REVEAL_TIMEOUT`
+    /// after the first commit. The frozen points and tally stay.
+    pub fn recommit_public(&mut self, now: i64, slot: u64) -> Result<()> {
+        require!(self.commitment.is_some(), SuperRaffleError::DrawNotCommitted);
+        require!(
+            now >= self.commit_time.saturating_add(REVEAL_TIMEOUT),
+            RaffleError::RevealTimeoutNotReached
+        );
+        self.commitment = Some(commitment_for(&PUBLIC_REVEAL_SECRET));
+        self.commit_slot = slot;
+        Ok(())
+    }
+    pub fn draw_id(&self) -> u64 {
+        self.draw_count + 1
+    }
+    pub fn restart_tally(&mut self) {
+        self.tallied_points = 0;
+        self.last_tallied = None;
+    }
+    /// Gives `stats` the next range of the draw's points. Users must come in
+    /// ascending order so nobody is counted twice.
+    pub fn tally(&mut self, season: &Season, stats: &mut UserStats) -> Result<()> {
+        // None orders before any key, so the first user always passes
+        require!(self.last_tallied < Some(stats.user), SuperRaffleError::TallyOutOfOrder);
+        stats.tally_draw = self.draw_id();
+        stats.tally_start = self.tallied_points;
+        self.tallied_points += stats.closed_points(season) as u64;
+        self.last_tallied = Some(stats.user);
+        Ok(())
+    }
+    /// Every frozen point has been given to a user.
+    pub fn is_tallied(&self) -> bool {
+        self.tallied_points == self.total_points
+    }
+    pub fn complete(&mut self) {
+        self.commitment = None;
+        self.epoch_closed = false;
+        self.draw_count += 1;
+    }
+}
+
+impl SuperRaffleResult {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        8 + // id
+        1 + // bump
+        32 + // winner
+        8 + // amount
+        4 + // winner points
+        8 + // total points
+        8 + // time
+        1   // season reset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn season() -> Season {
+        let mut season = Season {
+            bump: 0,
+            epoch: 0,
+            start_epoch: 0,
+            closed_start_epoch: 0,
+            total_points: 0,
+        };
+        season.initialize(255);
+        season
+    }
+
+    fn super_draw() -> SuperDraw {
+        SuperDraw {
+            bump: 255,
+            draw_count: 0,
+            commitment: None,
+            commit_slot: 0,
+            commit_time: 0,
+            reset_season: false,
+            total_points: 0,
+            tallied_points: 0,
+            last_tallied: None,
+            epoch_closed: false,
+        }
+    }
+
+    #[test]
+    fn expired_draws_fall_back_to_the_public_secret() {
+        let mut season = season();
+        let mut draw = super_draw();
+        season.total_points = 7;
+        draw.freeze_points(&mut season, true);
+        draw.commit([1; 32], 100, 10).unwrap();
+        draw.tallied_points = 7;
+
+        // No new operator secret for the same frozen points
+        assert_eq!(draw.commit([2; 32], 200, 600).err(), Some(SuperRaffleError::DrawAlreadyCommitted.into()));
+        assert_eq!(
+            draw.recommit_public(100 + REVEAL_TIMEOUT - 1, 600).err(),
+            Some(RaffleError::RevealTimeoutNotReached.into())
+        );
+        draw.recommit_public(100 + REVEAL_TIMEOUT, 600).unwrap();
+        assert_eq!(draw.commitment, Some(commitment_for(&PUBLIC_REVEAL_SECRET)));
+        assert_eq!(draw.commit_slot, 600);
+        assert!(draw.is_tallied());
+
+        // Freezing again before the draw completes closes no other epoch
+        season.total_points = 3;
+        draw.freeze_points(&mut season, false);
+        assert_eq!(season.epoch, 2);
+        assert_eq!(draw.total_points, 7);
+        assert!(draw.reset_season);
+
+        draw.complete();
+        draw.freeze_points(&mut season, false);
+        assert_eq!(season.epoch, 3);
+        assert_eq!(draw.total_points, 3);
+        draw.commit([2; 32], 200, 700).unwrap();
+    }
+
+    #[test]
+    fn tally_done_before_commit_is_kept() {
+        let mut season = season();
+        let mut draw = super_draw();
+        let mut stats = UserStats {
+            user: Pubkey::new_unique(),
+            bump: 255,
+            epoch: 0,
+            points: 0,
+            points_at_close: 0,
+            lifetime_tickets: 0,
+            raffles_won: 0,
+            nfts_sold: 0,
+            tally_draw: 0,
+            tally_start: 0,
+        };
+        stats.add_points(&mut season, 4);
+        draw.freeze_points(&mut season, false);
+        draw.tally(&season, &mut stats).unwrap();
+        assert!(draw.is_tallied());
+
+        draw.commit([1; 32], 0, 10).unwrap();
+        assert!(draw.is_tallied());
+        assert_eq!(stats.tally_draw, draw.draw_id());
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+use crate::model::RaffleError;
+
+/// Tickets a user holds in a raffle, one PDA per (raffle, user).
+/// Used for the per-user limit and for refunds.
+#[account]
+pub struct TicketHolder {
+    pub raffle_id: u64,
+    pub bump: u8,
+    pub user: Pubkey,
+    pub qty: u8,
+    /// What was paid for `qty`, refunded if the raffle is cancelled
+    pub lamports_paid: u64,
+    /// Paid in the raffle's `payment_mint`
+    pub tokens_paid: u64,
+}
+
+/// A single purchase, one PDA per (raffle, receipt index).
+/// Owns tickets `start..start + qty`, so a winning ticket index maps
+/// straight to the receipt holding it.
+#[account]
+pub struct TicketReceipt {
+    pub raffle_id: u64,
+    pub bump: u8,
+    pub user: Pubkey,
+    pub start: u32,
+    pub qty: u8,
+}
+
+impl TicketHolder {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        8 + // raffle id
+        1 + // bump
+        32 + // user (Pubkey)
+        1 + // qty (u8)
+        8 + // lamports paid
+        8 // tokens paid
+    }
+    pub fn add_tickets(&mut self, raffle_id: u64, bump: u8, user: &Pubkey, quantity: u8) {
+        self.raffle_id = raffle_id;
+        self.bump = bump;
+        self.user = *user;
+        self.qty += quantity;
+    }
+    pub fn add_payment(&mut self, lamports: u64, tokens: u64) -> Result<()> {
+        self.lamports_paid = self.lamports_paid.checked_add(lamports).ok_or(RaffleError::Overflow)?;
+        self.tokens_paid = self.tokens_paid.checked_add(tokens).ok_or(RaffleError::Overflow)?;
+        Ok(())
+    }
+    /// Zeroes the holder's tickets and returns the (lamports, tokens) owed to them.
+    pub fn refund(&mut self) -> Result<(u64, u64)> {
+        require!(self.qty > 0, RaffleError::NoRefund);
+        let refund = (self.lamports_paid, self.tokens_paid);
+        self.qty = 0;
+        self.lamports_paid = 0;
+        self.tokens_paid = 0;
+        Ok(refund)
+    }
+}
+
+impl TicketReceipt {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        8 + // raffle id
+        1 + // bump
+        32 + // user (Pubkey)
+        4 + // start (u32)
+        1 // qty (u8)
+    }
+    pub fn contains(&self, index: u32) -> bool {
+        index >= self.start && index - self.start < self.qty as u32
+    }
+}
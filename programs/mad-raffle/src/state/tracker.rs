@@ -1,11 +1,14 @@
 use anchor_lang::{prelude::*};
 use solana_program::{pubkey::Pubkey};
 use crate::{id::ID, constants::{RAFFLE_SEED}};
+use crate::model::PointsAwarded;
+use crate::state::{Season, UserStats};
 
 #[account]
 pub struct RaffleTracker {
     pub current_raffle: u64,
     pub bump: u8,
+    /// Legacy points, moved into `UserStats` by `migrate_user_points`
     pub scoreboard: Vec<UserPoints>,
 }
 
@@ -28,7 +31,7 @@ impl RaffleTracker {
         let (next_raffle_pda, next_raffle_bump) = Pubkey::find_program_address(
             &[
                 RAFFLE_SEED.as_ref(),
-                &(self.current_raffle + 1 as u64).to_le_bytes(),
+                &(self.current_raffle + 1).to_le_bytes(),
             ],
             &ID,
         );
@@ -44,22 +47,19 @@ impl RaffleTracker {
         4 + // min vec space
         (UserPoints::get_space() * (user_score_count)) // tickets
     }
-    pub fn add_points(&mut self, user: &Pubkey, num_points: u32) {
+    pub fn add_points(&self, season: &mut Season, stats: &mut UserStats, num_points: u32) {
         let multiplier = self.calculate_multiplier();
         let points_to_add = num_points * multiplier;
 
-        match self
-            .scoreboard
-            .iter_mut()
-            .find(|score: &&mut UserPoints| score.user == *user)
-        {
-            Some(user) => user.points += points_to_add,
-            None => self.scoreboard.push(UserPoints {
-                user: *user,
-                points: points_to_add,
-            }),
-        }
-        msg!("Added {} points to {}", points_to_add, user);        
+        stats.add_points(season, points_to_add);
+        msg!("Added {} points to {}", points_to_add, stats.user);
+        emit!(PointsAwarded {
+            user: stats.user,
+            points: points_to_add,
+            multiplier,
+            epoch: season.epoch,
+            season_points: stats.season_points(season),
+        });
     }
     // Bonus multiplier for points, rewards early participants
     fn calculate_multiplier(&self) -> u32 {
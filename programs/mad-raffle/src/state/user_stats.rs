@@ -0,0 +1,217 @@
+use anchor_lang::prelude::*;
+use solana_program::pubkey::Pubkey;
+
+/// Lifetime stats and season points for a wallet, one PDA per user.
+/// Points are tracked per epoch so a season reset or a super raffle
+/// snapshot never has to touch every user's account.
+#[account]
+pub struct UserStats {
+    pub user: Pubkey,
+    pub bump: u8,
+    /// Epoch `points` was last written in
+    pub epoch: u32,
+    pub points: u32,
+    /// Points as of the close of the epoch before `epoch`
+    pub points_at_close: u32,
+    pub lifetime_tickets: u32,
+    pub raffles_won: u32,
+    pub nfts_sold: u32,
+    /// Super raffle draw this user was last tallied for, and where their
+    /// points start in that draw's cumulative range
+    pub tally_draw: u64,
+    pub tally_start: u64,
+}
+
+/// Points season state. Each super raffle commit closes the current epoch,
+/// freezing its points for the draw while new points go to the next one.
+/// A season spans the epochs since the last reset.
+#[account]
+pub struct Season {
+    pub bump: u8,
+    pub epoch: u32,
+    /// First epoch of the current season
+    pub start_epoch: u32,
+    /// First epoch of the season the last closed epoch belonged to
+    pub closed_start_epoch: u32,
+    /// Points earned in the current season
+    pub total_points: u64,
+}
+
+impl UserStats {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        32 + // user
+        1 + // bump
+        4 + // epoch
+        4 + // points
+        4 + // points at close
+        4 + // lifetime tickets
+        4 + // raffles won
+        4 + // nfts sold
+        8 + // tally draw
+        8   // tally start
+    }
+    /// Sets the owner on a freshly created account.
+    pub fn initialize(&mut self, user: &Pubkey, bump: u8) {
+        if self.user == Pubkey::default() {
+            self.user = *user;
+            self.bump = bump;
+        }
+    }
+    pub fn add_points(&mut self, season: &mut Season, points: u32) {
+        self.roll(season);
+        self.points += points;
+        season.total_points += points as u64;
+    }
+    /// Points in the current season.
+    pub fn season_points(&self, season: &Season) -> u32 {
+        if self.epoch >= season.start_epoch {
+            self.points
+        } else {
+            0
+        }
+    }
+    /// Points frozen when the last epoch closed, used by the super raffle.
+    pub fn closed_points(&self, season: &Season) -> u32 {
+        if self.epoch == season.epoch {
+            self.points_at_close
+        } else if self.epoch >= season.closed_start_epoch {
+            self.points
+        } else {
+            0
+        }
+    }
+    /// Whether `index` falls in this user's range for super raffle `draw_id`.
+    pub fn holds_tally_index(&self, season: &Season, draw_id: u64, index: u64) -> bool {
+        self.tally_draw == draw_id
+            && index >= self.tally_start
+            && index - self.tally_start < self.closed_points(season) as u64
+    }
+    // Moves the account into the current epoch, snapshotting the closed one
+    fn roll(&mut self, season: &Season) {
+        if self.epoch < season.epoch {
+            self.points_at_close = self.closed_points(season);
+            self.points = self.season_points(season);
+            self.epoch = season.epoch;
+        }
+    }
+}
+
+impl Season {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        4 + // epoch
+        4 + // start epoch
+        4 + // closed start epoch
+        8   // total points
+    }
+    /// Sets up epoch 1 on a freshly created account.
+    pub fn initialize(&mut self, bump: u8) {
+        if self.epoch == 0 {
+            *self = Season {
+                bump,
+                epoch: 1,
+                start_epoch: 1,
+                closed_start_epoch: 1,
+                total_points: 0,
+            };
+        }
+    }
+    /// Closes the current epoch and returns its frozen point total.
+    /// With `reset`, the next epoch starts a new season from zero.
+    pub fn close_epoch(&mut self, reset: bool) -> u64 {
+        let closed_total = self.total_points;
+        self.closed_start_epoch = self.start_epoch;
+        self.epoch += 1;
+        if reset {
+            self.start_epoch = self.epoch;
+            self.total_points = 0;
+        }
+        closed_total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn season() -> Season {
+        let mut season = Season {
+            bump: 0,
+            epoch: 0,
+            start_epoch: 0,
+            closed_start_epoch: 0,
+            total_points: 0,
+        };
+        season.initialize(255);
+        season
+    }
+
+    fn user() -> UserStats {
+        UserStats {
+            user: Pubkey::new_unique(),
+            bump: 255,
+            epoch: 0,
+            points: 0,
+            points_at_close: 0,
+            lifetime_tickets: 0,
+            raffles_won: 0,
+            nfts_sold: 0,
+            tally_draw: 0,
+            tally_start: 0,
+        }
+    }
+
+    #[test]
+    fn closed_points_are_frozen_after_close() {
+        let mut season = season();
+        let (mut a, mut b) = (user(), user());
+        a.add_points(&mut season, 5);
+        b.add_points(&mut season, 2);
+
+        assert_eq!(season.close_epoch(false), 7);
+        // Points earned after the close don't change the snapshot
+        a.add_points(&mut season, 10);
+        assert_eq!(a.closed_points(&season), 5);
+        assert_eq!(b.closed_points(&season), 2);
+        assert_eq!(a.season_points(&season), 15);
+        assert_eq!(season.total_points, 17);
+    }
+
+    #[test]
+    fn reset_starts_a_new_season() {
+        let mut season = season();
+        let (mut a, b) = (user(), user());
+        a.add_points(&mut season, 5);
+
+        assert_eq!(season.close_epoch(true), 5);
+        assert_eq!(a.closed_points(&season), 5);
+        assert_eq!(a.season_points(&season), 0);
+        assert_eq!(season.total_points, 0);
+
+        a.add_points(&mut season, 3);
+        assert_eq!(a.closed_points(&season), 5);
+        assert_eq!(a.season_points(&season), 3);
+        assert_eq!(b.closed_points(&season), 0);
+
+        // Next close only sees points from the new season
+        assert_eq!(season.close_epoch(false), 3);
+        assert_eq!(a.closed_points(&season), 3);
+    }
+
+    #[test]
+    fn inactive_users_carry_points_until_reset() {
+        let mut season = season();
+        let mut a = user();
+        a.add_points(&mut season, 4);
+        season.close_epoch(false);
+        season.close_epoch(false);
+        assert_eq!(a.closed_points(&season), 4);
+        assert_eq!(a.season_points(&season), 4);
+        season.close_epoch(true);
+        assert_eq!(a.closed_points(&season), 4);
+        season.close_epoch(false);
+        assert_eq!(a.closed_points(&season), 0);
+    }
+}
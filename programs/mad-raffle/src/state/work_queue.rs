@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_WORK_ITEMS;
+use crate::model::WorkError;
+
+/// Housekeeping left on ended raffles, kept by `sync_work_queue` so a
+/// scheduler can find it from this PDA and `RaffleTracker` alone.
+#[account]
+pub struct WorkQueue {
+    pub bump: u8,
+    pub items: Vec<WorkItem>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WorkItem {
+    pub raffle_id: u64,
+    pub task: WorkTask,
+}
+
+/// An instruction a scheduler should send for a raffle, whose accounts all
+/// follow from the raffle's state.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorkTask {
+    /// `reveal_and_pick_winner` or `consume_randomness` for the first prize,
+    /// `draw_winner` for the others. Revealing takes the committed secret, so
+    /// only the operator's scheduler can run it until the public fallback
+    DrawWinner,
+    /// `distribute_prize` (or `distribute_prize_cnft`) for the slot, to the
+    /// active raffle if it rolled over
+    DistributePrize { slot: u8 },
+    /// `redraw_unclaimed` for the slot, its claim deadline passed
+    RedrawUnclaimed { slot: u8 },
+    /// `recommit_randomness`, the slot hash the first draw was committed
+    /// against expired. Anyone can run it `REVEAL_TIMEOUT` after the raffle
+    /// ended
+    RecommitRandomness,
+}
+
+impl WorkQueue {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        1 + // bump
+        4 + // items vec length
+        MAX_WORK_ITEMS * (
+            8 + // raffle id
+            1 + 1 // task (enum with slot)
+        )
+    }
+    /// Replaces the items of raffle `raffle_id` with `tasks`, in order.
+    pub fn sync(&mut self, raffle_id: u64, tasks: Vec<WorkTask>) -> Result<()> {
+        self.items.retain(|item| item.raffle_id != raffle_id);
+        require!(
+            self.items.len() + tasks.len() <= MAX_WORK_ITEMS,
+            WorkError::QueueFull
+        );
+        self.items
+            .extend(tasks.into_iter().map(|task| WorkItem { raffle_id, task }));
+        Ok(())
+    }
+    /// Raffles with pending work, oldest first.
+    pub fn raffle_ids(&self) -> Vec<u64> {
+        let mut ids: Vec<u64> = self.items.iter().map(|item| item.raffle_id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> WorkQueue {
+        WorkQueue { bump: 0, items: vec![] }
+    }
+
+    #[test]
+    fn sync_replaces_a_raffles_items() {
+        let mut queue = queue();
+        queue.sync(5, vec![WorkTask::DrawWinner]).unwrap();
+        queue
+            .sync(4, vec![WorkTask::DistributePrize { slot: 0 }, WorkTask::DistributePrize { slot: 1 }])
+            .unwrap();
+        assert_eq!(queue.raffle_ids(), vec![4, 5]);
+
+        queue.sync(4, vec![WorkTask::RedrawUnclaimed { slot: 1 }]).unwrap();
+        assert_eq!(
+            queue.items,
+            vec![
+                WorkItem { raffle_id: 5, task: WorkTask::DrawWinner },
+                WorkItem { raffle_id: 4, task: WorkTask::RedrawUnclaimed { slot: 1 } },
+            ]
+        );
+        queue.sync(5, vec![]).unwrap();
+        assert_eq!(queue.raffle_ids(), vec![4]);
+    }
+
+    #[test]
+    fn queue_is_capped() {
+        let mut queue = queue();
+        queue.sync(1, vec![WorkTask::DrawWinner; MAX_WORK_ITEMS]).unwrap();
+        assert_eq!(
+            queue.sync(2, vec![WorkTask::DrawWinner]).err(),
+            Some(WorkError::QueueFull.into())
+        );
+        // A raffle's own items make room for its new ones
+        queue.sync(1, vec![WorkTask::DistributePrize { slot: 0 }; MAX_WORK_ITEMS]).unwrap();
+    }
+}
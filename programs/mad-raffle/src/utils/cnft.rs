@@ -0,0 +1,259 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::{Collection, Creator};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    keccak,
+    program::{invoke, invoke_signed},
+};
+
+pub mod bubblegum {
+    anchor_lang::declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+}
+
+pub mod account_compression {
+    anchor_lang::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+
+pub mod noop {
+    anchor_lang::declare_id!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+}
+
+const ASSET_PREFIX: &[u8] = b"asset";
+// sha256("global:transfer")[..8]
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+
+/// A compressed NFT leaf, as passed to Bubblegum to prove and transfer it.
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct CompressedAsset {
+    /// Root of the tree the proof in the remaining accounts leads to
+    pub root: [u8; 32],
+    pub nonce: u64,
+    pub index: u32,
+    pub metadata: MetadataArgsLocal,
+}
+
+impl CompressedAsset {
+    /// Id of the asset in `merkle_tree`, the same for every owner.
+    pub fn asset_id(&self, merkle_tree: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(
+            &[ASSET_PREFIX, merkle_tree.as_ref(), &self.nonce.to_le_bytes()],
+            &bubblegum::ID,
+        )
+        .0
+    }
+    /// Hash of the metadata stored in the leaf. Bubblegum only accepts the
+    /// transfer if it matches, so the metadata can be trusted once it has.
+    pub fn data_hash(&self) -> Result<[u8; 32]> {
+        let metadata_hash = keccak::hashv(&[self.metadata.try_to_vec()?.as_slice()]);
+        Ok(keccak::hashv(&[
+            metadata_hash.as_ref(),
+            &self.metadata.seller_fee_basis_points.to_le_bytes(),
+        ])
+        .to_bytes())
+    }
+    pub fn creator_hash(&self) -> [u8; 32] {
+        let creator_data = self
+            .metadata
+            .creators
+            .iter()
+            .map(|c| [c.address.as_ref(), &[c.verified as u8], &[c.share]].concat())
+            .collect::<Vec<_>>();
+        keccak::hashv(&creator_data.iter().map(Vec::as_slice).collect::<Vec<_>>()).to_bytes()
+    }
+    pub fn collection(&self) -> Option<Collection> {
+        self.metadata.collection.as_ref().map(|c| Collection {
+            verified: c.verified,
+            key: c.key,
+        })
+    }
+    pub fn creators(&self) -> Vec<Creator> {
+        self.metadata
+            .creators
+            .iter()
+            .map(|c| Creator {
+                address: c.address,
+                verified: c.verified,
+                share: c.share,
+            })
+            .collect()
+    }
+}
+
+/// Moves the compressed NFT `asset` in `merkle_tree` from `leaf_owner` to
+/// `new_leaf_owner`, `proof` being the path to the tree's canopy.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_cnft<'info>(
+    tree_authority: &AccountInfo<'info>,
+    leaf_owner: &AccountInfo<'info>,
+    leaf_delegate: &AccountInfo<'info>,
+    new_leaf_owner: &AccountInfo<'info>,
+    merkle_tree: &AccountInfo<'info>,
+    log_wrapper: &AccountInfo<'info>,
+    compression_program: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    bubblegum_program: &AccountInfo<'info>,
+    proof: &[AccountInfo<'info>],
+    asset: &CompressedAsset,
+    // For signing w/ PDA if needed (e.g., from the raffle to user)
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<()> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(tree_authority.key(), false),
+        AccountMeta::new_readonly(leaf_owner.key(), true),
+        AccountMeta::new_readonly(leaf_delegate.key(), false),
+        AccountMeta::new_readonly(new_leaf_owner.key(), false),
+        AccountMeta::new(merkle_tree.key(), false),
+        AccountMeta::new_readonly(log_wrapper.key(), false),
+        AccountMeta::new_readonly(compression_program.key(), false),
+        AccountMeta::new_readonly(system_program.key(), false),
+    ];
+    accounts.extend(proof.iter().map(|node| AccountMeta::new_readonly(node.key(), false)));
+
+    let mut data = TRANSFER_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&asset.root);
+    data.extend_from_slice(&asset.data_hash()?);
+    data.extend_from_slice(&asset.creator_hash());
+    data.extend_from_slice(&asset.nonce.to_le_bytes());
+    data.extend_from_slice(&asset.index.to_le_bytes());
+    let transfer_ix = Instruction {
+        program_id: bubblegum::ID,
+        accounts,
+        data,
+    };
+
+    let mut account_infos = vec![
+        tree_authority.clone(),
+        leaf_owner.clone(),
+        leaf_delegate.clone(),
+        new_leaf_owner.clone(),
+        merkle_tree.clone(),
+        log_wrapper.clone(),
+        compression_program.clone(),
+        system_program.clone(),
+        bubblegum_program.clone(),
+    ];
+    account_infos.extend_from_slice(proof);
+
+    if let Some(signer_seeds) = signer_seeds {
+        invoke_signed(&transfer_ix, &account_infos, signer_seeds)?;
+    } else {
+        invoke(&transfer_ix, &account_infos)?;
+    }
+    Ok(())
+}
+
+// --------------------------------------- replicating bubblegum types for anchor IDL export
+// Field order and types must match Bubblegum's, the leaf hashes their borsh encoding
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct MetadataArgsLocal {
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub seller_fee_basis_points: u16,
+    pub primary_sale_happened: bool,
+    pub is_mutable: bool,
+    pub edition_nonce: Option<u8>,
+    pub token_standard: Option<TokenStandardLocal>,
+    pub collection: Option<CollectionLocal>,
+    pub uses: Option<UsesLocal>,
+    pub token_program_version: TokenProgramVersionLocal,
+    pub creators: Vec<CreatorLocal>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenStandardLocal {
+    NonFungible,
+    FungibleAsset,
+    Fungible,
+    NonFungibleEdition,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct CollectionLocal {
+    pub verified: bool,
+    pub key: Pubkey,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct UsesLocal {
+    pub use_method: UseMethodLocal,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UseMethodLocal {
+    Burn,
+    Multiple,
+    Single,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenProgramVersionLocal {
+    Original,
+    Token2022,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct CreatorLocal {
+    pub address: Pubkey,
+    pub verified: bool,
+    pub share: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(nonce: u64, collection_verified: bool) -> CompressedAsset {
+        CompressedAsset {
+            root: [0; 32],
+            nonce,
+            index: nonce as u32,
+            metadata: MetadataArgsLocal {
+                name: "Mad Lads #3482".to_string(),
+                symbol: "MAD".to_string(),
+                uri: "https://madlads.s3.us-west-2.amazonaws.com/json/3482.json".to_string(),
+                seller_fee_basis_points: 500,
+                primary_sale_happened: true,
+                is_mutable: true,
+                edition_nonce: None,
+                token_standard: Some(TokenStandardLocal::NonFungible),
+                collection: Some(CollectionLocal {
+                    verified: collection_verified,
+                    key: Pubkey::new_from_array([7; 32]),
+                }),
+                uses: None,
+                token_program_version: TokenProgramVersionLocal::Original,
+                creators: vec![
+                    CreatorLocal { address: Pubkey::new_from_array([1; 32]), verified: true, share: 60 },
+                    CreatorLocal { address: Pubkey::new_from_array([2; 32]), verified: false, share: 40 },
+                ],
+            },
+        }
+    }
+
+    #[test]
+    fn asset_id_is_per_tree_and_nonce() {
+        let tree = Pubkey::new_unique();
+        assert_eq!(asset(1, true).asset_id(&tree), asset(1, false).asset_id(&tree));
+        assert_ne!(asset(1, true).asset_id(&tree), asset(2, true).asset_id(&tree));
+        assert_ne!(asset(1, true).asset_id(&tree), asset(1, true).asset_id(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn leaf_hashes_commit_to_metadata() {
+        let verified = asset(1, true);
+        assert_ne!(verified.data_hash().unwrap(), asset(1, false).data_hash().unwrap());
+        assert_eq!(verified.collection().map(|c| c.verified), Some(true));
+
+        let mut creator_data = Vec::new();
+        for (byte, verified, share) in [(1, 1, 60), (2, 0, 40)] {
+            creator_data.extend_from_slice(&[byte; 32]);
+            creator_data.extend_from_slice(&[verified, share]);
+        }
+        assert_eq!(verified.creator_hash(), keccak::hash(&creator_data).to_bytes());
+        assert_eq!(verified.creators().iter().map(|c| c.share).sum::<u8>(), 100);
+    }
+}
@@ -2,4 +2,15 @@ pub mod nft;
 pub use nft::*;
 
 pub mod randomness;
-pub use randomness::*;
\ No newline at end of file
+pub use randomness::*;
+
+pub mod oracle;
+pub use oracle::*;
+
+pub mod price;
+pub use price::*;
+pub mod payout;
+pub use payout::*;
+
+pub mod cnft;
+pub use cnft::*;
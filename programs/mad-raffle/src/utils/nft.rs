@@ -1,7 +1,15 @@
 use anchor_spl::{
-    associated_token::AssociatedToken,
+    associated_token::{self, AssociatedToken, Create},
     metadata::MetadataAccount,
-    token::{Mint, Token, TokenAccount},
+    token_2022::{
+        self,
+        spl_token_2022::{
+            self,
+            extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+        },
+        TransferChecked,
+    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 use mpl_token_auth_rules::payload::{Payload, PayloadType, ProofInfo, SeedsVec};
 use mpl_token_metadata::{
@@ -11,20 +19,74 @@ use mpl_token_metadata::{
 };
 use solana_program::program::{invoke, invoke_signed};
 
-use crate::{constants::RAFFLE_SEED, model::PnftError, state::Raffle, *};
+use crate::{model::PnftError, *};
 
+/// How a prize NFT moves between token accounts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AssetKind {
+    /// Token Metadata transfer, with token records and rule sets
+    Programmable,
+    /// `transfer_checked` on SPL Token, for `NonFungible` NFTs and editions
+    Legacy,
+    /// `transfer_checked` on Token-2022
+    Token2022,
+}
+
+/// Which transfer a mint owned by `mint_owner` needs. Fails for anything but a
+/// single token, and for Token-2022 extensions that stop the prize moving
+/// freely. Transfer hooks can't be parsed by this version of Token-2022, and
+/// their extra accounts aren't resolved, so they are refused too.
+pub fn asset_kind(
+    mint_owner: &Pubkey,
+    mint_data: &[u8],
+    token_standard: Option<TokenStandard>,
+) -> Result<AssetKind> {
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(mint_data)
+        .map_err(|_| PnftError::UnsupportedAsset)?;
+    require!(mint.base.decimals == 0 && mint.base.supply == 1, PnftError::UnsupportedAsset);
+
+    if *mint_owner == spl_token_2022::ID {
+        require!(
+            token_standard != Some(TokenStandard::ProgrammableNonFungible),
+            PnftError::UnsupportedAsset
+        );
+        let extensions = mint
+            .get_extension_types()
+            .map_err(|_| PnftError::UnsupportedMintExtension)?;
+        for extension in extensions {
+            match extension {
+                ExtensionType::MintCloseAuthority | ExtensionType::InterestBearingConfig => {}
+                _ => return err!(PnftError::UnsupportedMintExtension),
+            }
+        }
+        return Ok(AssetKind::Token2022);
+    }
+    require_keys_eq!(*mint_owner, anchor_spl::token::ID, PnftError::UnsupportedAsset);
+    match token_standard {
+        Some(TokenStandard::ProgrammableNonFungible) => Ok(AssetKind::Programmable),
+        Some(TokenStandard::NonFungible) | Some(TokenStandard::NonFungibleEdition) | None => {
+            Ok(AssetKind::Legacy)
+        }
+        Some(TokenStandard::Fungible) | Some(TokenStandard::FungibleAsset) => {
+            err!(PnftError::UnsupportedAsset)
+        }
+    }
+}
+
+/// Sends an NFT of any `AssetKind` to `dest_ata`, the associated token account
+/// of `dest_owner` for `token_program`, creating it if needed.
 #[allow(clippy::too_many_arguments)]
-pub fn send_pnft<'info>(
+pub fn send_nft<'info>(
     authority_and_owner: &AccountInfo<'info>,
     payer: &AccountInfo<'info>,
-    source_ata: &Account<'info, TokenAccount>,
-    dest_ata: &Account<'info, TokenAccount>,
+    source_ata: &InterfaceAccount<'info, TokenAccount>,
+    dest_ata: &AccountInfo<'info>,
     dest_owner: &AccountInfo<'info>,
-    nft_mint: &Account<'info, Mint>,
+    nft_mint: &InterfaceAccount<'info, Mint>,
     nft_metadata: &Account<'info, MetadataAccount>,
     nft_edition: &UncheckedAccount<'info>,
     system_program: &Program<'info, System>,
-    token_program: &Program<'info, Token>,
+    token_program: &Interface<'info, TokenInterface>,
     ata_program: &Program<'info, AssociatedToken>,
     instructions: &UncheckedAccount<'info>,
     owner_token_record: &UncheckedAccount<'info>,
@@ -33,7 +95,87 @@ pub fn send_pnft<'info>(
     rules_acc: Option<&AccountInfo<'info>>,
     authorization_data: Option<AuthorizationDataLocal>,
     // For signing w/ PDA if needed (e.g., from the raffle to user)
-    program_signer: Option<&Account<'info, Raffle>>,
+    signer_seeds: Option<&[&[&[u8]]]>,
+) -> Result<()> {
+    let mint_info = nft_mint.to_account_info();
+    require_keys_eq!(*mint_info.owner, token_program.key(), PnftError::WrongTokenProgram);
+    let kind = asset_kind(mint_info.owner, &mint_info.try_borrow_data()?, nft_metadata.token_standard)?;
+
+    if dest_ata.data_is_empty() {
+        let create_context = CpiContext::new(
+            ata_program.to_account_info(),
+            Create {
+                payer: payer.to_account_info(),
+                associated_token: dest_ata.to_account_info(),
+                authority: dest_owner.to_account_info(),
+                mint: mint_info.clone(),
+                system_program: system_program.to_account_info(),
+                token_program: token_program.to_account_info(),
+            },
+        );
+        associated_token::create_idempotent(create_context)?;
+    }
+
+    match kind {
+        AssetKind::Programmable => send_pnft(
+            authority_and_owner,
+            payer,
+            &source_ata.to_account_info(),
+            dest_ata,
+            dest_owner,
+            &mint_info,
+            nft_metadata,
+            nft_edition,
+            system_program,
+            &token_program.to_account_info(),
+            ata_program,
+            instructions,
+            owner_token_record,
+            dest_token_record,
+            authorization_rules_program,
+            rules_acc,
+            authorization_data,
+            signer_seeds,
+        ),
+        AssetKind::Legacy | AssetKind::Token2022 => {
+            let transfer_context = CpiContext::new(
+                token_program.to_account_info(),
+                TransferChecked {
+                    from: source_ata.to_account_info(),
+                    mint: mint_info,
+                    to: dest_ata.to_account_info(),
+                    authority: authority_and_owner.to_account_info(),
+                },
+            );
+            token_2022::transfer_checked(
+                transfer_context.with_signer(signer_seeds.unwrap_or_default()),
+                1,
+                nft_mint.decimals,
+            )
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn send_pnft<'info>(
+    authority_and_owner: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    source_ata: &AccountInfo<'info>,
+    dest_ata: &AccountInfo<'info>,
+    dest_owner: &AccountInfo<'info>,
+    nft_mint: &AccountInfo<'info>,
+    nft_metadata: &Account<'info, MetadataAccount>,
+    nft_edition: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    token_program: &AccountInfo<'info>,
+    ata_program: &Program<'info, AssociatedToken>,
+    instructions: &UncheckedAccount<'info>,
+    owner_token_record: &UncheckedAccount<'info>,
+    dest_token_record: &UncheckedAccount<'info>,
+    authorization_rules_program: &UncheckedAccount<'info>,
+    rules_acc: Option<&AccountInfo<'info>>,
+    authorization_data: Option<AuthorizationDataLocal>,
+    signer_seeds: Option<&[&[&[u8]]]>,
 ) -> Result<()> {
     let mut builder = TransferBuilder::new();
 
@@ -106,8 +248,7 @@ pub fn send_pnft<'info>(
             V1 { rule_set } => {
                 if let Some(rule_set) = rule_set {
                     msg!("ruleset triggered");
-                    //safe to unwrap here, it's expected
-                    let rules_acc = rules_acc.unwrap();
+                    let rules_acc = rules_acc.ok_or(PnftError::MissingRulesAccount)?;
 
                     //1. validate
                     require!(rule_set == *rules_acc.key, PnftError::BadRuleset);
@@ -124,26 +265,19 @@ pub fn send_pnft<'info>(
         }
     }
 
+    let authorization_data = authorization_data
+        .map(AuthorizationData::try_from)
+        .transpose()?;
     let transfer_ix = builder
         .build(TransferArgs::V1 {
             amount: 1,
-            authorization_data: authorization_data
-                .map(|authorization_data| AuthorizationData::try_from(authorization_data).unwrap()),
+            authorization_data,
         })
-        .unwrap()
+        .map_err(|_| PnftError::BadTransferInstruction)?
         .instruction();
 
-    if let Some(program_signer) = program_signer {
+    if let Some(signer_seeds) = signer_seeds {
         msg!("signed invoke triggered");
-        let raffle = program_signer.clone();
-        let num_raffle_bytes = &(raffle.id).to_le_bytes();
-        let bump = &[raffle.bump];
-        // Should match raffle pda
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            RAFFLE_SEED.as_ref(),
-            num_raffle_bytes,
-            bump,
-        ]];
         invoke_signed(&transfer_ix, &account_infos, signer_seeds)?;
     } else {
         invoke(&transfer_ix, &account_infos)?;
@@ -154,7 +288,7 @@ pub fn send_pnft<'info>(
 
 #[inline(never)]
 pub fn assert_decode_metadata<'info>(
-    nft_mint: &Account<'info, Mint>,
+    nft_mint: &AccountInfo<'info>,
     metadata_account: &AccountInfo<'info>,
 ) -> Result<Metadata> {
     let (key, _) = Pubkey::find_program_address(
@@ -180,13 +314,16 @@ pub fn assert_decode_metadata<'info>(
 pub struct AuthorizationDataLocal {
     pub payload: Vec<TaggedPayload>,
 }
-impl From<AuthorizationDataLocal> for AuthorizationData {
-    fn from(val: AuthorizationDataLocal) -> Self {
+impl TryFrom<AuthorizationDataLocal> for AuthorizationData {
+    type Error = Error;
+    /// Fails on duplicate payload names rather than silently keeping the last one.
+    fn try_from(val: AuthorizationDataLocal) -> Result<Self> {
         let mut p = Payload::new();
-        val.payload.into_iter().for_each(|tp| {
-            p.insert(tp.name, PayloadType::try_from(tp.payload).unwrap());
-        });
-        AuthorizationData { payload: p }
+        for tp in val.payload {
+            p.try_insert(tp.name, PayloadType::from(tp.payload))
+                .map_err(|_| PnftError::BadAuthorizationData)?;
+        }
+        Ok(AuthorizationData { payload: p })
     }
 }
 
@@ -213,10 +350,10 @@ impl From<PayloadTypeLocal> for PayloadType {
         match val {
             PayloadTypeLocal::Pubkey(pubkey) => PayloadType::Pubkey(pubkey),
             PayloadTypeLocal::Seeds(seeds) => {
-                PayloadType::Seeds(SeedsVec::try_from(seeds).unwrap())
+                PayloadType::Seeds(SeedsVec::from(seeds))
             }
             PayloadTypeLocal::MerkleProof(proof) => {
-                PayloadType::MerkleProof(ProofInfo::try_from(proof).unwrap())
+                PayloadType::MerkleProof(ProofInfo::from(proof))
             }
             PayloadTypeLocal::Number(number) => PayloadType::Number(number),
         }
@@ -244,3 +381,98 @@ impl From<ProofInfoLocal> for ProofInfo {
         ProofInfo { proof: val.proof }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::{
+        extension::{
+            mint_close_authority::MintCloseAuthority, non_transferable::NonTransferable,
+            StateWithExtensionsMut,
+        },
+        state::{Account as Account2022, Mint as Mint2022},
+    };
+    use solana_program::{program_option::COption, program_pack::Pack};
+
+    fn nft_mint(supply: u64, extensions: &[ExtensionType]) -> Vec<u8> {
+        let mut data = vec![0; ExtensionType::get_account_len::<Mint2022>(extensions)];
+        let mut state = StateWithExtensionsMut::<Mint2022>::unpack_uninitialized(&mut data).unwrap();
+        state.base = Mint2022 {
+            mint_authority: COption::None,
+            supply,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        state.pack_base();
+        for extension in extensions {
+            match extension {
+                ExtensionType::MintCloseAuthority => {
+                    state.init_extension::<MintCloseAuthority>(true).unwrap();
+                }
+                ExtensionType::NonTransferable => {
+                    state.init_extension::<NonTransferable>(true).unwrap();
+                }
+                _ => unreachable!(),
+            }
+        }
+        if !extensions.is_empty() {
+            state.init_account_type().unwrap();
+        }
+        data
+    }
+
+    #[test]
+    fn spl_token_nfts_by_standard() {
+        let mint = nft_mint(1, &[]);
+        let token = anchor_spl::token::ID;
+        assert_eq!(
+            asset_kind(&token, &mint, Some(TokenStandard::ProgrammableNonFungible)).unwrap(),
+            AssetKind::Programmable
+        );
+        for standard in [Some(TokenStandard::NonFungible), Some(TokenStandard::NonFungibleEdition), None] {
+            assert_eq!(asset_kind(&token, &mint, standard).unwrap(), AssetKind::Legacy);
+        }
+        assert_eq!(
+            asset_kind(&token, &mint, Some(TokenStandard::Fungible)).err(),
+            Some(PnftError::UnsupportedAsset.into())
+        );
+        assert_eq!(
+            asset_kind(&token, &nft_mint(2, &[]), Some(TokenStandard::NonFungible)).err(),
+            Some(PnftError::UnsupportedAsset.into())
+        );
+        assert_eq!(
+            asset_kind(&Pubkey::new_unique(), &mint, Some(TokenStandard::NonFungible)).err(),
+            Some(PnftError::UnsupportedAsset.into())
+        );
+    }
+
+    #[test]
+    fn token_2022_nfts_by_extension() {
+        let token_2022 = spl_token_2022::ID;
+        assert_eq!(
+            asset_kind(&token_2022, &nft_mint(1, &[]), Some(TokenStandard::NonFungible)).unwrap(),
+            AssetKind::Token2022
+        );
+        let closable = nft_mint(1, &[ExtensionType::MintCloseAuthority]);
+        assert_eq!(asset_kind(&token_2022, &closable, None).unwrap(), AssetKind::Token2022);
+        assert_eq!(
+            asset_kind(&token_2022, &closable, Some(TokenStandard::ProgrammableNonFungible)).err(),
+            Some(PnftError::UnsupportedAsset.into())
+        );
+        assert_eq!(
+            asset_kind(&token_2022, &nft_mint(1, &[ExtensionType::NonTransferable]), None).err(),
+            Some(PnftError::UnsupportedMintExtension.into())
+        );
+
+        // A transfer hook, newer than this Token-2022, as the only extension.
+        // Extensions start after the padding to an account's length and the account type.
+        let mut hooked = closable;
+        let type_start = Account2022::LEN + 1;
+        hooked[type_start..type_start + 2].copy_from_slice(&14u16.to_le_bytes());
+        assert_eq!(
+            asset_kind(&token_2022, &hooked, None).err(),
+            Some(PnftError::UnsupportedMintExtension.into())
+        );
+    }
+}
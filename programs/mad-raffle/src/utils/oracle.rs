@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+use crate::model::OracleError;
+use crate::state::OracleProvider;
+
+/// A VRF oracle that can supply randomness for a raffle.
+pub trait RandomnessOracle {
+    /// Checks that `oracle` is a request account opened for `requester`.
+    fn verify_request(oracle: &AccountInfo, requester: &Pubkey) -> Result<()>;
+    /// Reads the fulfilled randomness, which must have been produced after `min_slot`.
+    fn read_randomness(oracle: &AccountInfo, requester: &Pubkey, min_slot: u64) -> Result<[u8; 32]>;
+}
+
+pub fn verify_oracle_request(
+    provider: OracleProvider,
+    oracle: &AccountInfo,
+    requester: &Pubkey,
+) -> Result<()> {
+    match provider {
+        OracleProvider::Mock => MockOracle::verify_request(oracle, requester),
+    }
+}
+
+pub fn read_oracle_randomness(
+    provider: OracleProvider,
+    oracle: &AccountInfo,
+    requester: &Pubkey,
+    min_slot: u64,
+) -> Result<[u8; 32]> {
+    match provider {
+        OracleProvider::Mock => MockOracle::read_randomness(oracle, requester, min_slot),
+    }
+}
+
+/// Randomness from the local `mock_oracle` program.
+pub struct MockOracle;
+
+#[cfg(feature = "mock-oracle")]
+impl MockOracle {
+    fn load(oracle: &AccountInfo, requester: &Pubkey) -> Result<mock_oracle::MockRandomness> {
+        require_keys_eq!(*oracle.owner, mock_oracle::ID, OracleError::InvalidOracleAccount);
+        let data = oracle.try_borrow_data()?;
+        let randomness = mock_oracle::MockRandomness::try_deserialize(&mut &data[..])
+            .map_err(|_| error!(OracleError::InvalidOracleAccount))?;
+        require_keys_eq!(randomness.requester, *requester, OracleError::InvalidOracleAccount);
+        Ok(randomness)
+    }
+}
+
+#[cfg(feature = "mock-oracle")]
+impl RandomnessOracle for MockOracle {
+    fn verify_request(oracle: &AccountInfo, requester: &Pubkey) -> Result<()> {
+        MockOracle::load(oracle, requester).map(|_| ())
+    }
+    fn read_randomness(oracle: &AccountInfo, requester: &Pubkey, min_slot: u64) -> Result<[u8; 32]> {
+        let randomness = MockOracle::load(oracle, requester)?;
+        require!(randomness.fulfilled_slot > min_slot, OracleError::RandomnessNotFulfilled);
+        randomness.result.ok_or_else(|| error!(OracleError::RandomnessNotFulfilled))
+    }
+}
+
+// Anyone can fulfill the mock, so it is rejected unless explicitly compiled in
+#[cfg(not(feature = "mock-oracle"))]
+impl RandomnessOracle for MockOracle {
+    fn verify_request(_oracle: &AccountInfo, _requester: &Pubkey) -> Result<()> {
+        err!(OracleError::UnsupportedOracle)
+    }
+    fn read_randomness(_oracle: &AccountInfo, _requester: &Pubkey, _min_slot: u64) -> Result<[u8; 32]> {
+        err!(OracleError::UnsupportedOracle)
+    }
+}
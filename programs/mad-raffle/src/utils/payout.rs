@@ -0,0 +1,344 @@
+use anchor_lang::prelude::*;
+use mpl_token_metadata::state::Creator;
+use anchor_spl::{
+    associated_token::get_associated_token_address,
+    token::{self, CloseAccount, Token, TokenAccount, Transfer},
+};
+
+use crate::constants::RAFFLE_SEED;
+use crate::model::{RaffleError, PaymentError, RoyaltyPaid, CrankRewardPaid};
+use crate::state::{Raffle, ProgramConfig};
+
+const TOTAL_RATE: u64 = 10000; // BPS 100% rate
+
+/// What the pot paid out when a raffle ended.
+pub struct PotPayout {
+    pub payment_to_seller: u64,
+    pub royalties_paid: u64,
+    pub token_payment_to_seller: u64,
+    pub token_royalties_paid: u64,
+}
+
+/// Lamports in the raffle available to the seller and creators,
+/// leaving its rent, the cost of the next raffle and the crank rewards.
+pub fn pot_balance(raffle: &Account<Raffle>, config: &ProgramConfig) -> Result<u64> {
+    Ok(reserved_balance(raffle, config)?.saturating_sub(raffle.crank_reserve()))
+}
+
+/// Lamports in the raffle above its rent and the cost of the next raffle.
+fn reserved_balance(raffle: &Account<Raffle>, config: &ProgramConfig) -> Result<u64> {
+    let rent_required = Rent::get()?.minimum_balance(Raffle::get_space());
+    Ok(raffle.to_account_info().lamports().saturating_sub(rent_required + config.new_raffle_cost))
+}
+
+/// Moves the pot of a raffle that ended short of its prizes, the part the
+/// prizes never sold would have been paid, into the `new_raffle` pot.
+/// Escrowed SPL tokens move to `new_pot_escrow`, the new raffle's token
+/// account for the same mint, and the emptied `pot_escrow` is closed.
+/// `new_raffle` must already be initialized. Returns the lamports moved.
+pub fn carry_over_pot<'info>(
+    raffle: &Account<'info, Raffle>,
+    new_raffle: &mut Account<'info, Raffle>,
+    config: &ProgramConfig,
+    pot_escrow: Option<&Account<'info, TokenAccount>>,
+    new_pot_escrow: Option<&Account<'info, TokenAccount>>,
+    token_program: Option<&Program<'info, Token>>,
+) -> Result<u64> {
+    let amount = pot_balance(raffle, config)?;
+    if amount > 0 {
+        **raffle.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **new_raffle.to_account_info().try_borrow_mut_lamports()? += amount;
+    }
+
+    if let Some(payment_mint) = raffle.payment_mint {
+        let (Some(pot_escrow), Some(new_pot_escrow), Some(token_program)) =
+            (pot_escrow, new_pot_escrow, token_program) else {
+            return err!(PaymentError::InvalidPaymentAccounts);
+        };
+        require_keys_eq!(
+            pot_escrow.key(),
+            get_associated_token_address(&raffle.key(), &payment_mint),
+            PaymentError::InvalidPaymentAccounts
+        );
+        require_keys_eq!(
+            new_pot_escrow.key(),
+            get_associated_token_address(&new_raffle.key(), &payment_mint),
+            PaymentError::InvalidPaymentAccounts
+        );
+
+        let raffle_id_bytes = raffle.id.to_le_bytes();
+        let bump = [raffle.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[RAFFLE_SEED.as_ref(), &raffle_id_bytes, &bump]];
+        if pot_escrow.amount > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    Transfer {
+                        from: pot_escrow.to_account_info(),
+                        to: new_pot_escrow.to_account_info(),
+                        authority: raffle.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                pot_escrow.amount,
+            )?;
+            new_raffle.accept_payment_mint(payment_mint)?;
+            msg!("Carried {} of {} over to raffle# {}", pot_escrow.amount, payment_mint, new_raffle.id);
+        }
+        token::close_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            CloseAccount {
+                account: pot_escrow.to_account_info(),
+                destination: new_raffle.to_account_info(),
+                authority: raffle.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+    Ok(amount)
+}
+
+/// Pays `raffle.crank_reward` to `cranker`, or what is left of it once the
+/// reserve runs out (e.g. prizes drawn again after their claim deadline).
+pub fn pay_crank_reward(
+    raffle: &Account<Raffle>,
+    cranker: &AccountInfo,
+    config: &ProgramConfig,
+) -> Result<u64> {
+    let amount = raffle.crank_reward.min(reserved_balance(raffle, config)?);
+    if amount > 0 {
+        **raffle.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **cranker.try_borrow_mut_lamports()? += amount;
+        emit!(CrankRewardPaid {
+            raffle_id: raffle.id,
+            cranker: cranker.key(),
+            amount,
+        });
+    }
+    Ok(amount)
+}
+
+/// Part of the pot due to the next prize sold into `raffle`, the pot being
+/// shared equally between the prizes it still collects.
+pub fn prize_share(raffle: &Account<Raffle>, config: &ProgramConfig) -> Result<u64> {
+    Ok(pot_balance(raffle, config)? / raffle.open_prize_slots().max(1))
+}
+
+/// The seller's part of `available_balance`, the rest being royalties.
+pub fn seller_share(available_balance: u64, royalty_bps: u16) -> u64 {
+    (available_balance * TOTAL_RATE) / (TOTAL_RATE + royalty_bps as u64)
+}
+
+/// Same as `seller_share`, for a share of the token pot, which can be
+/// larger than lamport amounts.
+fn token_seller_share(token_share: u64, royalty_bps: u16) -> Result<u64> {
+    let total_rate = TOTAL_RATE as u128;
+    u64::try_from(token_share as u128 * total_rate / (total_rate + royalty_bps as u128))
+        .map_err(|_| RaffleError::Overflow.into())
+}
+
+/// The seller's payment in lamports and in the payment mint out of the
+/// prize's `share` and `token_share` of the pot. Fails below either of the
+/// seller's minimums, so the slippage guard holds whichever currency the
+/// pot is in.
+fn seller_payments(
+    share: u64,
+    token_share: u64,
+    royalty_bps: u16,
+    min_payment: u64,
+    min_token_payment: u64,
+) -> Result<(u64, u64)> {
+    let payment_to_seller = seller_share(share, royalty_bps);
+    let token_payment_to_seller = token_seller_share(token_share, royalty_bps)?;
+    require!(
+        payment_to_seller >= min_payment && token_payment_to_seller >= min_token_payment,
+        RaffleError::PaymentBelowMinimum
+    );
+    Ok((payment_to_seller, token_payment_to_seller))
+}
+
+/// Pays the `prize_share` of the raffle pot to the seller and the NFT `creators`,
+/// `royalty_bps` going to the creators, in lamports and in the raffle's SPL
+/// payment mint if it has one.
+/// Fails unless the seller gets at least `min_payment` lamports and
+/// `min_token_payment` of the payment mint.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_out_pot<'info>(
+    raffle: &Account<'info, Raffle>,
+    seller: &AccountInfo<'info>,
+    nft_mint: Pubkey,
+    creators: &[Creator],
+    royalty_bps: u16,
+    config: &ProgramConfig,
+    creator_accounts: [Option<&AccountInfo<'info>>; 5],
+    pot_escrow: Option<&Account<'info, TokenAccount>>,
+    seller_token_account: Option<&Account<'info, TokenAccount>>,
+    creator_token_accounts: &[AccountInfo<'info>],
+    token_program: Option<&Program<'info, Token>>,
+    min_payment: u64,
+    min_token_payment: u64,
+) -> Result<PotPayout> {
+    // Escrowed SPL tokens are paid out the same way, in that mint
+    let token_payment = match raffle.payment_mint {
+        Some(payment_mint) => {
+            let (Some(pot_escrow), Some(seller_token_account), Some(token_program)) =
+                (pot_escrow, seller_token_account, token_program) else {
+                return err!(PaymentError::InvalidPaymentAccounts);
+            };
+            require_keys_eq!(
+                pot_escrow.key(),
+                get_associated_token_address(&raffle.key(), &payment_mint),
+                PaymentError::InvalidPaymentAccounts
+            );
+            require!(
+                seller_token_account.mint == payment_mint && seller_token_account.owner == seller.key(),
+                PaymentError::InvalidPaymentAccounts
+            );
+            Some((payment_mint, pot_escrow, seller_token_account, token_program))
+        }
+        None => None,
+    };
+    let token_share = token_payment
+        .map(|(_, pot_escrow, _, _)| pot_escrow.amount / raffle.open_prize_slots().max(1))
+        .unwrap_or_default();
+
+    // Calculate payment amount and royalty amount
+    // Leave enough lamports in the account to cover rent
+    let available_balance = prize_share(raffle, config)?;
+    let (payment_to_seller, mut token_payment_to_seller) =
+        seller_payments(available_balance, token_share, royalty_bps, min_payment, min_token_payment)?;
+    let royalties_payment: u64 = available_balance - payment_to_seller;
+    let mut royalties_paid = 0;
+    let min_creator_rent = Rent::get()?.minimum_balance(0);
+
+    for creator in creators.iter() {
+        // Find the account that matches the current creator
+        if let Some(creator_account) = creator_accounts.iter().flatten().find(|account| {
+            account.key() == creator.address
+        }) {
+            let creator_payment = (royalties_payment * creator.share as u64) / 100;
+
+            // Create a let binding for the AccountInfo object
+            let creator_account_info = creator_account.to_account_info();
+            let mut creator_lamports = creator_account_info.try_borrow_mut_lamports()?;
+            let creator_balance = **creator_lamports;
+
+            // Don't send if the creator's balance is too low to pay rent (cause tx to fail)
+            if (creator_balance + creator_payment) > min_creator_rent {
+                royalties_paid += creator_payment;
+                **creator_lamports += creator_payment;
+                emit!(RoyaltyPaid {
+                    raffle_id: raffle.id,
+                    nft_mint,
+                    creator: creator.address,
+                    payment_mint: None,
+                    amount: creator_payment,
+                });
+            }
+        }
+    }
+
+    // Raffle pays for the SOL to the NFT seller, royalties to creator
+    **raffle.to_account_info().try_borrow_mut_lamports()? -= payment_to_seller + royalties_paid;
+    **seller.try_borrow_mut_lamports()? += payment_to_seller;
+
+    let mut token_royalties_paid = 0;
+    if let Some((payment_mint, pot_escrow, seller_token_account, token_program)) = token_payment {
+        let creator_token_accounts = creator_token_accounts
+            .iter()
+            .map(Account::<TokenAccount>::try_from)
+            .collect::<Result<Vec<_>>>()?;
+        let token_royalties = token_share - token_payment_to_seller;
+
+        let raffle_id_bytes = raffle.id.to_le_bytes();
+        let bump = [raffle.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[RAFFLE_SEED.as_ref(), &raffle_id_bytes, &bump]];
+        let transfer_from_pot = |to: AccountInfo<'info>, amount: u64| {
+            let transfer_context = CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: pot_escrow.to_account_info(),
+                    to,
+                    authority: raffle.to_account_info(),
+                },
+                signer_seeds,
+            );
+            token::transfer(transfer_context, amount)
+        };
+
+        for creator in creators.iter() {
+            if let Some(creator_token_account) = creator_token_accounts.iter().find(|account| {
+                account.owner == creator.address && account.mint == payment_mint
+            }) {
+                let creator_payment = (token_royalties as u128 * creator.share as u128 / 100) as u64;
+                if creator_payment == 0 {
+                    continue;
+                }
+                transfer_from_pot(creator_token_account.to_account_info(), creator_payment)?;
+                token_royalties_paid += creator_payment;
+                emit!(RoyaltyPaid {
+                    raffle_id: raffle.id,
+                    nft_mint,
+                    creator: creator.address,
+                    payment_mint: Some(payment_mint),
+                    amount: creator_payment,
+                });
+            }
+        }
+        // Royalties of creators without a token account go to the seller rather
+        // than stay in the escrow, which nothing else pays out of
+        token_payment_to_seller += token_royalties - token_royalties_paid;
+        transfer_from_pot(seller_token_account.to_account_info(), token_payment_to_seller)?;
+
+        // The last prize empties the escrow
+        if raffle.open_prize_slots() == 1 {
+            token::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: pot_escrow.to_account_info(),
+                    destination: raffle.to_account_info(),
+                    authority: raffle.to_account_info(),
+                },
+                signer_seeds,
+            ))?;
+        }
+    }
+
+    Ok(PotPayout {
+        payment_to_seller,
+        royalties_paid,
+        token_payment_to_seller,
+        token_royalties_paid,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_token_share_like_lamports() {
+        // 5% royalties out of 1.05M tokens
+        assert_eq!(token_seller_share(1_050_000, 500).unwrap(), 1_000_000);
+        assert_eq!(token_seller_share(1_050_000, 500).unwrap(), seller_share(1_050_000, 500));
+        // Too large for the u64 lamport math
+        assert_eq!(token_seller_share(u64::MAX, 0).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn checks_both_minimums() {
+        assert_eq!(
+            seller_payments(1_050_000, 2_100_000, 500, 1_000_000, 2_000_000).unwrap(),
+            (1_000_000, 2_000_000)
+        );
+        // SPL paid raffle: the lamport payout passes, the token payout is short
+        assert_eq!(
+            seller_payments(0, 2_100_000, 500, 0, 2_000_001).err(),
+            Some(RaffleError::PaymentBelowMinimum.into())
+        );
+        assert_eq!(
+            seller_payments(1_050_000, 0, 500, 1_000_001, 0).err(),
+            Some(RaffleError::PaymentBelowMinimum.into())
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use anchor_lang::prelude::*;
+use pyth_sdk_solana::{load_price_feed_from_account_info, Price};
+
+use crate::model::{FeedError, RaffleError};
+
+/// Decimals of a lamport amount.
+pub const SOL_DECIMALS: u8 = 9;
+/// Decimals of a USD cent amount.
+pub const USD_DECIMALS: u8 = 2;
+
+/// $1, for converting from USD amounts.
+const USD: Price = Price { price: 1, conf: 0, expo: 0, publish_time: 0 };
+
+/// Loads the current price from a Pyth feed, rejecting prices older than
+/// `staleness_threshold` seconds or with a confidence interval wider than
+/// `max_confidence_bps` of the price.
+pub fn load_price(
+    feed: &AccountInfo,
+    now: i64,
+    staleness_threshold: u64,
+    max_confidence_bps: u16,
+) -> Result<Price> {
+    let price_feed = load_price_feed_from_account_info(feed)
+        .map_err(|_| error!(FeedError::InvalidPriceFeed))?;
+    let price = price_feed
+        .get_price_no_older_than(now, staleness_threshold)
+        .ok_or(FeedError::StalePrice)?;
+    require!(price.price > 0, FeedError::InvalidPrice);
+    require!(is_confident(&price, max_confidence_bps), FeedError::PriceUncertain);
+    Ok(price)
+}
+
+pub fn is_confident(price: &Price, max_confidence_bps: u16) -> bool {
+    (price.conf as u128) * 10_000 <= (price.price as u128) * max_confidence_bps as u128
+}
+
+/// Converts `amount` (in units with `from_decimals`) priced at `from_price`
+/// into units with `to_decimals` priced at `to_price`, rounding up so the
+/// buyer never underpays.
+pub fn convert_amount(
+    amount: u64,
+    from_price: &Price,
+    from_decimals: u8,
+    to_price: &Price,
+    to_decimals: u8,
+) -> Result<u64> {
+    require!(from_price.price > 0 && to_price.price > 0, FeedError::InvalidPrice);
+    let exponent = from_price.expo - to_price.expo + to_decimals as i32 - from_decimals as i32;
+    let scale = 10u128
+        .checked_pow(exponent.unsigned_abs())
+        .ok_or(RaffleError::Overflow)?;
+
+    let mut numerator = (amount as u128)
+        .checked_mul(from_price.price as u128)
+        .ok_or(RaffleError::Overflow)?;
+    let mut denominator = to_price.price as u128;
+    if exponent >= 0 {
+        numerator = numerator.checked_mul(scale).ok_or(RaffleError::Overflow)?;
+    } else {
+        denominator = denominator.checked_mul(scale).ok_or(RaffleError::Overflow)?;
+    }
+    let converted = numerator
+        .checked_add(denominator - 1)
+        .ok_or(RaffleError::Overflow)?
+        / denominator;
+    Ok(u64::try_from(converted).map_err(|_| RaffleError::Overflow)?)
+}
+
+/// Token amount worth `lamports` at the given SOL and token prices.
+pub fn lamports_to_tokens(lamports: u64, sol_price: &Price, token_price: &Price, decimals: u8) -> Result<u64> {
+    convert_amount(lamports, sol_price, SOL_DECIMALS, token_price, decimals)
+}
+
+/// Lamports worth `cents` USD at the given SOL price.
+pub fn usd_cents_to_lamports(cents: u64, sol_price: &Price) -> Result<u64> {
+    convert_amount(cents, &USD, USD_DECIMALS, sol_price, SOL_DECIMALS)
+}
+
+/// Token amount worth `cents` USD at the given token price.
+pub fn usd_cents_to_tokens(cents: u64, token_price: &Price, decimals: u8) -> Result<u64> {
+    convert_amount(cents, &USD, USD_DECIMALS, token_price, decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(price: i64, conf: u64, expo: i32) -> Price {
+        Price { price, conf, expo, publish_time: 0 }
+    }
+
+    #[test]
+    fn converts_lamports_to_usdc() {
+        // 0.67 SOL at $20.00 is $13.40
+        let sol = price(2_000_000_000, 0, -8);
+        let usdc = price(100_000_000, 0, -8);
+        assert_eq!(lamports_to_tokens(670_000_000, &sol, &usdc, 6).unwrap(), 13_400_000);
+    }
+
+    #[test]
+    fn rounds_up_partial_units() {
+        // 1 lamport at $20.00 is a fraction of a USDC unit
+        let sol = price(2_000_000_000, 0, -8);
+        let usdc = price(100_000_000, 0, -8);
+        assert_eq!(lamports_to_tokens(1, &sol, &usdc, 6).unwrap(), 1);
+    }
+
+    #[test]
+    fn handles_mismatched_exponents() {
+        // BONK at $0.00001 (expo -10), 0.5 SOL at $20.00 is $10 = 1M BONK
+        let sol = price(20_000, 0, -3);
+        let bonk = price(100_000, 0, -10);
+        assert_eq!(lamports_to_tokens(500_000_000, &sol, &bonk, 5).unwrap(), 100_000_000_000);
+    }
+
+    #[test]
+    fn converts_usd_cents() {
+        // $10.00 at $20.00/SOL is 0.5 SOL, and 10 USDC
+        let sol = price(2_000_000_000, 0, -8);
+        let usdc = price(100_000_000, 0, -8);
+        assert_eq!(usd_cents_to_lamports(1_000, &sol).unwrap(), 500_000_000);
+        assert_eq!(usd_cents_to_tokens(1_000, &usdc, 6).unwrap(), 10_000_000);
+    }
+
+    #[test]
+    fn rejects_wide_confidence() {
+        assert!(is_confident(&price(10_000, 200, -4), 200));
+        assert!(!is_confident(&price(10_000, 201, -4), 200));
+    }
+}
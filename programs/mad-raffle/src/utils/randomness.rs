@@ -0,0 +1,291 @@
+use anchor_lang::prelude::*;
+use solana_program::{hash, keccak::hashv};
+
+use crate::model::{PrizeError, RaffleError, SuperRaffleError};
+use crate::state::{Season, TicketReceipt, UserStats};
+
+/// Size of a single (slot, hash) entry in the SlotHashes sysvar.
+const SLOT_HASH_ENTRY_SIZE: usize = 8 + 32;
+
+/// Hashes the given entropy sources into a single u64.
+pub fn hash_entropy(sources: &[&[u8]]) -> u64 {
+    let hash = hashv(sources).to_bytes();
+    u64::from_le_bytes(hash[..8].try_into().unwrap())
+}
+
+/// Maps entropy to a ticket index in `0..total_tickets`.
+/// Returns None if no tickets were sold.
+pub fn winning_ticket_index(total_tickets: u32, entropy: u64) -> Option<u32> {
+    if total_tickets == 0 {
+        return None;
+    }
+    Some((entropy % total_tickets as u64) as u32)
+}
+
+/// Picks the winning ticket from `total_tickets` and returns its owner.
+/// The caller supplies the receipt holding that ticket, which is found off chain
+/// by the same entropy, so no other holders need to be loaded.
+pub fn winner_from_entropy(total_tickets: u32, receipt: &TicketReceipt, entropy: u64) -> Result<Pubkey> {
+    let index = winning_ticket_index(total_tickets, entropy).ok_or(RaffleError::NoEligibleTickets)?;
+    require!(receipt.contains(index), RaffleError::WrongTicketReceipt);
+    Ok(receipt.user)
+}
+
+/// Picks the winning point from a super raffle tally of `total_points` and
+/// checks it falls in the range `stats` was tallied with, as with tickets.
+pub fn points_winner(
+    total_points: u64,
+    draw_id: u64,
+    season: &Season,
+    stats: &UserStats,
+    entropy: u64,
+) -> Result<Pubkey> {
+    require!(total_points > 0, SuperRaffleError::NoPoints);
+    let index = entropy % total_points;
+    require!(stats.holds_tally_index(season, draw_id, index), PrizeError::InvalidWinner);
+    Ok(stats.user)
+}
+
+/// Randomness to draw winners from, mixing a revealed secret with the hash
+/// of a slot recorded after the raffle ended.
+pub fn reveal_seed(secret: [u8; 32], slot_hash: [u8; 32]) -> [u8; 32] {
+    hashv(&[&secret, &slot_hash]).to_bytes()
+}
+
+/// Entropy for the `nonce`th draw of a raffle from its seed.
+pub fn draw_entropy(seed: &[u8; 32], raffle_id: u64, nonce: u32) -> u64 {
+    hash_entropy(&[seed, &raffle_id.to_le_bytes(), &nonce.to_le_bytes()])
+}
+
+/// The commitment stored on a raffle for a given secret (sha256).
+pub fn commitment_for(secret: &[u8; 32]) -> [u8; 32] {
+    hash::hash(secret).to_bytes()
+}
+
+/// Secret of the commitment a raffle falls back to when its operator never
+/// revealed in time. Anyone can reveal it, the slot hash alone decides.
+pub const PUBLIC_REVEAL_SECRET: [u8; 32] = [0; 32];
+
+/// Whether the hash of the first slot after `slot` has been evicted from the
+/// SlotHashes sysvar, so a draw against it can never be revealed.
+pub fn slot_hash_expired(data: &[u8], slot: u64) -> bool {
+    slot_hash_after(data, slot).err() == Some(RaffleError::RandomnessExpired.into())
+}
+
+/// Finds the hash of the first slot after `slot` in raw SlotHashes sysvar data.
+/// Entries are stored newest first, so the sysvar only covers the last ~512 slots.
+pub fn slot_hash_after(data: &[u8], slot: u64) -> Result<[u8; 32]> {
+    let len = data
+        .get(..8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(RaffleError::SlotHashNotAvailable)? as usize;
+
+    let mut found: Option<[u8; 32]> = None;
+    for i in 0..len {
+        let start = 8 + i * SLOT_HASH_ENTRY_SIZE;
+        let entry = data
+            .get(start..start + SLOT_HASH_ENTRY_SIZE)
+            .ok_or(RaffleError::SlotHashNotAvailable)?;
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot <= slot {
+            return found.ok_or_else(|| error!(RaffleError::SlotHashNotAvailable));
+        }
+        found = Some(entry[8..].try_into().unwrap());
+    }
+    if len == 0 {
+        return Err(error!(RaffleError::SlotHashNotAvailable));
+    }
+    // Every recorded slot is after `slot`, so the one we need has been evicted
+    Err(error!(RaffleError::RandomnessExpired))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipts(quantities: &[u8]) -> Vec<TicketReceipt> {
+        let mut start = 0u32;
+        quantities
+            .iter()
+            .map(|qty| {
+                let receipt = TicketReceipt {
+                    raffle_id: 1,
+                    bump: 255,
+                    user: Pubkey::new_unique(),
+                    start,
+                    qty: *qty,
+                };
+                start += *qty as u32;
+                receipt
+            })
+            .collect()
+    }
+
+    #[test]
+    fn no_tickets_has_no_index() {
+        assert_eq!(winning_ticket_index(0, 42), None);
+        assert_eq!(
+            winner_from_entropy(0, &receipts(&[1])[0], 42).unwrap_err(),
+            error!(RaffleError::NoEligibleTickets)
+        );
+    }
+
+    #[test]
+    fn wrong_receipt_is_an_error() {
+        let tickets = receipts(&[1, 1]);
+        assert_eq!(
+            winner_from_entropy(2, &tickets[1], 0).unwrap_err(),
+            error!(RaffleError::WrongTicketReceipt)
+        );
+    }
+
+    #[test]
+    fn index_is_uniform_over_ticket_count() {
+        for total in 1..=64u32 {
+            let rounds = 100u64;
+            let mut counts = vec![0u64; total as usize];
+            for entropy in 0..(total as u64 * rounds) {
+                counts[winning_ticket_index(total, entropy).unwrap() as usize] += 1;
+            }
+            assert!(counts.iter().all(|count| *count == rounds));
+        }
+    }
+
+    #[test]
+    fn every_ticket_is_owned_by_exactly_one_receipt() {
+        let tickets = receipts(&[3, 1, 50, 7]);
+        let total: u32 = tickets.iter().map(|receipt| receipt.qty as u32).sum();
+        let mut wins = vec![0u32; tickets.len()];
+        for entropy in 0..total as u64 {
+            let owners: Vec<usize> = tickets
+                .iter()
+                .enumerate()
+                .filter(|(_, receipt)| winner_from_entropy(total, receipt, entropy).is_ok())
+                .map(|(position, _)| position)
+                .collect();
+            assert_eq!(owners.len(), 1);
+            wins[owners[0]] += 1;
+        }
+        // Each receipt wins exactly once per ticket it holds
+        for (receipt, count) in tickets.iter().zip(wins.iter()) {
+            assert_eq!(receipt.qty as u32, *count);
+        }
+    }
+
+    #[test]
+    fn receipt_contains_respects_boundaries() {
+        let tickets = receipts(&[2, 1]);
+        assert!(tickets[0].contains(0));
+        assert!(tickets[0].contains(1));
+        assert!(!tickets[0].contains(2));
+        assert!(tickets[1].contains(2));
+        assert!(!tickets[1].contains(3));
+    }
+
+    #[test]
+    fn points_winner_is_weighted_by_points() {
+        let mut season = Season {
+            bump: 255,
+            epoch: 0,
+            start_epoch: 0,
+            closed_start_epoch: 0,
+            total_points: 0,
+        };
+        season.initialize(255);
+        let mut users: Vec<UserStats> = (0..3)
+            .map(|_| UserStats {
+                user: Pubkey::new_unique(),
+                bump: 255,
+                epoch: 0,
+                points: 0,
+                points_at_close: 0,
+                lifetime_tickets: 0,
+                raffles_won: 0,
+                nfts_sold: 0,
+                tally_draw: 0,
+                tally_start: 0,
+            })
+            .collect();
+        for (stats, points) in users.iter_mut().zip([5u32, 0, 2]) {
+            stats.add_points(&mut season, points);
+        }
+        let total_points = season.close_epoch(false);
+        let mut tallied = 0u64;
+        for stats in users.iter_mut() {
+            stats.tally_draw = 1;
+            stats.tally_start = tallied;
+            tallied += stats.closed_points(&season) as u64;
+        }
+        assert_eq!(tallied, total_points);
+
+        let mut wins = [0u32; 3];
+        for entropy in 0..7u64 {
+            let winners: Vec<usize> = users
+                .iter()
+                .enumerate()
+                .filter(|(_, stats)| points_winner(total_points, 1, &season, stats, entropy).is_ok())
+                .map(|(position, _)| position)
+                .collect();
+            assert_eq!(winners.len(), 1);
+            wins[winners[0]] += 1;
+        }
+        assert_eq!(wins, [5, 0, 2]);
+        // Ranges from another draw don't count
+        assert!(points_winner(total_points, 2, &season, &users[0], 0).is_err());
+        assert!(points_winner(0, 1, &season, &users[0], 0).is_err());
+    }
+
+    fn slot_hashes(slots: &[u64]) -> Vec<u8> {
+        let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+        for slot in slots {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[*slot as u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn slot_hash_after_picks_next_recorded_slot() {
+        // Newest first, slot 13 was skipped
+        let data = slot_hashes(&[15, 14, 12, 11, 10]);
+        assert_eq!(slot_hash_after(&data, 10).unwrap(), [11; 32]);
+        assert_eq!(slot_hash_after(&data, 12).unwrap(), [14; 32]);
+        assert_eq!(slot_hash_after(&data, 13).unwrap(), [14; 32]);
+    }
+
+    #[test]
+    fn slot_hash_after_rejects_future_and_evicted_slots() {
+        let data = slot_hashes(&[15, 14, 12]);
+        assert!(slot_hash_after(&data, 15).is_err());
+        assert!(slot_hash_after(&data, 20).is_err());
+        assert!(slot_hash_after(&data, 5).is_err());
+        assert!(slot_hash_after(&slot_hashes(&[]), 5).is_err());
+    }
+
+    #[test]
+    fn only_evicted_slot_hashes_are_expired() {
+        let data = slot_hashes(&[15, 14, 12]);
+        assert!(slot_hash_expired(&data, 5));
+        assert!(slot_hash_expired(&data, 11));
+        assert!(!slot_hash_expired(&data, 12));
+        assert!(!slot_hash_expired(&data, 15));
+        assert!(!slot_hash_expired(&slot_hashes(&[]), 5));
+    }
+
+    #[test]
+    fn commitment_matches_only_its_secret() {
+        let secret = [7u8; 32];
+        assert_eq!(commitment_for(&secret), commitment_for(&[7u8; 32]));
+        assert_ne!(commitment_for(&secret), commitment_for(&[8u8; 32]));
+    }
+
+    #[test]
+    fn hashed_entropy_is_deterministic() {
+        let random = Pubkey::new_unique();
+        let a = hash_entropy(&[random.as_ref(), &1u64.to_le_bytes()]);
+        let b = hash_entropy(&[random.as_ref(), &1u64.to_le_bytes()]);
+        let c = hash_entropy(&[random.as_ref(), &2u64.to_le_bytes()]);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+}
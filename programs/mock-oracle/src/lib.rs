@@ -0,0 +1,77 @@
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("MockVrfmEKdY8RFR3AGjs7dH3hqmsymEddA7XgpfCuU");
+
+/// Anchor seeds for a MockRandomness PDA.
+pub const RANDOMNESS_SEED: [u8; 10] = *b"randomness";
+
+/// Stand-in for a VRF oracle. Anyone can fulfill a request with any value,
+/// so this must never be trusted outside of local tests.
+#[program]
+pub mod mock_oracle {
+
+    use super::*;
+    pub fn request(ctx: Context<Request>, requester: Pubkey) -> Result<()> {
+        ctx.accounts.randomness.set_inner(MockRandomness {
+            requester,
+            bump: *ctx.bumps.get("randomness").unwrap(),
+            fulfilled_slot: 0,
+            result: None,
+        });
+        Ok(())
+    }
+
+    pub fn fulfill(ctx: Context<Fulfill>, result: [u8; 32]) -> Result<()> {
+        let randomness = &mut ctx.accounts.randomness;
+        randomness.fulfilled_slot = Clock::get()?.slot;
+        randomness.result = Some(result);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(requester: Pubkey)]
+pub struct Request<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = MockRandomness::get_space(),
+        seeds = [RANDOMNESS_SEED.as_ref(), requester.as_ref()],
+        bump
+    )]
+    pub randomness: Account<'info, MockRandomness>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Fulfill<'info> {
+    #[account(
+        mut,
+        seeds = [RANDOMNESS_SEED.as_ref(), randomness.requester.as_ref()],
+        bump = randomness.bump
+    )]
+    pub randomness: Account<'info, MockRandomness>,
+}
+
+#[account]
+pub struct MockRandomness {
+    pub requester: Pubkey,
+    pub bump: u8,
+    pub fulfilled_slot: u64,
+    pub result: Option<[u8; 32]>,
+}
+
+impl MockRandomness {
+    pub fn get_space() -> usize {
+        8 + // discriminator
+        32 + // requester
+        1 + // bump
+        8 + // fulfilled slot
+        1 + // option
+        32 // result
+    }
+}
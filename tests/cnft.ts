@@ -0,0 +1,209 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { COLLECTION_SEED, CONFIG_SEED, LISTING_BOOK_SEED, SEASON_SEED, TRACKER_SEED, USER_STATS_SEED } from "./helpers/seeds";
+import { revealWinnerAccounts } from "./helpers/tickets";
+import { buyTickets, commitRandomness, currentRaffleId, rafflePda } from "./helpers/raffles";
+import { CNFT_SHARED, LocalTree, MintedCnft } from "./helpers/cnft";
+import { AUTH_KEYPAIR } from "./helpers/keys";
+import { createFundedWallet } from "./utils/pnft";
+import { ConcurrentMerkleTreeAccount, MerkleTree } from "@solana/spl-account-compression";
+const { PublicKey, SystemProgram, SYSVAR_SLOT_HASHES_PUBKEY } = web3;
+
+// Needs Bubblegum and account-compression, cloned into the local validator by Anchor.toml
+describe("Compressed NFT prizes", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+  const programId = program.programId;
+
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync([CONFIG_SEED], programId);
+  const [trackerPda, _trackerBump] = PublicKey.findProgramAddressSync([TRACKER_SEED], programId);
+  const [seasonPda, _seasonBump] = PublicKey.findProgramAddressSync([SEASON_SEED], programId);
+
+  let tree: LocalTree;
+  let collectionSettings: web3.PublicKey;
+
+  const updateConfig = (update: object) =>
+    program.methods.updateConfig(update as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+  // Starts a new raffle, so it snapshots the current config
+  const restartRaffle = async () => {
+    const raffleId = await currentRaffleId(program);
+    await program.methods.cancelRaffle()
+      .accounts({
+        raffle: rafflePda(programId, raffleId),
+        newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+        tracker: trackerPda,
+        config: configPda,
+        signer: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+  };
+
+  const sellCnft = async (seller: web3.Keypair, cnft: MintedCnft, raffleId: bigint) => {
+    const raffle = rafflePda(programId, raffleId);
+    const { asset, proof } = tree.proof(cnft);
+    await program.methods.endRaffleCnft(asset, proof.length, new anchor.BN(0), new anchor.BN(0))
+      .accounts({
+        owner: seller.publicKey,
+        leafDelegate: seller.publicKey,
+        merkleTree: tree.merkleTree.publicKey,
+        treeAuthority: tree.treeAuthority,
+        cnftShared: CNFT_SHARED,
+        systemProgram: SystemProgram.programId,
+        raffle,
+        newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+        tracker: trackerPda,
+        sellerStats: PublicKey.findProgramAddressSync([USER_STATS_SEED, seller.publicKey.toBuffer()], programId)[0],
+        season: seasonPda,
+        config: configPda,
+        creator1: null,
+        creator2: null,
+        creator3: null,
+        creator4: null,
+        creator5: null,
+        potEscrow: null,
+        sellerTokenAccount: null,
+        paymentTokenProgram: null,
+        collectionSettings,
+        listingBook: PublicKey.findProgramAddressSync([LISTING_BOOK_SEED], programId)[0],
+      })
+      .remainingAccounts(proof)
+      .signers([seller])
+      .rpc();
+    tree.transferred(cnft, raffle);
+  };
+
+  const reveal = async (raffleId: bigint, secret: number[]) => {
+    const raffle = rafflePda(programId, raffleId);
+    const tx = await program.methods.revealAndPickWinner(new anchor.BN(raffleId.toString()), secret)
+      .accounts({
+        raffle,
+        ...await revealWinnerAccounts(program, raffle, secret),
+        cranker: AUTH_KEYPAIR.publicKey,
+        config: configPda,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .signers([AUTH_KEYPAIR])
+      .transaction();
+    const { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+    tx.feePayer = AUTH_KEYPAIR.publicKey;
+    tx.recentBlockhash = blockhash;
+    tx.lastValidBlockHeight = lastValidBlockHeight;
+    await web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
+  };
+
+  before(async () => {
+    tree = await LocalTree.create(provider, await createFundedWallet(provider, 5));
+    collectionSettings = PublicKey.findProgramAddressSync([COLLECTION_SEED, tree.collectionMint.toBuffer()], programId)[0];
+    await program.methods.setCollection(true, 0, { fixed: { basisPoints: 0 } } as any)
+      .accounts({
+        collectionSettings,
+        collection: tree.collectionMint,
+        config: configPda,
+        authority: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+  });
+
+  it("sells a cNFT and sends it to the winner", async () => {
+    const { prizesPerRaffle } = await program.account.programConfig.fetch(configPda);
+    await updateConfig({ prizesPerRaffle: 1 });
+    await restartRaffle();
+
+    const raffleId = await currentRaffleId(program);
+    const raffle = rafflePda(programId, raffleId);
+    await buyTickets(provider, program, 2);
+    const secret = await commitRandomness(program);
+    const seller = await createFundedWallet(provider);
+    const cnft = await tree.mint(seller.publicKey, [{ address: seller.publicKey, share: 100 }]);
+    await sellCnft(seller, cnft, raffleId);
+    expect((await program.account.raffle.fetch(raffle)).active).to.equal(false);
+    await reveal(raffleId, secret);
+
+    const { winner } = (await program.account.raffle.fetch(raffle)).prizes[0];
+    const { asset, proof } = tree.proof(cnft);
+    await program.methods.distributePrizeCnft(new anchor.BN(raffleId.toString()), 0, asset)
+      .accounts({
+        authority: AUTH_KEYPAIR.publicKey,
+        winner,
+        merkleTree: tree.merkleTree.publicKey,
+        treeAuthority: tree.treeAuthority,
+        cnftShared: CNFT_SHARED,
+        systemProgram: SystemProgram.programId,
+        raffle,
+        config: configPda,
+      })
+      .remainingAccounts(proof)
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    tree.transferred(cnft, winner);
+
+    const { prizes } = await program.account.raffle.fetch(raffle);
+    expect(prizes[0].sent).to.equal(true);
+    expect(prizes[0].rolledOver).to.equal(false);
+    // The on-chain root only matches the mirror if the leaf now belongs to the winner
+    const onChain = await ConcurrentMerkleTreeAccount.fromAccountAddress(connection, tree.merkleTree.publicKey);
+    expect(onChain.getCurrentRoot().equals(new MerkleTree(tree.leaves).root)).to.equal(true);
+
+    await updateConfig({ prizesPerRaffle });
+  });
+
+  it("rolls over an unclaimed cNFT prize", async () => {
+    const { claimWindow, prizesPerRaffle } = await program.account.programConfig.fetch(configPda);
+    await updateConfig({ claimWindow: new anchor.BN(1), prizesPerRaffle: 1 });
+    await restartRaffle();
+
+    const raffleId = await currentRaffleId(program);
+    const raffle = rafflePda(programId, raffleId);
+    await buyTickets(provider, program, 1);
+    const secret = await commitRandomness(program);
+    const seller = await createFundedWallet(provider);
+    const cnft = await tree.mint(seller.publicKey, [{ address: seller.publicKey, share: 100 }]);
+    await sellCnft(seller, cnft, raffleId);
+    await reveal(raffleId, secret);
+
+    // The only holder let the claim window lapse, so nobody is left to draw
+    await new Promise((resolve) => setTimeout(resolve, 2000));
+    await program.methods.redrawUnclaimed(new anchor.BN(raffleId.toString()), 0)
+      .accounts({ raffle })
+      .rpc();
+
+    const activeRaffle = rafflePda(programId, await currentRaffleId(program));
+    const { asset, proof } = tree.proof(cnft);
+    await program.methods.distributePrizeCnft(new anchor.BN(raffleId.toString()), 0, asset)
+      .accounts({
+        authority: AUTH_KEYPAIR.publicKey,
+        winner: activeRaffle,
+        merkleTree: tree.merkleTree.publicKey,
+        treeAuthority: tree.treeAuthority,
+        cnftShared: CNFT_SHARED,
+        systemProgram: SystemProgram.programId,
+        raffle,
+        config: configPda,
+      })
+      .remainingAccounts(proof)
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    tree.transferred(cnft, activeRaffle);
+
+    const { prizes } = await program.account.raffle.fetch(raffle);
+    expect(prizes[0].rolledOver).to.equal(true);
+    expect(prizes[0].sent).to.equal(true);
+    expect(prizes[0].winner?.toBase58()).to.equal(activeRaffle.toBase58());
+    const active = await program.account.raffle.fetch(activeRaffle);
+    expect(active.prizes.map((slot) => slot.prize.assetId?.toBase58()))
+      .to.include(cnft.assetId.toBase58());
+
+    await updateConfig({ claimWindow, prizesPerRaffle });
+  });
+});
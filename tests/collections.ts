@@ -0,0 +1,118 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { assert, expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { CONFIG_SEED } from "./helpers/seeds";
+import { AUTH_KEYPAIR } from "./helpers/keys";
+import { buildAndSendTx, createAndFundATA, createFundedWallet } from "./utils/pnft";
+import { PNftTransferClient } from './utils/PNftTransferClient';
+const { PublicKey, Keypair } = web3;
+
+describe("Partner collections", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const pNftTransferClient = new PNftTransferClient(provider.connection, provider.wallet as anchor.Wallet);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+  const collection = Keypair.generate();
+  const collectionSettings = pNftTransferClient.findCollectionSettingsPDA(collection.publicKey);
+  const fixedRoyalty = { fixed: { basisPoints: 0 } };
+
+  let seller: web3.Keypair;
+  let mint: web3.PublicKey;
+  let ata: web3.PublicKey;
+
+  before(async () => {
+    seller = await createFundedWallet(provider);
+    ({ mint, ata } = await createAndFundATA({
+      provider: provider,
+      owner: seller,
+      creators: [{ address: Keypair.generate().publicKey, share: 100 }],
+      royaltyBps: 500,
+      programmable: true,
+      ruleSetAddr: new PublicKey('eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9'),
+      collection,
+      collectionVerified: true,
+    }));
+  });
+
+  const listPartnerNft = async (settings: web3.PublicKey | null) => {
+    const builder = await pNftTransferClient.buildListPNFT({
+      nftMint: mint,
+      sourceAta: ata,
+      seller: seller.publicKey,
+      ask: new anchor.BN(web3.LAMPORTS_PER_SOL),
+      collectionSettings: settings,
+    });
+    await buildAndSendTx({ provider, ixs: [await builder.instruction()], extraSigners: [seller] });
+  };
+
+  const setCollection = (enabled: boolean, royaltyPolicy: object, authority = AUTH_KEYPAIR) =>
+    program.methods.setCollection(enabled, 25, royaltyPolicy as any)
+      .accounts({
+        collectionSettings,
+        collection: collection.publicKey,
+        config: configPda,
+        authority: authority.publicKey,
+      })
+      .signers([authority])
+      .rpc();
+
+  it("unauthorized cannot set a collection", async () => {
+    const unauthorized = await createFundedWallet(provider);
+    try {
+      await setCollection(true, fixedRoyalty, unauthorized);
+      assert.fail("Setting the collection should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("UnauthorizedSigner");
+    }
+  });
+
+  it("rejects a royalty above 100%", async () => {
+    try {
+      await setCollection(true, { fixed: { basisPoints: 10001 } });
+      assert.fail("Setting the collection should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("InvalidRoyalty");
+    }
+  });
+
+  it("only accepts enabled partner collections", async () => {
+    try {
+      await listPartnerNft(null);
+      assert.fail("Listing outside the default collection should fail");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("InvalidCollectionAddress");
+    }
+
+    await setCollection(false, fixedRoyalty);
+    try {
+      // Another collection's settings can't stand in for the disabled ones
+      const otherSettings = pNftTransferClient.findCollectionSettingsPDA(Keypair.generate().publicKey);
+      await listPartnerNft(otherSettings);
+      assert.fail("Listing with another collection's settings should fail");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("InvalidCollectionSettings");
+    }
+    try {
+      await listPartnerNft(collectionSettings);
+      assert.fail("Listing from a disabled collection should fail");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("CollectionDisabled");
+    }
+
+    await setCollection(true, fixedRoyalty);
+    await listPartnerNft(collectionSettings);
+    const listing = await program.account.listing.fetch(pNftTransferClient.findListingPDA(mint));
+    assert.strictEqual(listing.royaltyBps, 0, "the collection's fixed royalty should apply");
+
+    const cancel = await pNftTransferClient.buildCancelListing({ nftMint: mint, seller: seller.publicKey });
+    await buildAndSendTx({ provider, ixs: [await cancel.instruction()], extraSigners: [seller] });
+  });
+});
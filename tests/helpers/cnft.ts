@@ -0,0 +1,203 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { keypairIdentity, Metaplex } from "@metaplex-foundation/js";
+import {
+    computeCompressedNFTHash,
+    createCreateTreeInstruction,
+    createMintToCollectionV1Instruction,
+    getLeafAssetId,
+    MetadataArgs,
+    PROGRAM_ID as BUBBLEGUM_PROGRAM_ID,
+    TokenProgramVersion,
+    TokenStandard,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+    createAllocTreeIx,
+    MerkleTree,
+    SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    SPL_NOOP_PROGRAM_ID,
+} from "@solana/spl-account-compression";
+import { buildAndSendTx } from "../utils/pnft";
+const { PublicKey, Keypair } = web3;
+
+const TMETA_PROG_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
+// Smallest depth/buffer pair account-compression accepts, 32 leaves
+const MAX_DEPTH = 5;
+const MAX_BUFFER_SIZE = 8;
+
+/**
+ * The `cnft_shared` accounts of `end_raffle_cnft` and `distribute_prize_cnft`.
+ */
+export const CNFT_SHARED = {
+    bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+    compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+    logWrapper: SPL_NOOP_PROGRAM_ID,
+};
+
+/**
+ *
+ * @returns the Bubblegum tree authority PDA of `merkleTree`
+ */
+export function treeAuthorityPda(merkleTree: web3.PublicKey): web3.PublicKey {
+    return PublicKey.findProgramAddressSync([merkleTree.toBuffer()], BUBBLEGUM_PROGRAM_ID)[0];
+}
+
+/**
+ * A compressed NFT minted by `LocalTree`, in the shape of the program's `CompressedAsset`
+ * minus the root, which changes with every write to the tree.
+ */
+export interface MintedCnft {
+    assetId: web3.PublicKey;
+    nonce: number;
+    metadata: MetadataArgs;
+}
+
+/**
+ * A Bubblegum tree whose leaves are mirrored locally, since the local validator
+ * has no DAS API to fetch proofs from. Every write to the tree has to go
+ * through `mint` or `transferred` to keep the proofs valid.
+ */
+export class LocalTree {
+    provider: anchor.AnchorProvider;
+    merkleTree: web3.Keypair;
+    creator: web3.Keypair;
+    collectionMint: web3.PublicKey;
+    leaves: Buffer[] = Array.from({ length: 2 ** MAX_DEPTH }, () => Buffer.alloc(32));
+    minted = 0;
+
+    private constructor(provider: anchor.AnchorProvider, creator: web3.Keypair) {
+        this.provider = provider;
+        this.creator = creator;
+        this.merkleTree = Keypair.generate();
+    }
+
+    /**
+     * Creates a tree owned by `creator`, along with a collection NFT they are
+     * the update authority of, so its cNFTs can be minted verified.
+     */
+    static async create(provider: anchor.AnchorProvider, creator: web3.Keypair): Promise<LocalTree> {
+        const tree = new LocalTree(provider, creator);
+        const allocTreeIx = await createAllocTreeIx(
+            provider.connection,
+            tree.merkleTree.publicKey,
+            creator.publicKey,
+            { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE },
+            0
+        );
+        const createTreeIx = createCreateTreeInstruction(
+            {
+                treeAuthority: tree.treeAuthority,
+                merkleTree: tree.merkleTree.publicKey,
+                payer: creator.publicKey,
+                treeCreator: creator.publicKey,
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+            },
+            { maxDepth: MAX_DEPTH, maxBufferSize: MAX_BUFFER_SIZE, public: false }
+        );
+        await buildAndSendTx({
+            provider,
+            ixs: [allocTreeIx, createTreeIx],
+            extraSigners: [creator, tree.merkleTree],
+        });
+
+        const mplex = new Metaplex(provider.connection).use(keypairIdentity(creator));
+        const { mintAddress } = await mplex.nfts().create({
+            uri: 'https://madlads.s3.us-west-2.amazonaws.com/json/8420.json',
+            name: 'Mad Lads KING',
+            sellerFeeBasisPoints: 0,
+            isCollection: true,
+        }, { commitment: 'finalized' });
+        tree.collectionMint = mintAddress;
+        return tree;
+    }
+
+    get treeAuthority(): web3.PublicKey {
+        return treeAuthorityPda(this.merkleTree.publicKey);
+    }
+
+    /**
+     * Mints a cNFT of the tree's collection to `owner`, with `creators` unverified.
+     */
+    async mint(owner: web3.PublicKey, creators: { address: web3.PublicKey, share: number }[], royaltyBps = 500): Promise<MintedCnft> {
+        const metadata: MetadataArgs = {
+            name: 'Mad Lads #3482',
+            symbol: 'MAD',
+            uri: 'https://madlads.s3.us-west-2.amazonaws.com/json/3482.json',
+            sellerFeeBasisPoints: royaltyBps,
+            primarySaleHappened: true,
+            isMutable: true,
+            editionNonce: null,
+            tokenStandard: TokenStandard.NonFungible,
+            collection: { verified: false, key: this.collectionMint },
+            uses: null,
+            tokenProgramVersion: TokenProgramVersion.Original,
+            creators: creators.map(({ address, share }) => ({ address, share, verified: false })),
+        };
+        const metadataPda = (seeds: Buffer[]) =>
+            PublicKey.findProgramAddressSync([Buffer.from('metadata'), TMETA_PROG_ID.toBuffer(), ...seeds], TMETA_PROG_ID)[0];
+        const mintIx = createMintToCollectionV1Instruction(
+            {
+                treeAuthority: this.treeAuthority,
+                leafOwner: owner,
+                leafDelegate: owner,
+                merkleTree: this.merkleTree.publicKey,
+                payer: this.creator.publicKey,
+                treeDelegate: this.creator.publicKey,
+                collectionAuthority: this.creator.publicKey,
+                collectionAuthorityRecordPda: BUBBLEGUM_PROGRAM_ID,
+                collectionMint: this.collectionMint,
+                collectionMetadata: metadataPda([this.collectionMint.toBuffer()]),
+                editionAccount: metadataPda([this.collectionMint.toBuffer(), Buffer.from('edition')]),
+                bubblegumSigner: PublicKey.findProgramAddressSync([Buffer.from('collection_cpi')], BUBBLEGUM_PROGRAM_ID)[0],
+                logWrapper: SPL_NOOP_PROGRAM_ID,
+                compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+                tokenMetadataProgram: TMETA_PROG_ID,
+            },
+            { metadataArgs: metadata }
+        );
+        await buildAndSendTx({ provider: this.provider, ixs: [mintIx], extraSigners: [this.creator] });
+
+        // Bubblegum verifies the collection as it mints
+        metadata.collection.verified = true;
+        const nonce = this.minted++;
+        const assetId = await getLeafAssetId(this.merkleTree.publicKey, new anchor.BN(nonce));
+        const cnft = { assetId, nonce, metadata };
+        this.transferred(cnft, owner);
+        return cnft;
+    }
+
+    /**
+     * Records that `cnft` is now owned, and delegated, to `owner`.
+     */
+    transferred(cnft: MintedCnft, owner: web3.PublicKey) {
+        this.leaves[cnft.nonce] = computeCompressedNFTHash(cnft.assetId, owner, owner, new anchor.BN(cnft.nonce), cnft.metadata);
+    }
+
+    /**
+     * The `asset` argument and proof accounts for moving `cnft` out of its current owner.
+     */
+    proof(cnft: MintedCnft): { asset: any, proof: web3.AccountMeta[] } {
+        const { root, proof } = new MerkleTree(this.leaves).getProof(cnft.nonce);
+        return {
+            asset: {
+                root: Array.from(root),
+                nonce: new anchor.BN(cnft.nonce),
+                index: cnft.nonce,
+                metadata: toMetadataArgsLocal(cnft.metadata),
+            },
+            proof: proof.map((node) => ({ pubkey: new PublicKey(node), isSigner: false, isWritable: false })),
+        };
+    }
+}
+
+/**
+ * Bubblegum's `MetadataArgs` as the program's Anchor `MetadataArgsLocal`.
+ */
+function toMetadataArgsLocal(metadata: MetadataArgs): object {
+    return {
+        ...metadata,
+        tokenStandard: { nonFungible: {} },
+        tokenProgramVersion: { original: {} },
+    };
+}
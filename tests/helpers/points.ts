@@ -0,0 +1,67 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { MadRaffle } from "../../target/types/mad_raffle";
+import { SEASON_SEED, USER_STATS_SEED } from "./seeds";
+const { PublicKey } = web3;
+
+/**
+ * 
+ * @returns the UserStats PDA for `user`
+ */
+export function userStatsPda(programId: web3.PublicKey, user: web3.PublicKey): web3.PublicKey {
+    return PublicKey.findProgramAddressSync([USER_STATS_SEED, user.toBuffer()], programId)[0];
+}
+
+/**
+ * 
+ * @returns the points Season PDA
+ */
+export function seasonPda(programId: web3.PublicKey): web3.PublicKey {
+    return PublicKey.findProgramAddressSync([SEASON_SEED], programId)[0];
+}
+
+/**
+ * Tallies every UserStats account for the committed super raffle draw, in
+ * ascending user order and `batchSize` accounts per transaction.
+ */
+export async function tallySuperRaffle(
+    program: anchor.Program<MadRaffle>,
+    accounts: { superDraw: web3.PublicKey, season: web3.PublicKey, config: web3.PublicKey },
+    authority: web3.Keypair,
+    batchSize = 20
+): Promise<web3.PublicKey[]> {
+    const stats = (await program.account.userStats.all())
+        .map(({ publicKey, account }) => ({ publicKey, user: account.user as web3.PublicKey }))
+        .sort((a, b) => Buffer.compare(a.user.toBuffer(), b.user.toBuffer()));
+    for (let i = 0; i < stats.length; i += batchSize) {
+        await program.methods.tallySuperRaffle(i === 0)
+            .accounts({ ...accounts, authority: authority.publicKey })
+            .remainingAccounts(stats.slice(i, i + batchSize).map(({ publicKey }) => ({
+                pubkey: publicKey,
+                isSigner: false,
+                isWritable: true,
+            })))
+            .signers([authority])
+            .rpc();
+    }
+    return stats.map(({ publicKey }) => publicKey);
+}
+
+/**
+ * Mirrors `utils::points_winner`: the tallied user whose range holds
+ * `entropy % totalPoints`. Ranges are contiguous in tally order.
+ */
+export async function tallyWinner(
+    program: anchor.Program<MadRaffle>,
+    tallied: web3.PublicKey[],
+    totalPoints: bigint,
+    entropy: bigint
+): Promise<web3.PublicKey> {
+    const index = entropy % totalPoints;
+    const stats = await program.account.userStats.fetchMultiple(tallied);
+    let winner: web3.PublicKey;
+    for (const account of stats) {
+        if (BigInt(account.tallyStart.toString()) <= index) winner = account.user;
+    }
+    return winner;
+}
@@ -0,0 +1,123 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import { MadRaffle } from "../../target/types/mad_raffle";
+import { CONFIG_SEED, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, raffleNumberBuffer } from "./seeds";
+import { newRandomnessCommitment } from "./randomness";
+import { ticketAccounts } from "./tickets";
+import { AUTH_KEYPAIR, COLLECTION_KEYPAIR, VAULT_KEYPAIR } from "./keys";
+import { buildAndSendTx, createAndFundATA, createFundedWallet } from "../utils/pnft";
+import { PNftTransferClient } from "../utils/PNftTransferClient";
+const { PublicKey, Keypair } = web3;
+
+/**
+ *
+ * @returns the Raffle PDA for raffle `raffleId`
+ */
+export function rafflePda(programId: web3.PublicKey, raffleId: bigint): web3.PublicKey {
+    return PublicKey.findProgramAddressSync([RAFFLE_SEED, raffleNumberBuffer(raffleId)], programId)[0];
+}
+
+/**
+ *
+ * @returns the id of the active raffle
+ */
+export async function currentRaffleId(program: anchor.Program<MadRaffle>): Promise<bigint> {
+    const [trackerPda] = PublicKey.findProgramAddressSync([TRACKER_SEED], program.programId);
+    const { currentRaffle } = await program.account.raffleTracker.fetch(trackerPda);
+    return BigInt(currentRaffle.toString());
+}
+
+/**
+ * Buys one ticket in the active raffle for each of `count` new wallets.
+ */
+export async function buyTickets(
+    provider: anchor.AnchorProvider,
+    program: anchor.Program<MadRaffle>,
+    count: number
+): Promise<web3.Keypair[]> {
+    const programId = program.programId;
+    const raffle = rafflePda(programId, await currentRaffleId(program));
+    const buyers = [];
+    for (let i = 0; i < count; i++) {
+        const buyer = await createFundedWallet(provider, 1);
+        await program.methods
+            .buyTicket(1)
+            .accounts({
+                raffle,
+                ...await ticketAccounts(program, raffle, buyer.publicKey),
+                buyer: buyer.publicKey,
+                feeVault: VAULT_KEYPAIR.publicKey,
+                tracker: PublicKey.findProgramAddressSync([TRACKER_SEED], programId)[0],
+                superVault: PublicKey.findProgramAddressSync([SUPER_RAFFLE_SEED], programId)[0],
+                config: PublicKey.findProgramAddressSync([CONFIG_SEED], programId)[0],
+            })
+            .signers([buyer])
+            .rpc();
+        buyers.push(buyer);
+    }
+    return buyers;
+}
+
+/**
+ * Commits a new secret for the active raffle and returns it.
+ */
+export async function commitRandomness(program: anchor.Program<MadRaffle>): Promise<number[]> {
+    const programId = program.programId;
+    const { secret, commitment } = newRandomnessCommitment();
+    await program.methods.commitRandomness(commitment)
+        .accounts({
+            raffle: rafflePda(programId, await currentRaffleId(program)),
+            tracker: PublicKey.findProgramAddressSync([TRACKER_SEED], programId)[0],
+            authority: AUTH_KEYPAIR.publicKey,
+            config: PublicKey.findProgramAddressSync([CONFIG_SEED], programId)[0],
+        })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+    return secret;
+}
+
+/**
+ * Sells new pNFTs of the default collection into the active raffle until it
+ * collected all its prizes, which ends it. Returns the id of the ended raffle.
+ */
+export async function endRaffleWithPNfts(
+    provider: anchor.AnchorProvider,
+    program: anchor.Program<MadRaffle>
+): Promise<bigint> {
+    const pNftTransferClient = new PNftTransferClient(provider.connection, provider.wallet as anchor.Wallet);
+    const programId = program.programId;
+    const raffleId = await currentRaffleId(program);
+    const raffle = rafflePda(programId, raffleId);
+    while ((await program.account.raffle.fetch(raffle)).active) {
+        const seller = await createFundedWallet(provider);
+        const creators = [{ address: Keypair.generate().publicKey, share: 100 }];
+        const { mint, ata } = await createAndFundATA({
+            provider: provider,
+            owner: seller,
+            creators,
+            royaltyBps: 50,
+            programmable: true,
+            ruleSetAddr: new PublicKey('eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9'),
+            collection: COLLECTION_KEYPAIR,
+            collectionVerified: true,
+            skipNewCollection: true
+        });
+        const builder = await pNftTransferClient.buildTransferPNFT({
+            sourceAta: ata,
+            nftMint: mint,
+            destAta: await getAssociatedTokenAddress(mint, raffle, true),
+            owner: seller.publicKey,
+            tracker: PublicKey.findProgramAddressSync([TRACKER_SEED], programId)[0],
+            raffle,
+            newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+            creators: creators.map(creator => creator.address),
+        });
+        await buildAndSendTx({
+            provider,
+            ixs: [await builder.instruction()],
+            extraSigners: [seller],
+        });
+    }
+    return raffleId;
+}
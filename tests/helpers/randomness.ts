@@ -0,0 +1,72 @@
+import { createHash, randomBytes } from "crypto";
+import { Connection, SYSVAR_SLOT_HASHES_PUBKEY } from "@solana/web3.js";
+import { keccak_256 } from "@noble/hashes/sha3";
+
+/**
+ * 
+ * @returns a random 32 byte secret and its sha256 commitment
+ */
+export function newRandomnessCommitment(): { secret: number[], commitment: number[] } {
+    const secret = randomBytes(32);
+    const commitment = createHash("sha256").update(secret).digest();
+    return { secret: [...secret], commitment: [...commitment] };
+}
+
+/**
+ * Mirrors `utils::slot_hash_after`: the hash of the first slot after `slot`
+ * in raw SlotHashes sysvar data (entries are newest first).
+ */
+export function slotHashAfter(data: Buffer, slot: bigint): Buffer | null {
+    const len = Number(data.readBigUInt64LE(0));
+    let found: Buffer | null = null;
+    for (let i = 0; i < len; i++) {
+        const start = 8 + i * 40;
+        const entrySlot = data.readBigUInt64LE(start);
+        if (entrySlot <= slot) return found;
+        found = data.subarray(start + 8, start + 40);
+    }
+    return null;
+}
+
+/**
+ * Mirrors `utils::hash_entropy`: first 8 bytes (LE) of keccak256 over the sources.
+ */
+export function hashEntropy(sources: Uint8Array[]): bigint {
+    const hash = keccak_256(Buffer.concat(sources));
+    return Buffer.from(hash).readBigUInt64LE(0);
+}
+
+/**
+ * Mirrors `utils::reveal_seed`: keccak256 of the secret and the slot hash.
+ */
+export function revealSeed(secret: number[], slotHash: Buffer): Buffer {
+    return Buffer.from(keccak_256(Buffer.concat([Buffer.from(secret), slotHash])));
+}
+
+/**
+ * Mirrors `utils::draw_entropy`: entropy of the `nonce`th draw of raffle `raffleId`.
+ */
+export function drawEntropy(seed: Uint8Array, raffleId: Uint8Array, nonce: number): bigint {
+    const nonceBytes = Buffer.alloc(4);
+    nonceBytes.writeUInt32LE(nonce);
+    return hashEntropy([seed, raffleId, nonceBytes]);
+}
+
+/**
+ * Mirrors `utils::PUBLIC_REVEAL_SECRET`, the secret of a raffle that fell back to public randomness.
+ */
+export const PUBLIC_REVEAL_SECRET: number[] = Array(32).fill(0);
+
+/**
+ * Waits until the SlotHashes sysvar no longer holds the hash after `slot`, which
+ * makes a reveal against it fail with `RandomnessExpired` (~512 slots).
+ */
+export async function waitForSlotHashEviction(connection: Connection, slot: bigint): Promise<void> {
+    for (;;) {
+        const { data } = await connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+        const len = Number(data.readBigUInt64LE(0));
+        const oldest = data.readBigUInt64LE(8 + (len - 1) * 40);
+        if (oldest > slot + BigInt(1)) return;
+        await new Promise((resolve) => setTimeout(resolve, 5000));
+    }
+}
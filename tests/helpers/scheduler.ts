@@ -0,0 +1,169 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+import { MadRaffle } from "../../target/types/mad_raffle";
+import { CONFIG_SEED, TRACKER_SEED, WORK_QUEUE_SEED } from "./seeds";
+import { drawWinnerAccounts, revealWinnerAccounts } from "./tickets";
+import { PUBLIC_REVEAL_SECRET } from "./randomness";
+import { rafflePda } from "./raffles";
+import { buildAndSendTx } from "../utils/pnft";
+import { PNftTransferClient } from "../utils/PNftTransferClient";
+const { PublicKey } = web3;
+
+/**
+ *
+ * @returns the WorkQueue PDA
+ */
+export function workQueuePda(programId: web3.PublicKey): web3.PublicKey {
+    return PublicKey.findProgramAddressSync([WORK_QUEUE_SEED], programId)[0];
+}
+
+/**
+ * Stands in for an automation thread running the end-of-raffle housekeeping on a cron.
+ * Every account is derived from the tracker, the work queue and the raffles in it; the
+ * only thing it is handed is the commit-reveal secret of each raffle it should pick for.
+ * Oracle draws and compressed prizes are left alone, they need the oracle and a proof.
+ */
+export class LocalScheduler {
+    program: anchor.Program<MadRaffle>;
+    provider: anchor.AnchorProvider;
+    pNftTransferClient: PNftTransferClient;
+    cranker: web3.Keypair;
+    secrets = new Map<string, number[]>();
+
+    constructor(provider: anchor.AnchorProvider, program: anchor.Program<MadRaffle>, cranker: web3.Keypair) {
+        this.provider = provider;
+        this.program = program;
+        this.cranker = cranker;
+        this.pNftTransferClient = new PNftTransferClient(provider.connection, provider.wallet as anchor.Wallet);
+    }
+
+    get workQueue(): web3.PublicKey {
+        return workQueuePda(this.program.programId);
+    }
+
+    async sync(raffleId: bigint) {
+        await this.program.methods.syncWorkQueue(new anchor.BN(raffleId.toString()))
+            .accounts({
+                workQueue: this.workQueue,
+                raffle: rafflePda(this.program.programId, raffleId),
+                cranker: this.cranker.publicKey,
+                slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            })
+            .signers([this.cranker])
+            .rpc();
+    }
+
+    /**
+     * One run: syncs the raffle that ended last, then sends each queued item once and
+     * re-syncs its raffle. Returns the number of items sent.
+     */
+    async tick(): Promise<number> {
+        const [trackerPda] = PublicKey.findProgramAddressSync([TRACKER_SEED], this.program.programId);
+        const { currentRaffle } = await this.program.account.raffleTracker.fetch(trackerPda);
+        const activeRaffle = BigInt(currentRaffle.toString());
+        if (activeRaffle > BigInt(1)) {
+            await this.sync(activeRaffle - BigInt(1));
+        }
+
+        const { items } = await this.program.account.workQueue.fetch(this.workQueue);
+        const raffleIds = [...new Set(items.map(({ raffleId }) => raffleId.toString()))];
+        let sent = 0;
+        for (const raffleId of raffleIds.map(BigInt)) {
+            for (const { task } of items.filter((item) => item.raffleId.toString() === raffleId.toString())) {
+                if (await this.run(raffleId, activeRaffle, task)) sent++;
+            }
+            await this.sync(raffleId);
+        }
+        return sent;
+    }
+
+    private async run(raffleId: bigint, activeRaffle: bigint, task: any): Promise<boolean> {
+        const programId = this.program.programId;
+        const raffle = rafflePda(programId, raffleId);
+        const [config] = PublicKey.findProgramAddressSync([CONFIG_SEED], programId);
+        const raffleStatus = await this.program.account.raffle.fetch(raffle);
+        const id = new anchor.BN(raffleId.toString());
+
+        if (task.drawWinner) {
+            if (raffleStatus.drawSeed) {
+                await this.program.methods.drawWinner(id)
+                    .accounts({
+                        raffle,
+                        ...await drawWinnerAccounts(this.program, raffle),
+                        config,
+                        cranker: this.cranker.publicKey,
+                    })
+                    .signers([this.cranker])
+                    .rpc();
+                return true;
+            }
+            const secret = this.secrets.get(raffleId.toString());
+            if (!raffleStatus.commitment || !secret) return false;
+            const tx = await this.program.methods.revealAndPickWinner(id, secret)
+                .accounts({
+                    raffle,
+                    ...await revealWinnerAccounts(this.program, raffle, secret),
+                    cranker: this.cranker.publicKey,
+                    config,
+                    slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+                })
+                .transaction();
+            const { connection } = this.provider;
+            const { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+            tx.feePayer = this.cranker.publicKey;
+            tx.recentBlockhash = blockhash;
+            tx.lastValidBlockHeight = lastValidBlockHeight;
+            await web3.sendAndConfirmTransaction(connection, tx, [this.cranker], { commitment: "finalized" });
+            return true;
+        }
+
+        if (task.recommitRandomness) {
+            // Falls back to public randomness `REVEAL_TIMEOUT` after the raffle ended
+            try {
+                await this.program.methods.recommitRandomness(id)
+                    .accounts({
+                        raffle,
+                        signer: this.cranker.publicKey,
+                        slotHashes: web3.SYSVAR_SLOT_HASHES_PUBKEY,
+                    })
+                    .signers([this.cranker])
+                    .rpc();
+            } catch (e) {
+                if (e.error?.errorCode?.code === "RevealTimeoutNotReached") return false;
+                throw e;
+            }
+            this.secrets.set(raffleId.toString(), PUBLIC_REVEAL_SECRET);
+            return true;
+        }
+
+        if (task.redrawUnclaimed) {
+            await this.program.methods.redrawUnclaimed(id, task.redrawUnclaimed.slot)
+                .accounts({ raffle })
+                .rpc();
+            return true;
+        }
+
+        const { slot } = task.distributePrize;
+        const { prize, winner, rolledOver } = raffleStatus.prizes[slot];
+        if (prize.assetId) return false;
+        // A rolled over prize goes into whichever raffle is active now
+        const recipient = rolledOver ? rafflePda(programId, activeRaffle) : winner;
+        const builder = await this.pNftTransferClient.buildDistributePNFT({
+            authority: this.cranker.publicKey,
+            winner: recipient,
+            sourceAta: prize.ata,
+            nftMint: prize.mint,
+            destAta: await getAssociatedTokenAddress(prize.mint, recipient, true),
+            raffle,
+            raffleId: id,
+            slot,
+        });
+        await buildAndSendTx({
+            provider: this.provider,
+            ixs: [await builder.instruction()],
+            extraSigners: [this.cranker],
+        });
+        return true;
+    }
+}
@@ -14,4 +14,19 @@ export function raffleNumberBuffer(value: bigint): Uint8Array {
 
 export const TRACKER_SEED = Buffer.from("tracker");
 export const RAFFLE_SEED = Buffer.from("raffle");
-export const SUPER_RAFFLE_SEED = Buffer.from("skull");
\ No newline at end of file
+export const SUPER_RAFFLE_SEED = Buffer.from("skull");
+export const CONFIG_SEED = Buffer.from("config");
+export const SUPER_DRAW_SEED = Buffer.from("skull_draw");
+export const SUPER_RESULT_SEED = Buffer.from("skull_result");
+export const TICKET_HOLDER_SEED = Buffer.from("holder");
+export const TICKET_RECEIPT_SEED = Buffer.from("receipt");
+export const USER_STATS_SEED = Buffer.from("user_stats");
+export const SEASON_SEED = Buffer.from("season");
+export const PAYMENT_MINT_SEED = Buffer.from("payment_mint");
+export const LISTING_SEED = Buffer.from("listing");
+export const LISTING_BOOK_SEED = Buffer.from("listing_book");
+export const COLLECTION_SEED = Buffer.from("collection");
+export const WORK_QUEUE_SEED = Buffer.from("work_queue");
+
+// mock_oracle
+export const RANDOMNESS_SEED = Buffer.from("randomness");
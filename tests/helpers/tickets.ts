@@ -0,0 +1,108 @@
+import * as anchor from "@project-serum/anchor";
+import { web3, utils } from '@project-serum/anchor';
+import { MadRaffle } from "../../target/types/mad_raffle";
+import { raffleNumberBuffer, TICKET_HOLDER_SEED, TICKET_RECEIPT_SEED } from "./seeds";
+import { drawEntropy, revealSeed, slotHashAfter } from "./randomness";
+import { seasonPda, userStatsPda } from "./points";
+const { PublicKey, SYSVAR_SLOT_HASHES_PUBKEY } = web3;
+
+/**
+ * 
+ * @returns the TicketHolder PDA for `user` in raffle `raffleId`
+ */
+export function ticketHolderPda(programId: web3.PublicKey, raffleId: bigint, user: web3.PublicKey): web3.PublicKey {
+    return PublicKey.findProgramAddressSync(
+        [TICKET_HOLDER_SEED, raffleNumberBuffer(raffleId), user.toBuffer()],
+        programId
+    )[0];
+}
+
+/**
+ * 
+ * @returns the TicketReceipt PDA for purchase `index` in raffle `raffleId`
+ */
+export function ticketReceiptPda(programId: web3.PublicKey, raffleId: bigint, index: number): web3.PublicKey {
+    const indexBuffer = Buffer.alloc(4);
+    indexBuffer.writeUInt32LE(index);
+    return PublicKey.findProgramAddressSync(
+        [TICKET_RECEIPT_SEED, raffleNumberBuffer(raffleId), indexBuffer],
+        programId
+    )[0];
+}
+
+/**
+ * Accounts `buy_ticket` needs for `buyer`. The receipt is the raffle's next
+ * one, so purchases into the same raffle must be sent one at a time.
+ */
+export async function ticketAccounts(
+    program: anchor.Program<MadRaffle>,
+    raffle: web3.PublicKey,
+    buyer: web3.PublicKey
+): Promise<{ ticketHolder: web3.PublicKey, receipt: web3.PublicKey, userStats: web3.PublicKey, season: web3.PublicKey }> {
+    const { id, receiptCount } = await program.account.raffle.fetch(raffle);
+    const raffleId = BigInt(id.toString());
+    return {
+        ticketHolder: ticketHolderPda(program.programId, raffleId, buyer),
+        receipt: ticketReceiptPda(program.programId, raffleId, receiptCount),
+        userStats: userStatsPda(program.programId, buyer),
+        season: seasonPda(program.programId),
+    };
+}
+
+/**
+ * The `receipt` and matching `winnerStats` accounts for picking a winner.
+ */
+export async function receiptAccounts(
+    program: anchor.Program<MadRaffle>,
+    receipt: web3.PublicKey
+): Promise<{ receipt: web3.PublicKey, winnerStats: web3.PublicKey }> {
+    const { user } = await program.account.ticketReceipt.fetch(receipt);
+    return { receipt, winnerStats: userStatsPda(program.programId, user) };
+}
+
+/**
+ * Finds the receipt (and its owner's stats) holding ticket `entropy % ticketCount`, mirroring
+ * `utils::winner_from_entropy`.
+ */
+export async function winnerAccounts(
+    program: anchor.Program<MadRaffle>,
+    raffle: web3.PublicKey,
+    entropy: bigint
+): Promise<{ receipt: web3.PublicKey, winnerStats: web3.PublicKey }> {
+    const { id, ticketCount } = await program.account.raffle.fetch(raffle);
+    const index = entropy % BigInt(ticketCount);
+    const receipts = await program.account.ticketReceipt.all([
+        { memcmp: { offset: 8, bytes: utils.bytes.bs58.encode(raffleNumberBuffer(BigInt(id.toString()))) } },
+    ]);
+    const winner = receipts.find(({ account }) =>
+        BigInt(account.start) <= index && index < BigInt(account.start + account.qty)
+    );
+    return { receipt: winner.publicKey, winnerStats: userStatsPda(program.programId, winner.account.user) };
+}
+
+/**
+ * The receipt `reveal_and_pick_winner` will select for `secret`, for the first prize.
+ */
+export async function revealWinnerAccounts(
+    program: anchor.Program<MadRaffle>,
+    raffle: web3.PublicKey,
+    secret: number[]
+): Promise<{ receipt: web3.PublicKey, winnerStats: web3.PublicKey }> {
+    const { id, endSlot } = await program.account.raffle.fetch(raffle);
+    const slotHashes = await program.provider.connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+    const slotHash = slotHashAfter(slotHashes.data, BigInt(endSlot.toString()));
+    const entropy = drawEntropy(revealSeed(secret, slotHash), raffleNumberBuffer(BigInt(id.toString())), 0);
+    return winnerAccounts(program, raffle, entropy);
+}
+
+/**
+ * The receipt the next `draw_winner` will select, once the raffle's draws are seeded.
+ */
+export async function drawWinnerAccounts(
+    program: anchor.Program<MadRaffle>,
+    raffle: web3.PublicKey
+): Promise<{ receipt: web3.PublicKey, winnerStats: web3.PublicKey }> {
+    const { id, drawSeed, drawNonce } = await program.account.raffle.fetch(raffle);
+    const entropy = drawEntropy(Uint8Array.from(drawSeed), raffleNumberBuffer(BigInt(id.toString())), drawNonce);
+    return winnerAccounts(program, raffle, entropy);
+}
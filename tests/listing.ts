@@ -0,0 +1,163 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { COLLECTION_KEYPAIR } from "./helpers/keys";
+import { TRACKER_SEED } from "./helpers/seeds";
+import { buyTickets, commitRandomness, currentRaffleId, endRaffleWithPNfts, rafflePda } from "./helpers/raffles";
+import { buildAndSendTx, createAndFundATA, createFundedWallet } from "./utils/pnft";
+import { PNftTransferClient } from './utils/PNftTransferClient';
+import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+const { PublicKey, Keypair } = web3;
+
+describe("Listings", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const pNftTransferClient = new PNftTransferClient(provider.connection, provider.wallet as anchor.Wallet);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+
+  let seller: web3.Keypair;
+  let mint: web3.PublicKey;
+  let ata: web3.PublicKey;
+  const ask = new anchor.BN(web3.LAMPORTS_PER_SOL);
+
+  before(async () => {
+    seller = await createFundedWallet(provider);
+    const creators = Array(5)
+      .fill(null)
+      .map((_) => ({ address: Keypair.generate().publicKey, share: 20 }));
+    ({ mint, ata } = await createAndFundATA({
+      provider: provider,
+      owner: seller,
+      creators,
+      royaltyBps: 50,
+      programmable: true,
+      ruleSetAddr: new PublicKey('eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9'),
+      collection: COLLECTION_KEYPAIR,
+      collectionVerified: true,
+      skipNewCollection: true
+    }));
+  });
+
+  it("escrows a listed NFT", async () => {
+    const builder = await pNftTransferClient.buildListPNFT({
+      nftMint: mint,
+      sourceAta: ata,
+      seller: seller.publicKey,
+      ask,
+    });
+    await buildAndSendTx({ provider, ixs: [await builder.instruction()], extraSigners: [seller] });
+
+    const listingPda = pNftTransferClient.findListingPDA(mint);
+    const listing = await program.account.listing.fetch(listingPda);
+    expect(listing.seller.toBase58()).to.equal(seller.publicKey.toBase58());
+    expect(listing.ask.toString()).to.equal(ask.toString());
+    const escrow = getAssociatedTokenAddressSync(mint, listingPda, true);
+    const escrowBalance = await provider.connection.getTokenAccountBalance(escrow);
+    expect(escrowBalance.value.uiAmount).to.equal(1);
+
+    const book = await program.account.listingBook.fetch(pNftTransferClient.findListingBookPDA());
+    expect(book.listings.some(entry => entry.nftMint.equals(mint))).to.be.true;
+  });
+
+  it("only the seller cancels a listing", async () => {
+    const outsider = await createFundedWallet(provider);
+    const builder = await pNftTransferClient.buildCancelListing({ nftMint: mint, seller: outsider.publicKey });
+    try {
+      await buildAndSendTx({ provider, ixs: [await builder.instruction()], extraSigners: [outsider] });
+      expect.fail("Outsider cancel should fail");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("UnauthorizedSigner");
+    }
+  });
+
+  it("returns the NFT on cancel", async () => {
+    const builder = await pNftTransferClient.buildCancelListing({ nftMint: mint, seller: seller.publicKey });
+    await buildAndSendTx({ provider, ixs: [await builder.instruction()], extraSigners: [seller] });
+
+    const sellerBalance = await provider.connection.getTokenAccountBalance(ata);
+    expect(sellerBalance.value.uiAmount).to.equal(1);
+    const listing = await program.account.listing.fetchNullable(pNftTransferClient.findListingPDA(mint));
+    expect(listing).to.be.null;
+    const book = await program.account.listingBook.fetch(pNftTransferClient.findListingBookPDA());
+    expect(book.listings.some(entry => entry.nftMint.equals(mint))).to.be.false;
+  });
+
+  it("lists and cancels a non-programmable NFT", async () => {
+    const { mint: legacyMint, ata: legacyAta } = await createAndFundATA({
+      provider: provider,
+      owner: seller,
+      creators: [{ address: Keypair.generate().publicKey, share: 100 }],
+      royaltyBps: 50,
+      programmable: false,
+      collection: COLLECTION_KEYPAIR,
+      collectionVerified: true,
+      skipNewCollection: true
+    });
+    const list = await pNftTransferClient.buildListPNFT({
+      nftMint: legacyMint,
+      sourceAta: legacyAta,
+      seller: seller.publicKey,
+      ask,
+    });
+    await buildAndSendTx({ provider, ixs: [await list.instruction()], extraSigners: [seller] });
+    const escrow = getAssociatedTokenAddressSync(legacyMint, pNftTransferClient.findListingPDA(legacyMint), true);
+    expect((await provider.connection.getTokenAccountBalance(escrow)).value.uiAmount).to.equal(1);
+
+    const cancel = await pNftTransferClient.buildCancelListing({ nftMint: legacyMint, seller: seller.publicKey });
+    await buildAndSendTx({ provider, ixs: [await cancel.instruction()], extraSigners: [seller] });
+    expect((await provider.connection.getTokenAccountBalance(legacyAta)).value.uiAmount).to.equal(1);
+  });
+
+  it("sells a listing the pot meets before any other NFT", async () => {
+    await buyTickets(provider, program, 1);
+    await commitRandomness(program);
+    const creators = [{ address: Keypair.generate().publicKey, share: 100 }];
+    const { mint: listedMint, ata: listedAta } = await createAndFundATA({
+      provider: provider,
+      owner: seller,
+      creators,
+      royaltyBps: 50,
+      programmable: false,
+      collection: COLLECTION_KEYPAIR,
+      collectionVerified: true,
+      skipNewCollection: true
+    });
+    const list = await pNftTransferClient.buildListPNFT({
+      nftMint: listedMint,
+      sourceAta: listedAta,
+      seller: seller.publicKey,
+      ask: new anchor.BN(1),
+    });
+    await buildAndSendTx({ provider, ixs: [await list.instruction()], extraSigners: [seller] });
+
+    try {
+      await endRaffleWithPNfts(provider, program);
+      expect.fail("Ending the raffle should fail while a listing is met");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("ListingMet");
+    }
+
+    const raffleId = await currentRaffleId(program);
+    const raffle = rafflePda(program.programId, raffleId);
+    const settle = await pNftTransferClient.buildSettleListing({
+      nftMint: listedMint,
+      payer: provider.wallet.publicKey,
+      seller: seller.publicKey,
+      raffle,
+      newRaffle: rafflePda(program.programId, raffleId + BigInt(1)),
+      tracker: PublicKey.findProgramAddressSync([TRACKER_SEED], program.programId)[0],
+      creators: creators.map(creator => creator.address),
+    });
+    await buildAndSendTx({ provider, ixs: [await settle.instruction()] });
+    const { prizes } = await program.account.raffle.fetch(raffle);
+    expect(prizes.some(slot => slot.prize.mint.equals(listedMint))).to.be.true;
+
+    // Any prizes still missing are sold as usual once the book is clear
+    if ((await program.account.raffle.fetch(raffle)).active) {
+      await endRaffleWithPNfts(provider, program);
+    }
+  });
+});
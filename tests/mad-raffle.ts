@@ -3,8 +3,10 @@ import { web3, Program, workspace } from '@project-serum/anchor';
 import { LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { assert, expect } from "chai";
 import { MadRaffle } from "../target/types/mad_raffle";
-import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED } from "./helpers/seeds";
+import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED } from "./helpers/seeds";
 import { AUTH_KEYPAIR, VAULT_KEYPAIR } from "./helpers/keys";
+import { ticketAccounts, ticketHolderPda } from "./helpers/tickets";
+import { seasonPda, userStatsPda } from "./helpers/points";
 import { createFundedWallet } from "./utils/pnft";
 
 const { PublicKey } = web3;
@@ -37,6 +39,11 @@ describe("Mad Raffle Tests", async () => {
     program.programId
   );
 
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+
   beforeEach(async () => {
     let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
     const airdropTx = await connection.requestAirdrop(AUTH_KEYPAIR.publicKey, LAMPORTS_PER_SOL * 1);
@@ -57,6 +64,8 @@ describe("Mad Raffle Tests", async () => {
           tracker: trackerPda,
           authority: AUTH_KEYPAIR.publicKey,
           superVault: superVaultPda,
+          config: configPda,
+          season: seasonPda(program.programId),
           raffle: rafflePda
         })
         .signers([AUTH_KEYPAIR])
@@ -78,6 +87,102 @@ describe("Mad Raffle Tests", async () => {
     const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
     assert.ok(raffleTracker.currentRaffle.eq(new anchor.BN(CURRENT_RAFFLE)), "currentRaffle should be 1");
   });
+  it("Checks that the config is initiated", async () => {
+    const config = await program.account.programConfig.fetch(configPda);
+    assert.ok(config.authority.equals(AUTH_KEYPAIR.publicKey), "config authority should be the initializer");
+    assert.ok(config.feeVault.equals(VAULT_KEYPAIR.publicKey), "config fee vault should be the default vault");
+  });
+  it("updates the config", async () => {
+    const { maxTicketsPerUser } = await program.account.programConfig.fetch(configPda);
+    await program.methods.updateConfig({ maxTicketsPerUser: maxTicketsPerUser + 1 } as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    let config = await program.account.programConfig.fetch(configPda);
+    assert.strictEqual(config.maxTicketsPerUser, maxTicketsPerUser + 1);
+    await program.methods.updateConfig({ maxTicketsPerUser } as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    config = await program.account.programConfig.fetch(configPda);
+    assert.strictEqual(config.maxTicketsPerUser, maxTicketsPerUser);
+  });
+  it("switches the ticket price mode", async () => {
+    await program.methods.updateConfig({ priceMode: { usdCents: {} }, usdTicketPrice: new anchor.BN(1_500) } as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    let config = await program.account.programConfig.fetch(configPda);
+    expect(config.priceMode).to.deep.equal({ usdCents: {} });
+    assert.strictEqual(config.usdTicketPrice.toNumber(), 1_500);
+    // Only raffles created after the switch are pegged, so the current raffle is unaffected
+    const raffle = await program.account.raffle.fetch(rafflePda);
+    assert.strictEqual(raffle.usdTicketPrice, null);
+
+    await program.methods.updateConfig({ priceMode: { lamports: {} } } as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    config = await program.account.programConfig.fetch(configPda);
+    expect(config.priceMode).to.deep.equal({ lamports: {} });
+    try {
+      await program.methods.updateConfig({ usdTicketPrice: new anchor.BN(0) } as any)
+        .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+      assert.fail("Zero USD price should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("InvalidTicketPrice");
+    }
+  });
+  it("unauthorized cannot update the config", async () => {
+    const unauthorized = await createFundedWallet(provider);
+    try {
+      await program.methods.updateConfig({ ticketPrice: new anchor.BN(1) } as any)
+        .accounts({ config: configPda, authority: unauthorized.publicKey })
+        .signers([unauthorized])
+        .rpc();
+      assert.fail("Config update should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("UnauthorizedSigner");
+    }
+  });
+  it("rotates the authority in two steps", async () => {
+    const newAuthority = await createFundedWallet(provider);
+    const impostor = await createFundedWallet(provider);
+    await program.methods.proposeAuthority(newAuthority.publicKey)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    try {
+      await program.methods.acceptAuthority()
+        .accounts({ config: configPda, newAuthority: impostor.publicKey })
+        .signers([impostor])
+        .rpc();
+      assert.fail("Only the proposed authority can accept");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("NotPendingAuthority");
+    }
+    await program.methods.acceptAuthority()
+      .accounts({ config: configPda, newAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    let config = await program.account.programConfig.fetch(configPda);
+    assert.ok(config.authority.equals(newAuthority.publicKey), "authority should be rotated");
+    assert.strictEqual(config.pendingAuthority, null);
+
+    // Hand it back so the remaining tests can use AUTH_KEYPAIR
+    await program.methods.proposeAuthority(AUTH_KEYPAIR.publicKey)
+      .accounts({ config: configPda, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods.acceptAuthority()
+      .accounts({ config: configPda, newAuthority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    config = await program.account.programConfig.fetch(configPda);
+    assert.ok(config.authority.equals(AUTH_KEYPAIR.publicKey), "authority should be restored");
+  });
   it("Checks that the super tracker is initiated", async () => {
     const superTracker = await program.account.superVault.fetch(superVaultPda);
     assert.ok(superTracker.bump, "supertracker bump should be defined");
@@ -89,6 +194,8 @@ describe("Mad Raffle Tests", async () => {
           tracker: trackerPda,
           authority: AUTH_KEYPAIR.publicKey,
           superVault: superVaultPda,
+          config: configPda,
+          season: seasonPda(program.programId),
           raffle: rafflePda
         })
         .signers([AUTH_KEYPAIR])
@@ -104,7 +211,7 @@ describe("Mad Raffle Tests", async () => {
   });
   it("Checks the number of ticket buyers", async () => {
     const initialRaffleStatus = await program.account.raffle.fetch(rafflePda);
-    if (initialRaffleStatus.tickets.length > 0) return;
+    if (initialRaffleStatus.ticketCount > 0) return;
     const numberOfBuyers = 10;
     let numberOfTickets = 0;
     const wallets = [];
@@ -120,49 +227,136 @@ describe("Mad Raffle Tests", async () => {
     });
     await Promise.all(airdropPromises);
     await new Promise((resolve) => setTimeout(resolve, 5000));
-    const ticketPromises = wallets.map(async (wallet, i) => {
+    // Each purchase takes the raffle's next receipt, so buy one at a time
+    for (const wallet of wallets) {
       const timesToBuy = Math.floor(Math.random() * 3) + 1;
       numberOfTickets += timesToBuy;
       for (let j = 0; j < timesToBuy; j++) {
-        let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
-
-        const signature = await program.methods
-          .buyTicket()
+        await program.methods
+          .buyTicket(1)
           .accounts({
             raffle: rafflePda,
+            ...await ticketAccounts(program, rafflePda, wallet.publicKey),
             buyer: wallet.publicKey,
             feeVault: VAULT_KEYPAIR.publicKey,
             tracker: trackerPda,
-            superVault: superVaultPda
+            superVault: superVaultPda,
+            config: configPda,
           })
           .signers([wallet])
           .rpc();
-
-        await connection.confirmTransaction({
-          signature,
-          lastValidBlockHeight,
-          blockhash
-        });
       }
-    });
-    await Promise.all(ticketPromises);
+    }
     const raffleStatus = await program.account.raffle.fetch(rafflePda);
     assert.strictEqual(
-      raffleStatus.tickets.length,
-      numberOfBuyers + initialRaffleStatus.tickets.length, // Initial value + new buyersc
-      "The number of tickets bought should equal the number of buyers"
+      raffleStatus.receiptCount,
+      numberOfTickets + initialRaffleStatus.receiptCount,
+      "There should be one receipt per single-ticket purchase"
     );
-    // Compute the sum of all TicketHolder quantities
-    const initialTicketsBought = initialRaffleStatus.tickets.reduce((sum, ticketHolder) => sum + ticketHolder.qty, 0);
-    const totalTicketsBought = raffleStatus.tickets.reduce((sum, ticketHolder) => sum + ticketHolder.qty, 0);
-
-    // Check if the computed sum is equal to numberOfTickets
     assert.strictEqual(
-      totalTicketsBought,
-      numberOfTickets + initialTicketsBought,
-      "The total number of tickets bought should equal the sum of all TicketHolder quantities"
+      raffleStatus.ticketCount,
+      numberOfTickets + initialRaffleStatus.ticketCount,
+      "The ticket count should equal the number of tickets bought"
     );
-
+    // Each holder's tickets add up across their purchases
+    for (const wallet of wallets) {
+      const holder = await program.account.ticketHolder.fetch(
+        ticketHolderPda(program.programId, BigInt(CURRENT_RAFFLE), wallet.publicKey)
+      );
+      assert.ok(holder.qty >= 1 && holder.qty <= 3, "holder should hold 1 to 3 tickets");
+    }
+  });
+  it("Buys several tickets in one call", async () => {
+    const wallet = await createFundedWallet(provider, 5);
+    await program.methods
+      .buyTicket(3)
+      .accounts({
+        raffle: rafflePda,
+        ...await ticketAccounts(program, rafflePda, wallet.publicKey),
+        buyer: wallet.publicKey,
+        feeVault: VAULT_KEYPAIR.publicKey,
+        tracker: trackerPda,
+        superVault: superVaultPda,
+        config: configPda,
+      })
+      .signers([wallet])
+      .rpc();
+    const holder = await program.account.ticketHolder.fetch(
+      ticketHolderPda(program.programId, BigInt(CURRENT_RAFFLE), wallet.publicKey)
+    );
+    assert.strictEqual(holder.qty, 3, "buyer should hold 3 tickets");
+    const stats = await program.account.userStats.fetch(userStatsPda(program.programId, wallet.publicKey));
+    assert.strictEqual(stats.lifetimeTickets, 3, "stats should count 3 tickets");
+    assert.ok(stats.points > 0, "buyer should earn points");
+  });
+  it("Emits TicketPurchased", async () => {
+    const wallet = await createFundedWallet(provider, 5);
+    let listener: number;
+    const event = new Promise<any>(resolve => {
+      listener = program.addEventListener("TicketPurchased", (event) => {
+        if (event.buyer.equals(wallet.publicKey)) resolve(event);
+      });
+    });
+    const accounts = await ticketAccounts(program, rafflePda, wallet.publicKey);
+    await program.methods
+      .buyTicket(2)
+      .accounts({
+        raffle: rafflePda,
+        ...accounts,
+        buyer: wallet.publicKey,
+        feeVault: VAULT_KEYPAIR.publicKey,
+        tracker: trackerPda,
+        superVault: superVaultPda,
+        config: configPda,
+      })
+      .signers([wallet])
+      .rpc();
+    const purchased = await event;
+    await program.removeEventListener(listener);
+    const raffle = await program.account.raffle.fetch(rafflePda);
+    assert.strictEqual(purchased.raffleId.toNumber(), CURRENT_RAFFLE);
+    assert.ok(purchased.receipt.equals(accounts.receipt), "event should name the receipt");
+    assert.strictEqual(purchased.quantity, 2);
+    assert.strictEqual(purchased.ticketPrice.toString(), raffle.ticketPrice.muln(2).toString());
+    assert.strictEqual(purchased.totalTickets, raffle.ticketCount);
+  });
+  it("Only migrates users on the legacy scoreboard", async () => {
+    const user = web3.Keypair.generate().publicKey;
+    try {
+      await program.methods.migrateUserPoints(user)
+        .accounts({
+          tracker: trackerPda,
+          userStats: userStatsPda(program.programId, user),
+          season: seasonPda(program.programId),
+          payer: provider.wallet.publicKey,
+        })
+        .rpc();
+      assert.fail("Migration should fail for a user without legacy points");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("NotOnScoreboard");
+    }
+  });
+  it("Cannot buy more than the max tickets per user", async () => {
+    const { maxTicketsPerUser } = await program.account.programConfig.fetch(configPda);
+    const wallet = await createFundedWallet(provider, 1);
+    try {
+      await program.methods
+        .buyTicket(maxTicketsPerUser + 1)
+        .accounts({
+          raffle: rafflePda,
+          ...await ticketAccounts(program, rafflePda, wallet.publicKey),
+          buyer: wallet.publicKey,
+          feeVault: VAULT_KEYPAIR.publicKey,
+          tracker: trackerPda,
+          superVault: superVaultPda,
+          config: configPda,
+        })
+        .signers([wallet])
+        .rpc();
+      assert.fail("Buying past the max should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("MaxTicketsPerUserExceeded");
+    }
   });
   it("Tries to buy ticket with wrong vault", async () => {
     const wallet = await createFundedWallet(provider, 2);
@@ -170,13 +364,15 @@ describe("Mad Raffle Tests", async () => {
     try {
       let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
       const signature = await program.methods
-        .buyTicket()
+        .buyTicket(1)
         .accounts({
           raffle: rafflePda,
+          ...await ticketAccounts(program, rafflePda, wallet.publicKey),
           buyer: wallet.publicKey,
           feeVault: wrongVault.publicKey,
           tracker: trackerPda,
-          superVault: superVaultPda
+          superVault: superVaultPda,
+          config: configPda,
         })
         .signers([wallet])
         .rpc();
@@ -200,13 +396,15 @@ describe("Mad Raffle Tests", async () => {
     try {
       let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
       const signature = await program.methods
-        .buyTicket()
+        .buyTicket(1)
         .accounts({
           raffle: wrongRaffle,
+          ...await ticketAccounts(program, rafflePda, wallet.publicKey),
           buyer: wallet.publicKey,
           feeVault: VAULT_KEYPAIR.publicKey,
           tracker: trackerPda,
-          superVault: superVaultPda
+          superVault: superVaultPda,
+          config: configPda,
         })
         .signers([wallet])
         .rpc();
@@ -227,13 +425,15 @@ describe("Mad Raffle Tests", async () => {
     try {
       let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
       const signature = await program.methods
-        .buyTicket()
+        .buyTicket(1)
         .accounts({
           raffle: rafflePda,
+          ...await ticketAccounts(program, rafflePda, wallet.publicKey),
           buyer: wallet.publicKey,
           feeVault: VAULT_KEYPAIR.publicKey,
           tracker: trackerPda,
-          superVault: superVaultPda
+          superVault: superVaultPda,
+          config: configPda,
         })
         .signers([wallet])
         .rpc();  
@@ -2,7 +2,9 @@ import * as anchor from "@project-serum/anchor";
 import { getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
 import { Keypair, PublicKey } from "@solana/web3.js";
 import { assert, expect } from "chai";
-import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED } from "./helpers/seeds";
+import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED } from "./helpers/seeds";
+import { newRandomnessCommitment } from "./helpers/randomness";
+import { receiptAccounts, revealWinnerAccounts, ticketAccounts, ticketReceiptPda } from "./helpers/tickets";
 import { buildAndSendTx, createAndFundATA, createFundedWallet, createTokenAuthorizationRules } from "./utils/pnft";
 import { PNftTransferClient } from './utils/PNftTransferClient';
 import { MadRaffle } from "../target/types/mad_raffle";
@@ -40,6 +42,31 @@ describe("pnft_transfer tests (end raffle 1)", () => {
         program.programId
     );
 
+    const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+        [CONFIG_SEED],
+        program.programId
+    );
+
+    const { secret, commitment } = newRandomnessCommitment();
+
+    it('commits randomness for the raffle', async () => {
+        const tx = await program.methods.commitRandomness(commitment)
+            .accounts({
+                raffle: rafflePda,
+                tracker: trackerPda,
+                authority: AUTH_KEYPAIR.publicKey,
+                config: configPda,
+            })
+            .signers([AUTH_KEYPAIR])
+            .transaction();
+        let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+        tx.feePayer = AUTH_KEYPAIR.publicKey;
+        tx.recentBlockhash = blockhash;
+        tx.lastValidBlockHeight = lastValidBlockHeight;
+        await anchor.web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
+        const raffleStatus = await program.account.raffle.fetch(rafflePda);
+        expect(raffleStatus.commitment).to.deep.equal(commitment);
+    });
 
     it('Sells pNFT to end the Raffle', async () => {
         const nftOwner = await createFundedWallet(provider);
@@ -103,7 +130,7 @@ describe("pnft_transfer tests (end raffle 1)", () => {
         const raffleMinRent = await connection.getMinimumBalanceForRentExemption(raffleAccountInfo.data.length);
         assert.ok(!raffleStatus.active, "expect that raffle is not active");
         assert.ok(raffleStatus.endTime, "expect that raffle has an end time");
-        assert.ok(raffleStatus.prize, "expect that raffle has a prize");
+        assert.ok(raffleStatus.prizes[0], "expect that raffle has a prize");
         assert.ok(raffleAccountInfo.lamports >= raffleMinRent, "expect that raffle account has enough lamports");
 
     });
@@ -168,13 +195,15 @@ describe("pnft_transfer tests (end raffle 1)", () => {
         try {
             let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
             const signature = await program.methods
-                .buyTicket()
+                .buyTicket(1)
                 .accounts({
                     raffle: rafflePda,
+                    ...await ticketAccounts(program, rafflePda, wallet.publicKey),
                     buyer: wallet.publicKey,
                     feeVault: VAULT_KEYPAIR.publicKey,
                     tracker: trackerPda,
-                    superVault: superVaultPda
+                    superVault: superVaultPda,
+                    config: configPda,
                 })
                 .signers([wallet])
                 .rpc();
@@ -188,77 +217,57 @@ describe("pnft_transfer tests (end raffle 1)", () => {
             expect(e, "Expected transaction to fail due to closed raffle");
         }
     });
-    it('unauthorized cannot select winner', async () => {
-        let unauthorized = await createFundedWallet(provider);
+    it('cannot reveal a different secret', async () => {
+        const { secret: wrongSecret } = newRandomnessCommitment();
         try {
-            const tx = await program.methods.selectWinner(new anchor.BN(1))
+            await program.methods.revealAndPickWinner(new anchor.BN(CURRENT_RAFFLE), wrongSecret)
                 .accounts({
                     raffle: rafflePda,
-                    authority: unauthorized.publicKey,
-                    random: Keypair.generate().publicKey,
+                    ...await receiptAccounts(program, ticketReceiptPda(program.programId, BigInt(CURRENT_RAFFLE), 0)),
+                    cranker: AUTH_KEYPAIR.publicKey,
+                    config: configPda,
+                    slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
                 })
-                .signers([unauthorized])
-                .transaction();
-            let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
-            tx.feePayer = unauthorized.publicKey;
-            tx.recentBlockhash = blockhash;
-            tx.lastValidBlockHeight = lastValidBlockHeight;
-            await anchor.web3.sendAndConfirmTransaction(connection, tx, [unauthorized], { commitment: "finalized" });
+                .signers([AUTH_KEYPAIR])
+                .rpc();
+            assert.fail("Reveal with the wrong secret should fail");
         } catch (e) {
-            expect(e, "Winner selection should fail");
+            expect(e.error?.errorCode?.code).to.equal("InvalidReveal");
         }
     });
-    it('selects a winner', async () => {
-        const tx = await program.methods.selectWinner(new anchor.BN(CURRENT_RAFFLE))
+    it('anyone with the secret selects a winner for a reward', async () => {
+        const keeper = await createFundedWallet(provider);
+        const { crankReward } = await program.account.raffle.fetch(rafflePda);
+        const preRaffleLamports = await connection.getBalance(rafflePda);
+        const tx = await program.methods.revealAndPickWinner(new anchor.BN(CURRENT_RAFFLE), secret)
             .accounts({
                 raffle: rafflePda,
-                authority: AUTH_KEYPAIR.publicKey,
-                random: Keypair.generate().publicKey,
+                ...await revealWinnerAccounts(program, rafflePda, secret),
+                cranker: keeper.publicKey,
+                config: configPda,
+                slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
             })
-            .signers([AUTH_KEYPAIR])
+            .signers([keeper])
             .transaction();
         let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
-        tx.feePayer = AUTH_KEYPAIR.publicKey;
+        tx.feePayer = keeper.publicKey;
         tx.recentBlockhash = blockhash;
         tx.lastValidBlockHeight = lastValidBlockHeight;
-        await anchor.web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
-    });
-    it('unauthorized cannot send prize', async () => {
-        let unauthorized = await createFundedWallet(provider);
-        try {
-            const raffleStatus = await program.account.raffle.fetch(rafflePda);
-            const { winner } = raffleStatus;
-            const { mint, ata } = raffleStatus.prize;
-
-            let destAta = await getAssociatedTokenAddress(mint, winner);
+        await anchor.web3.sendAndConfirmTransaction(connection, tx, [keeper], { commitment: "finalized" });
 
-            const builder = await pNftTransferClient.buildDistributePNFT({
-                authority: unauthorized.publicKey,
-                winner,
-                sourceAta: ata,
-                nftMint: mint,
-                destAta: destAta,
-                raffle: rafflePda,
-                raffleId: new anchor.BN(CURRENT_RAFFLE),
-            })
-            await buildAndSendTx({
-                provider,
-                ixs: [await builder.instruction()],
-                extraSigners: [unauthorized],
-            });
-        } catch (e) {
-            expect(e, "Prize distribution should fail");
-        }
+        const postRaffleLamports = await connection.getBalance(rafflePda);
+        expect(preRaffleLamports - postRaffleLamports).to.equal(crankReward.toNumber());
     });
-    it('sends prize', async () => {
+    it('anyone sends the prize to the winner for a reward', async () => {
+        const keeper = await createFundedWallet(provider);
         const raffleStatus = await program.account.raffle.fetch(rafflePda);
-        const { winner } = raffleStatus;
-        const { mint, ata } = raffleStatus.prize;
+        const { prize: { mint, ata }, winner } = raffleStatus.prizes[0];
+        const preRaffleLamports = await connection.getBalance(rafflePda);
 
         let destAta = await getAssociatedTokenAddress(mint, winner);
 
         const builder = await pNftTransferClient.buildDistributePNFT({
-            authority: AUTH_KEYPAIR.publicKey,
+            authority: keeper.publicKey,
             winner,
             sourceAta: ata,
             nftMint: mint,
@@ -269,12 +278,14 @@ describe("pnft_transfer tests (end raffle 1)", () => {
         await buildAndSendTx({
             provider,
             ixs: [await builder.instruction()],
-            extraSigners: [AUTH_KEYPAIR],
+            extraSigners: [keeper],
         });
 
+        const postRaffleLamports = await connection.getBalance(rafflePda);
+        expect(preRaffleLamports - postRaffleLamports).to.equal(raffleStatus.crankReward.toNumber());
         const winnerBalance = await provider.connection.getTokenAccountBalance(destAta);
         const postRaffleStatus = await program.account.raffle.fetch(rafflePda);
-        expect(postRaffleStatus.prize.sent).to.equal(true);
+        expect(postRaffleStatus.prizes[0].sent).to.equal(true);
         expect(winnerBalance.value.uiAmount).to.equal(1);
     });
 
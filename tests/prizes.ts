@@ -0,0 +1,248 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { keypairIdentity, Metaplex } from "@metaplex-foundation/js";
+import { getAssociatedTokenAddressSync, TOKEN_2022_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { COLLECTION_SEED, CONFIG_SEED, TRACKER_SEED } from "./helpers/seeds";
+import { revealWinnerAccounts } from "./helpers/tickets";
+import { buyTickets, commitRandomness, currentRaffleId, rafflePda } from "./helpers/raffles";
+import { AUTH_KEYPAIR } from "./helpers/keys";
+import { buildAndSendTx, createAndFundATA, createAndFundToken2022Nft, createFundedWallet } from "./utils/pnft";
+import { PNftTransferClient } from "./utils/PNftTransferClient";
+const { PublicKey, Keypair, SYSVAR_SLOT_HASHES_PUBKEY } = web3;
+
+// Token-2022 NFTs need the Token Metadata cloned into the local validator by Anchor.toml
+describe("Legacy and Token-2022 NFT prizes", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+  const programId = program.programId;
+  const pNftTransferClient = new PNftTransferClient(connection, provider.wallet as anchor.Wallet);
+
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync([CONFIG_SEED], programId);
+  const [trackerPda, _trackerBump] = PublicKey.findProgramAddressSync([TRACKER_SEED], programId);
+  const collection = Keypair.generate();
+
+  let collectionAuthority: web3.Keypair;
+  let prizesPerRaffle: number;
+  let raffleDuration: anchor.BN;
+
+  const updateConfig = (update: object) =>
+    program.methods.updateConfig(update as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+  // Starts a new raffle, so it snapshots the current config
+  const restartRaffle = async () => {
+    const raffleId = await currentRaffleId(program);
+    await program.methods.cancelRaffle()
+      .accounts({
+        raffle: rafflePda(programId, raffleId),
+        newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+        tracker: trackerPda,
+        config: configPda,
+        signer: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+  };
+
+  const verifyCollection = (mint: web3.PublicKey) =>
+    new Metaplex(connection)
+      .use(keypairIdentity(collectionAuthority))
+      .nfts()
+      .verifyCollection({ mintAddress: mint, collectionMintAddress: collection.publicKey }, { commitment: 'finalized' });
+
+  const reveal = async (raffleId: bigint, secret: number[]) => {
+    const raffle = rafflePda(programId, raffleId);
+    const tx = await program.methods.revealAndPickWinner(new anchor.BN(raffleId.toString()), secret)
+      .accounts({
+        raffle,
+        ...await revealWinnerAccounts(program, raffle, secret),
+        cranker: AUTH_KEYPAIR.publicKey,
+        config: configPda,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .signers([AUTH_KEYPAIR])
+      .transaction();
+    const { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+    tx.feePayer = AUTH_KEYPAIR.publicKey;
+    tx.recentBlockhash = blockhash;
+    tx.lastValidBlockHeight = lastValidBlockHeight;
+    await web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
+  };
+
+  const mintLegacyNft = async (seller: web3.Keypair) => {
+    const { mint, ata } = await createAndFundATA({
+      provider,
+      owner: seller,
+      creators: [{ address: seller.publicKey, share: 100 }],
+      royaltyBps: 50,
+      programmable: false,
+      collection,
+      skipNewCollection: true,
+    });
+    await verifyCollection(mint);
+    return { mint, ata };
+  };
+
+  // Starts a raffle collecting `prizes` prizes, with tickets and a commitment so it can be sold into
+  const startRaffle = async (prizes: number, duration = 0) => {
+    await updateConfig({ prizesPerRaffle: prizes, raffleDuration: new anchor.BN(duration) });
+    await restartRaffle();
+    await updateConfig({ prizesPerRaffle, raffleDuration });
+    const raffleId = await currentRaffleId(program);
+    await buyTickets(provider, program, 2);
+    return { raffleId, raffle: rafflePda(programId, raffleId), secret: await commitRandomness(program) };
+  };
+
+  const sell = async (seller: web3.Keypair, mint: web3.PublicKey, ata: web3.PublicKey, raffleId: bigint, tokenProgram = TOKEN_PROGRAM_ID) => {
+    const raffle = rafflePda(programId, raffleId);
+    const builder = await pNftTransferClient.buildTransferPNFT({
+      sourceAta: ata,
+      nftMint: mint,
+      destAta: getAssociatedTokenAddressSync(mint, raffle, true, tokenProgram),
+      owner: seller.publicKey,
+      tracker: trackerPda,
+      raffle,
+      newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+      creators: [seller.publicKey],
+      tokenProgram,
+    });
+    await buildAndSendTx({ provider, ixs: [await builder.instruction()], extraSigners: [seller] });
+  };
+
+  // Sells `mint` into a new one-prize raffle, draws it and sends the prize to the winner
+  const sellAndDistribute = async (seller: web3.Keypair, mint: web3.PublicKey, ata: web3.PublicKey, tokenProgram: web3.PublicKey) => {
+    const { raffleId, raffle, secret } = await startRaffle(1);
+    await sell(seller, mint, ata, raffleId, tokenProgram);
+    expect((await program.account.raffle.fetch(raffle)).active).to.equal(false);
+    await reveal(raffleId, secret);
+
+    const { prize, winner } = (await program.account.raffle.fetch(raffle)).prizes[0];
+    const destAta = getAssociatedTokenAddressSync(mint, winner, false, tokenProgram);
+    const distribute = await pNftTransferClient.buildDistributePNFT({
+      authority: AUTH_KEYPAIR.publicKey,
+      winner,
+      sourceAta: prize.ata,
+      nftMint: mint,
+      destAta,
+      raffle,
+      raffleId: new anchor.BN(raffleId.toString()),
+      tokenProgram,
+    });
+    await buildAndSendTx({ provider, ixs: [await distribute.instruction()], extraSigners: [AUTH_KEYPAIR] });
+
+    expect((await program.account.raffle.fetch(raffle)).prizes[0].sent).to.equal(true);
+    expect((await connection.getTokenAccountBalance(destAta)).value.uiAmount).to.equal(1);
+    // The raffle's token account is closed once the prize is out
+    expect(await connection.getAccountInfo(prize.ata)).to.equal(null);
+  };
+
+  before(async () => {
+    collectionAuthority = await createFundedWallet(provider, 5);
+    await new Metaplex(connection).use(keypairIdentity(collectionAuthority)).nfts().create({
+      useNewMint: collection,
+      uri: 'https://madlads.s3.us-west-2.amazonaws.com/json/8420.json',
+      name: 'Mad Lads KING',
+      sellerFeeBasisPoints: 0,
+      isCollection: true,
+    }, { commitment: 'finalized' });
+    await program.methods.setCollection(true, 0, { fixed: { basisPoints: 0 } } as any)
+      .accounts({
+        collectionSettings: PublicKey.findProgramAddressSync([COLLECTION_SEED, collection.publicKey.toBuffer()], programId)[0],
+        collection: collection.publicKey,
+        config: configPda,
+        authority: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+    ({ prizesPerRaffle, raffleDuration } = await program.account.programConfig.fetch(configPda));
+  });
+
+  // Leave the next test file a raffle with the original config
+  after(restartRaffle);
+
+  it("sells and distributes a legacy NFT", async () => {
+    const seller = await createFundedWallet(provider);
+    const { mint, ata } = await mintLegacyNft(seller);
+    await sellAndDistribute(seller, mint, ata, TOKEN_PROGRAM_ID);
+  });
+
+  it("sells and distributes a Token-2022 NFT", async () => {
+    const seller = await createFundedWallet(provider);
+    const { mint, ata } = await createAndFundToken2022Nft({
+      provider,
+      owner: seller,
+      creators: [{ address: seller.publicKey, share: 100 }],
+      royaltyBps: 50,
+      collection: collection.publicKey,
+    });
+    await verifyCollection(mint);
+    await sellAndDistribute(seller, mint, ata, TOKEN_2022_PROGRAM_ID);
+  });
+
+  it("sells several prizes into the same raffle", async () => {
+    const seller = await createFundedWallet(provider);
+    const first = await mintLegacyNft(seller);
+    const second = await mintLegacyNft(seller);
+    const { raffleId, raffle } = await startRaffle(2);
+
+    // The first sale creates the next raffle's account, the last one initializes it
+    await sell(seller, first.mint, first.ata, raffleId);
+    expect((await program.account.raffle.fetch(raffle)).active).to.equal(true);
+    await sell(seller, second.mint, second.ata, raffleId);
+
+    const ended = await program.account.raffle.fetch(raffle);
+    expect(ended.active).to.equal(false);
+    expect(ended.prizes.map((slot) => slot.prize.mint.toBase58()))
+      .to.deep.equal([first.mint.toBase58(), second.mint.toBase58()]);
+    expect(await currentRaffleId(program)).to.equal(raffleId + BigInt(1));
+    expect((await program.account.raffle.fetch(rafflePda(programId, raffleId + BigInt(1)))).active).to.equal(true);
+  });
+
+  it("cancels a raffle holding 1 of 2 prizes", async () => {
+    const seller = await createFundedWallet(provider);
+    const { mint, ata } = await mintLegacyNft(seller);
+    const { raffleId, raffle } = await startRaffle(2);
+    await sell(seller, mint, ata, raffleId);
+
+    await restartRaffle();
+    const ended = await program.account.raffle.fetch(raffle);
+    expect(ended.active).to.equal(false);
+    expect(ended.cancelled).to.equal(false);
+    expect(ended.prizeCount).to.equal(1);
+    expect((await program.account.raffle.fetch(rafflePda(programId, raffleId + BigInt(1)))).active).to.equal(true);
+  });
+
+  it("expires a raffle holding 1 of 2 prizes", async () => {
+    const seller = await createFundedWallet(provider);
+    const { mint, ata } = await mintLegacyNft(seller);
+    const { raffleId, raffle } = await startRaffle(2, 60);
+    await sell(seller, mint, ata, raffleId);
+
+    const { endTime } = await program.account.raffle.fetch(raffle);
+    await new Promise((resolve) => setTimeout(resolve, Math.max(0, endTime.toNumber() * 1000 - Date.now()) + 2000));
+    const closer = await createFundedWallet(provider);
+    await program.methods.closeExpiredRaffle()
+      .accounts({
+        raffle,
+        newRaffle: rafflePda(programId, raffleId + BigInt(1)),
+        tracker: trackerPda,
+        config: configPda,
+        signer: closer.publicKey,
+      })
+      .signers([closer])
+      .rpc();
+
+    const ended = await program.account.raffle.fetch(raffle);
+    expect(ended.active).to.equal(false);
+    expect(ended.prizeCount).to.equal(1);
+    expect((await program.account.raffle.fetch(rafflePda(programId, raffleId + BigInt(1)))).active).to.equal(true);
+  });
+});
@@ -2,7 +2,9 @@ import * as anchor from "@project-serum/anchor";
 import { web3 } from '@project-serum/anchor';
 import { assert } from "chai";
 import { MadRaffle } from "../target/types/mad_raffle";
-import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED } from "./helpers/seeds";
+import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED } from "./helpers/seeds";
+import { newRandomnessCommitment } from "./helpers/randomness";
+import { revealWinnerAccounts, ticketAccounts } from "./helpers/tickets";
 import { AUTH_KEYPAIR, VAULT_KEYPAIR } from "./helpers/keys";
 import { expect } from "chai";
 import { buildAndSendTx, createAndFundATA, createFundedWallet, createTokenAuthorizationRules } from "./utils/pnft";
@@ -31,6 +33,10 @@ describe("Raffle Loop", () => {
     [SUPER_RAFFLE_SEED],
     program.programId
   );
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
   for (let CURRENT_RAFFLE = 2; CURRENT_RAFFLE <= 3; CURRENT_RAFFLE++) {
 
     const [rafflePda, _raffleBump] = PublicKey.findProgramAddressSync(
@@ -53,7 +59,7 @@ describe("Raffle Loop", () => {
     });
     it("Checks the number of ticket buyers", async () => {
       const initialRaffleStatus = await program.account.raffle.fetch(rafflePda);
-      if (initialRaffleStatus.tickets.length > 0) return;
+      if (initialRaffleStatus.ticketCount > 0) return;
       const numberOfBuyers = 10;
       let numberOfTickets = 0;
       const wallets = [];
@@ -69,52 +75,58 @@ describe("Raffle Loop", () => {
       });
       await Promise.all(airdropPromises);
       await new Promise((resolve) => setTimeout(resolve, 5000));
-      const ticketPromises = wallets.map(async (wallet, i) => {
+      // Each purchase takes the raffle's next receipt, so buy one at a time
+      for (const wallet of wallets) {
         const timesToBuy = Math.floor(Math.random() * 3) + 1;
         numberOfTickets += timesToBuy;
-        for (let j = 0; j < timesToBuy; j++) {
-          let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
-
-          const signature = await program.methods
-            .buyTicket()
-            .accounts({
-              raffle: rafflePda,
-              buyer: wallet.publicKey,
-              feeVault: VAULT_KEYPAIR.publicKey,
-              tracker: trackerPda,
-              superVault: superVaultPda
-            })
-            .signers([wallet])
-            .rpc();
-
-          await connection.confirmTransaction({
-            signature,
-            lastValidBlockHeight,
-            blockhash
-          });
-        }
-      });
-      await Promise.all(ticketPromises);
+        await program.methods
+          .buyTicket(timesToBuy)
+          .accounts({
+            raffle: rafflePda,
+            ...await ticketAccounts(program, rafflePda, wallet.publicKey),
+            buyer: wallet.publicKey,
+            feeVault: VAULT_KEYPAIR.publicKey,
+            tracker: trackerPda,
+            superVault: superVaultPda,
+            config: configPda,
+          })
+          .signers([wallet])
+          .rpc();
+      }
       const raffleStatus = await program.account.raffle.fetch(rafflePda);
       assert.strictEqual(
-        raffleStatus.tickets.length,
-        numberOfBuyers + initialRaffleStatus.tickets.length, // Initial value + new buyersc
-        "The number of tickets bought should equal the number of buyers"
+        raffleStatus.receiptCount,
+        numberOfBuyers + initialRaffleStatus.receiptCount, // Initial value + one purchase per buyer
+        "There should be one receipt per buyer"
       );
-      // Compute the sum of all TicketHolder quantities
-      const initialTicketsBought = initialRaffleStatus.tickets.reduce((sum, ticketHolder) => sum + ticketHolder.qty, 0);
-      const totalTicketsBought = raffleStatus.tickets.reduce((sum, ticketHolder) => sum + ticketHolder.qty, 0);
-
-      // Check if the computed sum is equal to numberOfTickets
       assert.strictEqual(
-        totalTicketsBought,
-        numberOfTickets + initialTicketsBought,
-        "The total number of tickets bought should equal the sum of all TicketHolder quantities"
+        raffleStatus.ticketCount,
+        numberOfTickets + initialRaffleStatus.ticketCount,
+        "The ticket count should equal the number of tickets bought"
       );
     });
 
 
 
+    const { secret, commitment } = newRandomnessCommitment();
+
+    it(`commits randomness for raffle ${CURRENT_RAFFLE}`, async () => {
+      const tx = await program.methods.commitRandomness(commitment)
+        .accounts({
+          raffle: rafflePda,
+          tracker: trackerPda,
+          authority: AUTH_KEYPAIR.publicKey,
+          config: configPda,
+        })
+        .signers([AUTH_KEYPAIR])
+        .transaction();
+      let { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+      tx.feePayer = AUTH_KEYPAIR.publicKey;
+      tx.recentBlockhash = blockhash;
+      tx.lastValidBlockHeight = lastValidBlockHeight;
+      await anchor.web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
+    });
+
     it('transfers pnft to another account (1 ruleset)', async () => {
       const nftOwner = await createFundedWallet(provider);
 
@@ -152,7 +164,7 @@ describe("Raffle Loop", () => {
       /*         const initialReceiverBalance = await provider.connection.getTokenAccountBalance(destAta)
             expect(initialReceiverBalance.value.uiAmount).to.equal(0)
       */
-      const builder = await pNftTransferClient.buildTransferPNFT({
+      const endAccounts = {
         sourceAta: ata,
         nftMint: mint,
         destAta: destAta,
@@ -161,7 +173,23 @@ describe("Raffle Loop", () => {
         raffle: rafflePda,
         newRaffle: newRafflePda,
         creators: creators.map(creator => creator.address),
+      };
+      // The pot can't cover a reserve larger than the raffle's whole balance
+      const reserve = await pNftTransferClient.buildTransferPNFT({
+        ...endAccounts,
+        minPayment: new anchor.BN(await provider.connection.getBalance(rafflePda)),
       })
+      try {
+        await buildAndSendTx({
+          provider,
+          ixs: [await reserve.instruction()],
+          extraSigners: [nftOwner],
+        });
+        expect.fail("End should fail below the seller's minimum payment");
+      } catch (e) {
+        expect(e.logs?.join("\n") ?? e.toString()).to.contain("PaymentBelowMinimum");
+      }
+      const builder = await pNftTransferClient.buildTransferPNFT(endAccounts)
       await buildAndSendTx({
         provider,
         ixs: [await builder.instruction()],
@@ -173,11 +201,13 @@ describe("Raffle Loop", () => {
 
     });
     it('selects a winner', async () => {
-      const tx = await program.methods.selectWinner(new anchor.BN(CURRENT_RAFFLE))
+      const tx = await program.methods.revealAndPickWinner(new anchor.BN(CURRENT_RAFFLE), secret)
         .accounts({
           raffle: rafflePda,
-          authority: AUTH_KEYPAIR.publicKey,
-          random: Keypair.generate().publicKey,
+          ...await revealWinnerAccounts(program, rafflePda, secret),
+          cranker: AUTH_KEYPAIR.publicKey,
+          config: configPda,
+          slotHashes: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
         })
         .signers([AUTH_KEYPAIR])
         .transaction();
@@ -189,8 +219,7 @@ describe("Raffle Loop", () => {
     });
     it('sends prize', async () => {
       const raffleStatus = await program.account.raffle.fetch(rafflePda);
-      const { winner } = raffleStatus;
-      const { mint, ata } = raffleStatus.prize;
+      const { prize: { mint, ata }, winner } = raffleStatus.prizes[0];
 
       let destAta = await getAssociatedTokenAddress(mint, winner);
 
@@ -211,7 +240,7 @@ describe("Raffle Loop", () => {
 
       const winnerBalance = await provider.connection.getTokenAccountBalance(destAta);
       const postRaffleStatus = await program.account.raffle.fetch(rafflePda);
-      expect(postRaffleStatus.prize.sent).to.equal(true);
+      expect(postRaffleStatus.prizes[0].sent).to.equal(true);
       expect(winnerBalance.value.uiAmount).to.equal(1);
   });
 
@@ -219,7 +248,7 @@ describe("Raffle Loop", () => {
 
   }
   it("Logs the scoreboard", async () => {
-    const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
-    console.log(raffleTracker.scoreboard.map((score) => `${score.user.toString().slice(0, 4)}...: ${score.points.toString()}`));
+    const stats = await program.account.userStats.all();
+    console.log(stats.map(({ account }) => `${account.user.toString().slice(0, 4)}...: ${account.points.toString()}`));
   });
 });
\ No newline at end of file
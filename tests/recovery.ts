@@ -0,0 +1,97 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { CONFIG_SEED } from "./helpers/seeds";
+import { waitForSlotHashEviction } from "./helpers/randomness";
+import { receiptAccounts, ticketReceiptPda } from "./helpers/tickets";
+import { buyTickets, commitRandomness, endRaffleWithPNfts, rafflePda } from "./helpers/raffles";
+import { AUTH_KEYPAIR } from "./helpers/keys";
+import { createFundedWallet } from "./utils/pnft";
+const { PublicKey, SYSVAR_SLOT_HASHES_PUBKEY } = web3;
+
+describe("Randomness recovery", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+
+  let raffleId: bigint;
+  let raffle: web3.PublicKey;
+  let secret: number[];
+
+  const reveal = async (secret: number[], accounts: { receipt: web3.PublicKey, winnerStats: web3.PublicKey }) => {
+    const tx = await program.methods.revealAndPickWinner(new anchor.BN(raffleId.toString()), secret)
+      .accounts({
+        raffle,
+        ...accounts,
+        cranker: AUTH_KEYPAIR.publicKey,
+        config: configPda,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .signers([AUTH_KEYPAIR])
+      .transaction();
+    const { lastValidBlockHeight, blockhash } = await connection.getLatestBlockhash('finalized');
+    tx.feePayer = AUTH_KEYPAIR.publicKey;
+    tx.recentBlockhash = blockhash;
+    tx.lastValidBlockHeight = lastValidBlockHeight;
+    await web3.sendAndConfirmTransaction(connection, tx, [AUTH_KEYPAIR], { commitment: "finalized" });
+  };
+
+  const recommit = (signer: web3.Keypair) =>
+    program.methods.recommitRandomness(new anchor.BN(raffleId.toString()))
+      .accounts({
+        raffle,
+        signer: signer.publicKey,
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .signers([signer])
+      .rpc();
+
+  before(async () => {
+    await buyTickets(provider, program, 2);
+    secret = await commitRandomness(program);
+    raffleId = await endRaffleWithPNfts(provider, program);
+    raffle = rafflePda(program.programId, raffleId);
+  });
+
+  it("cannot recommit while the slot hash is available", async () => {
+    try {
+      await recommit(AUTH_KEYPAIR);
+      expect.fail("Recommit should fail before the slot hash is evicted");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("RandomnessNotExpired");
+    }
+  });
+
+  it("holds a draw revealed too late until the reveal timeout", async function () {
+    const { endSlot } = await program.account.raffle.fetch(raffle);
+    await waitForSlotHashEviction(connection, BigInt(endSlot.toString()));
+
+    const anyReceipt = await receiptAccounts(program, ticketReceiptPda(program.programId, raffleId, 0));
+    try {
+      await reveal(secret, anyReceipt);
+      expect.fail("Reveal should fail once the slot hash is evicted");
+    } catch (e) {
+      expect(e.logs?.join("\n") ?? e.toString()).to.contain("RandomnessExpired");
+    }
+
+    // The operator gets no head start: a fresh secret would let it re-roll the draw
+    for (const signer of [AUTH_KEYPAIR, await createFundedWallet(provider)]) {
+      try {
+        await recommit(signer);
+        expect.fail("Recommit should fail before the reveal timeout");
+      } catch (e) {
+        expect(e.error?.errorCode?.code).to.equal("RevealTimeoutNotReached");
+      }
+    }
+    const held = await program.account.raffle.fetch(raffle);
+    expect(held.endSlot.eq(endSlot)).to.equal(true);
+  });
+});
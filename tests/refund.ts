@@ -0,0 +1,241 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { assert, expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED } from "./helpers/seeds";
+import { AUTH_KEYPAIR, VAULT_KEYPAIR } from "./helpers/keys";
+import { ticketAccounts, ticketHolderPda } from "./helpers/tickets";
+import { createFundedWallet } from "./utils/pnft";
+const { PublicKey } = web3;
+
+describe("Raffle cancellation and refunds", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+
+  const [trackerPda, _trackerBump] = PublicKey.findProgramAddressSync(
+    [TRACKER_SEED],
+    program.programId
+  );
+  const [superVaultPda, _superVaultBump] = PublicKey.findProgramAddressSync(
+    [SUPER_RAFFLE_SEED],
+    program.programId
+  );
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+
+  let currentRaffle: number;
+  let rafflePda: web3.PublicKey;
+  let newRafflePda: web3.PublicKey;
+  let buyer: web3.Keypair;
+  let holderPda: web3.PublicKey;
+  let receiptPda: web3.PublicKey;
+  let receiptIndex: number;
+
+  before(async () => {
+    const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
+    currentRaffle = raffleTracker.currentRaffle.toNumber();
+    [rafflePda] = PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(currentRaffle))],
+      program.programId
+    );
+    [newRafflePda] = PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(currentRaffle + 1))],
+      program.programId
+    );
+    buyer = await createFundedWallet(provider);
+    holderPda = ticketHolderPda(program.programId, BigInt(currentRaffle), buyer.publicKey);
+    receiptIndex = (await program.account.raffle.fetch(rafflePda)).receiptCount;
+    const accounts = await ticketAccounts(program, rafflePda, buyer.publicKey);
+    receiptPda = accounts.receipt;
+    await program.methods.buyTicket(2)
+      .accounts({
+        raffle: rafflePda,
+        ...accounts,
+        buyer: buyer.publicKey,
+        feeVault: VAULT_KEYPAIR.publicKey,
+        tracker: trackerPda,
+        superVault: superVaultPda,
+        config: configPda,
+      })
+      .signers([buyer])
+      .rpc();
+  });
+
+  it("cannot claim a refund from an active raffle", async () => {
+    try {
+      await program.methods.claimRefund(new anchor.BN(currentRaffle))
+        .accounts({ raffle: rafflePda, ticketHolder: holderPda, user: buyer.publicKey })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Refund should fail before cancellation");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("NotCancelled");
+    }
+  });
+
+  it("unauthorized cannot cancel before the max duration", async () => {
+    try {
+      await program.methods.cancelRaffle()
+        .accounts({
+          raffle: rafflePda,
+          newRaffle: newRafflePda,
+          tracker: trackerPda,
+          config: configPda,
+          signer: buyer.publicKey,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Cancel should fail for a non-authority signer");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("UnauthorizedSigner");
+    }
+  });
+
+  it("authority cancels the raffle", async () => {
+    await program.methods.cancelRaffle()
+      .accounts({
+        raffle: rafflePda,
+        newRaffle: newRafflePda,
+        tracker: trackerPda,
+        config: configPda,
+        signer: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    expect(raffleStatus.active).to.be.false;
+    expect(raffleStatus.cancelled).to.be.true;
+    const newRaffleStatus = await program.account.raffle.fetch(newRafflePda);
+    expect(newRaffleStatus.active).to.be.true;
+    const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
+    expect(raffleTracker.currentRaffle.toNumber()).to.equal(currentRaffle + 1);
+  });
+
+  it("cannot close a receipt of an active raffle", async () => {
+    try {
+      await program.methods.closeReceipt(new anchor.BN(currentRaffle), receiptIndex)
+        .accounts({ raffle: rafflePda, receipt: receiptPda, user: buyer.publicKey })
+        .rpc();
+      assert.fail("Closing the receipt should fail while tickets can be drawn");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("TicketsInUse");
+    }
+  });
+
+  it("refunds the ticket holder once", async () => {
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    // Ticket price plus the rent of the closed TicketHolder account
+    const holderRent = await connection.getBalance(holderPda);
+    const expectedRefund = raffleStatus.ticketPrice.toNumber() * 2 + holderRent;
+    const balanceBefore = await connection.getBalance(buyer.publicKey);
+
+    await program.methods.claimRefund(new anchor.BN(currentRaffle))
+      .accounts({ raffle: rafflePda, ticketHolder: holderPda, user: buyer.publicKey })
+      .signers([buyer])
+      .rpc();
+
+    const balanceAfter = await connection.getBalance(buyer.publicKey);
+    expect(balanceAfter - balanceBefore).to.equal(expectedRefund);
+
+    try {
+      await program.methods.claimRefund(new anchor.BN(currentRaffle))
+        .accounts({ raffle: rafflePda, ticketHolder: holderPda, user: buyer.publicKey })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Second refund should fail");
+    } catch (e) {
+      // The TicketHolder account was closed by the first refund
+      expect(e.error?.errorCode?.code).to.equal("AccountNotInitialized");
+    }
+  });
+
+  it("returns the receipt rent to the buyer", async () => {
+    const receiptRent = await connection.getBalance(receiptPda);
+    const balanceBefore = await connection.getBalance(buyer.publicKey);
+    // Anyone can close it, the rent only goes to the buyer
+    await program.methods.closeReceipt(new anchor.BN(currentRaffle), receiptIndex)
+      .accounts({ raffle: rafflePda, receipt: receiptPda, user: buyer.publicKey })
+      .rpc();
+
+    const balanceAfter = await connection.getBalance(buyer.publicKey);
+    expect(balanceAfter - balanceBefore).to.equal(receiptRent);
+    expect(await connection.getAccountInfo(receiptPda)).to.be.null;
+  });
+
+  it("closes a raffle once its end time passes", async () => {
+    const rafflePdaAt = (id: number) => PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(id))],
+      program.programId
+    )[0];
+    const setDuration = (raffleDuration: number) => program.methods
+      .updateConfig({ raffleDuration: new anchor.BN(raffleDuration) } as any)
+      .accounts({ config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+    // Roll over to a raffle created with a 2 second duration
+    await setDuration(2);
+    const open = currentRaffle + 1;
+    await program.methods.cancelRaffle()
+      .accounts({
+        raffle: rafflePdaAt(open),
+        newRaffle: rafflePdaAt(open + 1),
+        tracker: trackerPda,
+        config: configPda,
+        signer: AUTH_KEYPAIR.publicKey,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    await setDuration(0);
+    const expiring = rafflePdaAt(open + 1);
+    const closeAccounts = {
+      raffle: expiring,
+      newRaffle: rafflePdaAt(open + 2),
+      tracker: trackerPda,
+      config: configPda,
+      signer: buyer.publicKey,
+    };
+    const { startTime, endTime } = await program.account.raffle.fetch(expiring);
+    expect(endTime.sub(startTime).toNumber()).to.equal(2);
+
+    try {
+      await program.methods.closeExpiredRaffle().accounts(closeAccounts).signers([buyer]).rpc();
+      assert.fail("Close should fail before the end time");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("NotExpired");
+    }
+
+    await new Promise(resolve => setTimeout(resolve, 3000));
+    try {
+      await program.methods.buyTicket(1)
+        .accounts({
+          raffle: expiring,
+          ...await ticketAccounts(program, expiring, buyer.publicKey),
+          buyer: buyer.publicKey,
+          feeVault: VAULT_KEYPAIR.publicKey,
+          tracker: trackerPda,
+          superVault: superVaultPda,
+          config: configPda,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Purchase should fail after the end time");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("RaffleClosed");
+    }
+
+    await program.methods.closeExpiredRaffle().accounts(closeAccounts).signers([buyer]).rpc();
+    const closed = await program.account.raffle.fetch(expiring);
+    expect(closed.active).to.be.false;
+    expect(closed.cancelled).to.be.false;
+    const next = await program.account.raffle.fetch(rafflePdaAt(open + 2));
+    expect(next.active).to.be.true;
+    expect(next.endTime.toNumber()).to.equal(0);
+  });
+});
@@ -0,0 +1,83 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { LocalScheduler } from "./helpers/scheduler";
+import { buyTickets, commitRandomness, currentRaffleId, endRaffleWithPNfts, rafflePda } from "./helpers/raffles";
+import { waitForSlotHashEviction } from "./helpers/randomness";
+import { createFundedWallet } from "./utils/pnft";
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+
+describe("Housekeeping scheduler", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+
+  let scheduler: LocalScheduler;
+  let raffleId: bigint;
+  let raffle: web3.PublicKey;
+
+  before(async () => {
+    scheduler = new LocalScheduler(provider, program, await createFundedWallet(provider, 1));
+    raffleId = await currentRaffleId(program);
+    raffle = rafflePda(program.programId, raffleId);
+  });
+
+  const queuedFor = async (id: bigint) => {
+    const { items } = await program.account.workQueue.fetch(scheduler.workQueue);
+    return items.filter((item) => item.raffleId.toString() === id.toString());
+  };
+
+  it("has no work for the active raffle", async () => {
+    await scheduler.sync(raffleId);
+    expect(await queuedFor(raffleId)).to.be.empty;
+  });
+
+  it("ends the raffle", async () => {
+    await buyTickets(provider, program, 3);
+    scheduler.secrets.set(raffleId.toString(), await commitRandomness(program));
+    await endRaffleWithPNfts(provider, program);
+  });
+
+  it("queues the draw of the raffle that ended last", async () => {
+    await scheduler.sync(raffleId);
+    const queued = await queuedFor(raffleId);
+    expect(queued.map(({ task }) => Object.keys(task)[0])).to.deep.equal(["drawWinner"]);
+  });
+
+  it("picks and sends every prize on its own", async () => {
+    for (let ticks = 0; ticks < 10 && (await queuedFor(raffleId)).length > 0; ticks++) {
+      await scheduler.tick();
+    }
+    expect(await queuedFor(raffleId)).to.be.empty;
+
+    const { prizes } = await program.account.raffle.fetch(raffle);
+    for (const { prize, winner, sent } of prizes) {
+      expect(sent).to.equal(true);
+      const winnerAta = await getAssociatedTokenAddress(prize.mint, winner, true);
+      const balance = await connection.getTokenAccountBalance(winnerAta);
+      expect(balance.value.uiAmount).to.equal(1);
+    }
+  });
+
+  it("swaps a draw whose slot hash expired for a recommit", async () => {
+    const expiredId = await currentRaffleId(program);
+    const expired = rafflePda(program.programId, expiredId);
+    await buyTickets(provider, program, 1);
+    await commitRandomness(program);
+    await endRaffleWithPNfts(provider, program);
+    const { endSlot } = await program.account.raffle.fetch(expired);
+    await waitForSlotHashEviction(connection, BigInt(endSlot.toString()));
+
+    await scheduler.sync(expiredId);
+    let queued = await queuedFor(expiredId);
+    expect(queued.map(({ task }) => Object.keys(task)[0])).to.deep.equal(["recommitRandomness"]);
+
+    // Nobody can recommit before the reveal timeout, so the task stays queued
+    expect(await scheduler.tick()).to.equal(0);
+    queued = await queuedFor(expiredId);
+    expect(queued.map(({ task }) => Object.keys(task)[0])).to.deep.equal(["recommitRandomness"]);
+  });
+});
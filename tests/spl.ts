@@ -0,0 +1,110 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { assert, expect } from "chai";
+import { createMint, getAssociatedTokenAddress, getOrCreateAssociatedTokenAccount } from "@solana/spl-token";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { raffleNumberBuffer, RAFFLE_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED, PAYMENT_MINT_SEED } from "./helpers/seeds";
+import { AUTH_KEYPAIR, VAULT_KEYPAIR } from "./helpers/keys";
+import { ticketAccounts } from "./helpers/tickets";
+import { createFundedWallet } from "./utils/pnft";
+const { PublicKey, Keypair } = web3;
+
+// Pyth feeds aren't available on the local validator, so only the
+// whitelist is exercised here
+describe("SPL ticket payments", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+
+  const [trackerPda, _trackerBump] = PublicKey.findProgramAddressSync(
+    [TRACKER_SEED],
+    program.programId
+  );
+  const [superVaultPda, _superVaultBump] = PublicKey.findProgramAddressSync(
+    [SUPER_RAFFLE_SEED],
+    program.programId
+  );
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+  const priceFeed = Keypair.generate().publicKey;
+
+  let mint: web3.PublicKey;
+  let paymentConfigPda: web3.PublicKey;
+
+  before(async () => {
+    const payer = await createFundedWallet(provider);
+    mint = await createMint(provider.connection, payer, payer.publicKey, null, 6);
+    [paymentConfigPda] = PublicKey.findProgramAddressSync(
+      [PAYMENT_MINT_SEED, mint.toBuffer()],
+      program.programId
+    );
+  });
+
+  it("unauthorized cannot whitelist a mint", async () => {
+    const unauthorized = await createFundedWallet(provider);
+    try {
+      await program.methods.setPaymentMint(priceFeed, true)
+        .accounts({ paymentConfig: paymentConfigPda, mint, config: configPda, authority: unauthorized.publicKey })
+        .signers([unauthorized])
+        .rpc();
+      assert.fail("Whitelisting should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("UnauthorizedSigner");
+    }
+  });
+
+  it("whitelists and disables a mint", async () => {
+    await program.methods.setPaymentMint(priceFeed, true)
+      .accounts({ paymentConfig: paymentConfigPda, mint, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    let paymentConfig = await program.account.paymentMint.fetch(paymentConfigPda);
+    assert.ok(paymentConfig.mint.equals(mint));
+    assert.ok(paymentConfig.priceFeed.equals(priceFeed));
+    assert.ok(paymentConfig.enabled);
+
+    await program.methods.setPaymentMint(priceFeed, false)
+      .accounts({ paymentConfig: paymentConfigPda, mint, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    paymentConfig = await program.account.paymentMint.fetch(paymentConfigPda);
+    assert.ok(!paymentConfig.enabled);
+  });
+
+  it("cannot buy with a disabled mint", async () => {
+    const buyer = await createFundedWallet(provider);
+    const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
+    const [rafflePda] = PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(raffleTracker.currentRaffle.toString()))],
+      program.programId
+    );
+    const buyerTokenAccount = await getOrCreateAssociatedTokenAccount(provider.connection, buyer, mint, buyer.publicKey);
+    const { solPriceFeed } = await program.account.programConfig.fetch(configPda);
+    try {
+      await program.methods.buyTicketSpl(1)
+        .accounts({
+          raffle: rafflePda,
+          ...await ticketAccounts(program, rafflePda, buyer.publicKey),
+          buyer: buyer.publicKey,
+          paymentMint: mint,
+          paymentConfig: paymentConfigPda,
+          buyerTokenAccount: buyerTokenAccount.address,
+          potEscrow: await getAssociatedTokenAddress(mint, rafflePda, true),
+          tokenPriceFeed: priceFeed,
+          solPriceFeed,
+          feeVault: VAULT_KEYPAIR.publicKey,
+          tracker: trackerPda,
+          superVault: superVaultPda,
+          config: configPda,
+        })
+        .signers([buyer])
+        .rpc();
+      assert.fail("Purchase should fail with a disabled mint");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("MintNotWhitelisted");
+    }
+  });
+});
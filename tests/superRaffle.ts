@@ -0,0 +1,166 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { assert, expect } from "chai";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { raffleNumberBuffer, CONFIG_SEED, SUPER_DRAW_SEED, SUPER_RAFFLE_SEED, SUPER_RESULT_SEED } from "./helpers/seeds";
+import { hashEntropy, newRandomnessCommitment, slotHashAfter } from "./helpers/randomness";
+import { seasonPda, tallySuperRaffle, tallyWinner, userStatsPda } from "./helpers/points";
+import { AUTH_KEYPAIR } from "./helpers/keys";
+const { PublicKey, SYSVAR_SLOT_HASHES_PUBKEY } = web3;
+
+const wait = (ms: number) => new Promise(resolve => setTimeout(resolve, ms));
+
+describe("Super Raffle", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const { connection } = program.provider;
+
+  const [superVaultPda, _superVaultBump] = PublicKey.findProgramAddressSync(
+    [SUPER_RAFFLE_SEED],
+    program.programId
+  );
+  const [superDrawPda, _superDrawBump] = PublicKey.findProgramAddressSync(
+    [SUPER_DRAW_SEED],
+    program.programId
+  );
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+
+  const seasonAccount = seasonPda(program.programId);
+
+  const { secret, commitment } = newRandomnessCommitment();
+
+  it("closes the points epoch", async () => {
+    const season = await program.account.season.fetch(seasonAccount);
+    await program.methods.closeSuperRaffleEpoch(true)
+      .accounts({ superDraw: superDrawPda, season: seasonAccount, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    const superDraw = await program.account.superDraw.fetch(superDrawPda);
+    assert.strictEqual(superDraw.commitment, null);
+    assert.strictEqual(superDraw.totalPoints.toString(), season.totalPoints.toString(), "season points should be frozen");
+
+    const postClose = await program.account.season.fetch(seasonAccount);
+    assert.strictEqual(postClose.epoch, season.epoch + 1);
+    assert.strictEqual(postClose.startEpoch, postClose.epoch, "season should be reset");
+    assert.strictEqual(postClose.totalPoints.toNumber(), 0);
+  });
+
+  it("cannot tally out of order", async () => {
+    const stats = await program.account.userStats.all();
+    const [first, second] = stats
+      .sort((a, b) => Buffer.compare(a.account.user.toBuffer(), b.account.user.toBuffer()))
+      .map(({ publicKey }) => ({ pubkey: publicKey, isSigner: false, isWritable: true }));
+    try {
+      await program.methods.tallySuperRaffle(true)
+        .accounts({ superDraw: superDrawPda, season: seasonAccount, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+        .remainingAccounts([second, first])
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+      assert.fail("Descending tally should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("TallyOutOfOrder");
+    }
+  });
+
+  let tallied: web3.PublicKey[];
+
+  it("tallies the frozen points before the commit", async () => {
+    tallied = await tallySuperRaffle(
+      program,
+      { superDraw: superDrawPda, season: seasonAccount, config: configPda },
+      AUTH_KEYPAIR
+    );
+    const superDraw = await program.account.superDraw.fetch(superDrawPda);
+    assert.strictEqual(superDraw.talliedPoints.toString(), superDraw.totalPoints.toString(), "tally should be complete");
+  });
+
+  it("commits the super raffle draw", async () => {
+    const frozen = await program.account.superDraw.fetch(superDrawPda);
+    const season = await program.account.season.fetch(seasonAccount);
+    await program.methods.commitSuperRaffle(commitment, false)
+      .accounts({ superDraw: superDrawPda, season: seasonAccount, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+    const superDraw = await program.account.superDraw.fetch(superDrawPda);
+    expect(superDraw.commitment).to.deep.equal(commitment);
+    assert.strictEqual(superDraw.totalPoints.toString(), frozen.totalPoints.toString(), "frozen points should be kept");
+    assert.strictEqual(superDraw.talliedPoints.toString(), frozen.talliedPoints.toString(), "tally should be kept");
+    assert.strictEqual((await program.account.season.fetch(seasonAccount)).epoch, season.epoch, "epoch should not close twice");
+  });
+
+  it("cannot commit twice", async () => {
+    try {
+      await program.methods.commitSuperRaffle(commitment, true)
+        .accounts({ superDraw: superDrawPda, season: seasonAccount, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+      assert.fail("Second commit should fail");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("DrawAlreadyCommitted");
+    }
+  });
+
+  it("cannot recommit while the slot hash is available", async () => {
+    try {
+      await program.methods.recommitSuperRaffle()
+        .accounts({
+          superDraw: superDrawPda,
+          signer: AUTH_KEYPAIR.publicKey,
+          slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+      assert.fail("Recommit should fail before the slot hash is evicted");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("RandomnessNotExpired");
+    }
+  });
+
+  it("draws the super raffle", async () => {
+    const superDraw = await program.account.superDraw.fetch(superDrawPda);
+
+    // Let a slot pass so the post-commit slot hash exists
+    await wait(2000);
+    const drawId = superDraw.drawCount.toNumber() + 1;
+    const slotHashes = await connection.getAccountInfo(SYSVAR_SLOT_HASHES_PUBKEY);
+    const slotHash = slotHashAfter(slotHashes.data, BigInt(superDraw.commitSlot.toString()));
+    const entropy = hashEntropy([Buffer.from(secret), slotHash, raffleNumberBuffer(BigInt(drawId))]);
+    const winner = await tallyWinner(program, tallied, BigInt(superDraw.totalPoints.toString()), entropy);
+
+    const [resultPda] = PublicKey.findProgramAddressSync(
+      [SUPER_RESULT_SEED, raffleNumberBuffer(BigInt(drawId))],
+      program.programId
+    );
+    const vaultBefore = await connection.getBalance(superVaultPda);
+    const winnerBefore = await connection.getBalance(winner);
+    await program.methods.drawSuperRaffle(secret)
+      .accounts({
+        superDraw: superDrawPda,
+        result: resultPda,
+        superVault: superVaultPda,
+        season: seasonAccount,
+        cranker: AUTH_KEYPAIR.publicKey,
+        winner,
+        winnerStats: userStatsPda(program.programId, winner),
+        slotHashes: SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .signers([AUTH_KEYPAIR])
+      .rpc();
+
+    const result = await program.account.superRaffleResult.fetch(resultPda);
+    const vaultAfter = await connection.getBalance(superVaultPda);
+    const winnerAfter = await connection.getBalance(winner);
+    assert.ok(result.winner.equals(winner), "result should record the winner");
+    assert.ok(result.seasonReset, "result should record the season reset");
+    assert.strictEqual(result.amount.toNumber(), vaultBefore - vaultAfter);
+    assert.strictEqual(winnerAfter - winnerBefore, result.amount.toNumber());
+
+    const postDraw = await program.account.superDraw.fetch(superDrawPda);
+    assert.strictEqual(postDraw.commitment, null);
+  });
+});
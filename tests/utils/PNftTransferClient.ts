@@ -10,8 +10,9 @@ import { Idl } from "@project-serum/anchor";
 import { Connection, PublicKey, SystemProgram, SYSVAR_INSTRUCTIONS_PUBKEY } from "@solana/web3.js";
 import { MadRaffle } from "../../target/types/mad_raffle";
 import { fetchNft, findTokenRecordPDA } from './pnft';
-import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, getAssociatedTokenAddressSync } from "@solana/spl-token";
 import { AUTH_KEYPAIR } from "../helpers/keys";
+import { COLLECTION_SEED, CONFIG_SEED, LISTING_BOOK_SEED, LISTING_SEED, SEASON_SEED, USER_STATS_SEED } from "../helpers/seeds";
 const TMETA_PROG_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 
 export class PNftTransferClient  {
@@ -84,6 +85,10 @@ export class PNftTransferClient  {
             meta
         );
         const ruleSet = inflatedMeta.programmableConfig?.ruleSet;
+        // Passed whether or not `set_collection` created it, the program checks the seeds
+        const collectionSettingsPda = inflatedMeta.collection
+            ? this.findCollectionSettingsPDA(inflatedMeta.collection.key)
+            : SystemProgram.programId;
 
         const [ownerTokenRecordPda, ownerTokenRecordBump] =
             await findTokenRecordPDA(nftMint, sourceAta);
@@ -115,10 +120,43 @@ export class PNftTransferClient  {
             ruleSet,
             nftEditionPda,
             authDataSerialized,
+            collectionSettingsPda,
         };
     }
 
 
+    findConfigPDA() {
+        return PublicKey.findProgramAddressSync([CONFIG_SEED], this.program.programId)[0];
+    }
+
+    findSeasonPDA() {
+        return PublicKey.findProgramAddressSync([SEASON_SEED], this.program.programId)[0];
+    }
+
+    findUserStatsPDA(user: PublicKey) {
+        return PublicKey.findProgramAddressSync([USER_STATS_SEED, user.toBuffer()], this.program.programId)[0];
+    }
+
+    findCollectionSettingsPDA(collection: PublicKey) {
+        return PublicKey.findProgramAddressSync([COLLECTION_SEED, collection.toBuffer()], this.program.programId)[0];
+    }
+
+    findListingPDA(nftMint: PublicKey) {
+        return PublicKey.findProgramAddressSync([LISTING_SEED, nftMint.toBuffer()], this.program.programId)[0];
+    }
+
+    findListingBookPDA() {
+        return PublicKey.findProgramAddressSync([LISTING_BOOK_SEED], this.program.programId)[0];
+    }
+
+    pnftShared() {
+        return {
+            authorizationRulesProgram: AUTH_PROG_ID,
+            tokenMetadataProgram: TMETA_PROG_ID,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        };
+    }
+
     async buildTransferPNFT({
         nftMint,
         sourceAta,
@@ -128,7 +166,11 @@ export class PNftTransferClient  {
         raffle,
         tracker,
         creators,
-        newRaffle
+        newRaffle,
+        minPayment = new anchor.BN(0),
+        minTokenPayment = new anchor.BN(0),
+        collectionSettings = null,
+        tokenProgram = TOKEN_PROGRAM_ID,
     }: {
         nftMint: PublicKey;
         sourceAta: PublicKey;
@@ -139,6 +181,10 @@ export class PNftTransferClient  {
         tracker: PublicKey;
         creators: PublicKey[];
         newRaffle: PublicKey;
+        minPayment?: anchor.BN;
+        minTokenPayment?: anchor.BN;
+        collectionSettings?: PublicKey | null;
+        tokenProgram?: PublicKey;
     }) {
         //pnft
         const {
@@ -150,6 +196,7 @@ export class PNftTransferClient  {
             ruleSet,
             nftEditionPda,
             authDataSerialized,
+            collectionSettingsPda,
         } = await this.prepPnftAccounts({
             nftMint,
             destAta,
@@ -170,7 +217,7 @@ export class PNftTransferClient  {
           }, {});
         const threadAddress = new PublicKey('CTYHvECD7HcjzjkcwsdVDhesTLHwjSNnGm913vCLc7U1');
         const builder = this.program.methods
-            .endRaffle(authDataSerialized, !!ruleSet)
+            .endRaffle(authDataSerialized, !!ruleSet, minPayment, minTokenPayment)
             .accounts({
                 owner,
                 src: sourceAta,
@@ -180,7 +227,7 @@ export class PNftTransferClient  {
                 nftMint,
                 edition: nftEditionPda,
                 nftMetadata: meta,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgram,
                 systemProgram: SystemProgram.programId,
                 rent: anchor.web3.SYSVAR_RENT_PUBKEY,
                 associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -192,7 +239,12 @@ export class PNftTransferClient  {
                 raffle,
                 newRaffle,
                 tracker,
+                sellerStats: this.findUserStatsPDA(owner),
+                season: this.findSeasonPDA(),
+                config: this.findConfigPDA(),
                 ...creatorAccounts,
+                collectionSettings: collectionSettings ?? collectionSettingsPda,
+                listingBook: this.findListingBookPDA(),
             })
             .remainingAccounts(remainingAccounts)
 
@@ -205,7 +257,9 @@ export class PNftTransferClient  {
         sourceAta,
         destAta,
         raffle,
-        raffleId
+        raffleId,
+        slot = 0,
+        tokenProgram = TOKEN_PROGRAM_ID,
     }: {
         winner: PublicKey;
         authority: PublicKey;
@@ -214,6 +268,8 @@ export class PNftTransferClient  {
         destAta: PublicKey;
         raffle: PublicKey;
         raffleId: anchor.BN;
+        slot?: number;
+        tokenProgram?: PublicKey;
     }) {
         //pnft
         const {
@@ -238,7 +294,7 @@ export class PNftTransferClient  {
             });
         }
         const builder = this.program.methods
-            .distributePrize(raffleId, authDataSerialized, !!ruleSet)
+            .distributePrize(raffleId, slot, authDataSerialized, !!ruleSet)
             .accounts({
                 authority,
                 winner,
@@ -249,7 +305,7 @@ export class PNftTransferClient  {
                 nftMint,
                 edition: nftEditionPda,
                 nftMetadata: meta,
-                tokenProgram: TOKEN_PROGRAM_ID,
+                tokenProgram,
                 systemProgram: SystemProgram.programId,
                 rent: anchor.web3.SYSVAR_RENT_PUBKEY,
                 associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
@@ -259,10 +315,186 @@ export class PNftTransferClient  {
                     instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
                 },
                 raffle,
+                config: this.findConfigPDA(),
             })
             .signers([AUTH_KEYPAIR])
             .remainingAccounts(remainingAccounts)
 
         return builder
     }
-}
\ No newline at end of file
+
+    async buildListPNFT({
+        nftMint,
+        sourceAta,
+        seller,
+        ask,
+        collectionSettings = null,
+        tokenProgram = TOKEN_PROGRAM_ID,
+    }: {
+        nftMint: PublicKey;
+        sourceAta: PublicKey;
+        seller: PublicKey;
+        ask: anchor.BN;
+        collectionSettings?: PublicKey | null;
+        tokenProgram?: PublicKey;
+    }) {
+        const listing = this.findListingPDA(nftMint);
+        const escrow = getAssociatedTokenAddressSync(nftMint, listing, true, tokenProgram);
+        const {
+            meta,
+            ownerTokenRecordPda,
+            destTokenRecordPda,
+            ruleSet,
+            nftEditionPda,
+            authDataSerialized,
+            collectionSettingsPda,
+        } = await this.prepPnftAccounts({
+            nftMint,
+            destAta: escrow,
+            authData: null,
+            sourceAta,
+        });
+        return this.program.methods
+            .listNft(ask, authDataSerialized, !!ruleSet)
+            .accounts({
+                seller,
+                src: sourceAta,
+                escrow,
+                nftMint,
+                listing,
+                listingBook: this.findListingBookPDA(),
+                config: this.findConfigPDA(),
+                tokenProgram,
+                systemProgram: SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                nftMetadata: meta,
+                edition: nftEditionPda,
+                ownerTokenRecord: ownerTokenRecordPda,
+                destTokenRecord: destTokenRecordPda,
+                pnftShared: this.pnftShared(),
+                collectionSettings: collectionSettings ?? collectionSettingsPda,
+            })
+            .remainingAccounts(ruleSet ? [{ pubkey: ruleSet, isSigner: false, isWritable: false }] : []);
+    }
+
+    async buildCancelListing({
+        nftMint,
+        seller,
+        tokenProgram = TOKEN_PROGRAM_ID,
+    }: {
+        nftMint: PublicKey;
+        seller: PublicKey;
+        tokenProgram?: PublicKey;
+    }) {
+        const listing = this.findListingPDA(nftMint);
+        const escrow = getAssociatedTokenAddressSync(nftMint, listing, true, tokenProgram);
+        const dest = getAssociatedTokenAddressSync(nftMint, seller, false, tokenProgram);
+        const {
+            meta,
+            ownerTokenRecordPda,
+            destTokenRecordPda,
+            ruleSet,
+            nftEditionPda,
+            authDataSerialized,
+        } = await this.prepPnftAccounts({
+            nftMint,
+            destAta: dest,
+            authData: null,
+            sourceAta: escrow,
+        });
+        return this.program.methods
+            .cancelListing(authDataSerialized, !!ruleSet)
+            .accounts({
+                seller,
+                escrow,
+                dest,
+                nftMint,
+                listing,
+                listingBook: this.findListingBookPDA(),
+                tokenProgram,
+                systemProgram: SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                nftMetadata: meta,
+                edition: nftEditionPda,
+                ownerTokenRecord: ownerTokenRecordPda,
+                destTokenRecord: destTokenRecordPda,
+                pnftShared: this.pnftShared(),
+            })
+            .remainingAccounts(ruleSet ? [{ pubkey: ruleSet, isSigner: false, isWritable: false }] : []);
+    }
+
+    async buildSettleListing({
+        nftMint,
+        payer,
+        seller,
+        raffle,
+        newRaffle,
+        tracker,
+        creators,
+        collectionSettings = null,
+        tokenProgram = TOKEN_PROGRAM_ID,
+    }: {
+        nftMint: PublicKey;
+        payer: PublicKey;
+        seller: PublicKey;
+        raffle: PublicKey;
+        newRaffle: PublicKey;
+        tracker: PublicKey;
+        creators: PublicKey[];
+        collectionSettings?: PublicKey | null;
+        tokenProgram?: PublicKey;
+    }) {
+        const listing = this.findListingPDA(nftMint);
+        const src = getAssociatedTokenAddressSync(nftMint, listing, true, tokenProgram);
+        const dest = getAssociatedTokenAddressSync(nftMint, raffle, true, tokenProgram);
+        const {
+            meta,
+            ownerTokenRecordPda,
+            destTokenRecordPda,
+            ruleSet,
+            nftEditionPda,
+            authDataSerialized,
+            collectionSettingsPda,
+        } = await this.prepPnftAccounts({
+            nftMint,
+            destAta: dest,
+            authData: null,
+            sourceAta: src,
+        });
+        const creatorAccounts = creators.reduce((acc, creator, i) => {
+            acc[`creator${i + 1}`] = creator;
+            return acc;
+          }, {});
+        return this.program.methods
+            .settleListing(authDataSerialized, !!ruleSet)
+            .accounts({
+                payer,
+                seller,
+                listing,
+                listingBook: this.findListingBookPDA(),
+                src,
+                dest,
+                nftMint,
+                tokenProgram,
+                systemProgram: SystemProgram.programId,
+                rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                nftMetadata: meta,
+                edition: nftEditionPda,
+                ownerTokenRecord: ownerTokenRecordPda,
+                destTokenRecord: destTokenRecordPda,
+                pnftShared: this.pnftShared(),
+                raffle,
+                newRaffle,
+                tracker,
+                sellerStats: this.findUserStatsPDA(seller),
+                season: this.findSeasonPDA(),
+                config: this.findConfigPDA(),
+                ...creatorAccounts,
+                collectionSettings: collectionSettings ?? collectionSettingsPda,
+            })
+            .remainingAccounts(ruleSet ? [{ pubkey: ruleSet, isSigner: false, isWritable: false }] : []);
+    }
+}
@@ -39,7 +39,16 @@ import {
   
   import { encode } from '@msgpack/msgpack';
   import {backOff} from "exponential-backoff";
-  import { ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID } from '@solana/spl-token';
+  import {
+    ASSOCIATED_TOKEN_PROGRAM_ID,
+    createInitializeMintCloseAuthorityInstruction,
+    createInitializeMintInstruction,
+    ExtensionType,
+    getAssociatedTokenAddressSync,
+    getMintLen,
+    TOKEN_2022_PROGRAM_ID,
+    TOKEN_PROGRAM_ID,
+  } from '@solana/spl-token';
   
   const TMETA_PROG_ID = new PublicKey('metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s');
 
@@ -582,7 +591,122 @@ import {
       masterEdition: masterEditionAddress,
     };
   };
-  
+
+  // Token Metadata wants a Token-2022 NFT mint closable by its master edition,
+  // so the mint is made here rather than by the create instruction, which
+  // would pick its own extensions
+  export const createAndFundToken2022Nft = async ({
+    provider,
+    owner,
+    royaltyBps,
+    creators,
+    collection,
+  }: {
+    provider: AnchorProvider;
+    owner?: Keypair;
+    royaltyBps?: number;
+    creators?: CreatorInput[];
+    collection?: PublicKey;
+  }): Promise<{
+    mint: PublicKey;
+    ata: PublicKey;
+    owner: Keypair;
+    metadata: PublicKey;
+    masterEdition: PublicKey;
+  }> => {
+    const usedOwner = owner ?? (await createFundedWallet(provider));
+    const mint = Keypair.generate();
+    const [metadata] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TMETA_PROG_ID.toBuffer(), mint.publicKey.toBuffer()],
+      TMETA_PROG_ID
+    );
+    const [masterEdition] = PublicKey.findProgramAddressSync(
+      [Buffer.from('metadata'), TMETA_PROG_ID.toBuffer(), mint.publicKey.toBuffer(), Buffer.from('edition')],
+      TMETA_PROG_ID
+    );
+
+    const mintLen = getMintLen([ExtensionType.MintCloseAuthority]);
+    const ixs = [
+      SystemProgram.createAccount({
+        fromPubkey: usedOwner.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space: mintLen,
+        lamports: await provider.connection.getMinimumBalanceForRentExemption(mintLen),
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeMintCloseAuthorityInstruction(mint.publicKey, masterEdition, TOKEN_2022_PROGRAM_ID),
+      createInitializeMintInstruction(mint.publicKey, 0, usedOwner.publicKey, usedOwner.publicKey, TOKEN_2022_PROGRAM_ID),
+    ];
+
+    const createIx = createCreateInstruction(
+      {
+        metadata,
+        masterEdition,
+        mint: mint.publicKey,
+        authority: usedOwner.publicKey,
+        payer: usedOwner.publicKey,
+        splTokenProgram: TOKEN_2022_PROGRAM_ID,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        updateAuthority: usedOwner.publicKey,
+      },
+      {
+        createArgs: {
+          __kind: 'V1',
+          assetData: {
+            uri: 'https://madlads.s3.us-west-2.amazonaws.com/json/3482.json',
+            name: 'Mad Lads #3482',
+            symbol: 'MAD',
+            sellerFeeBasisPoints: royaltyBps ?? 0,
+            creators:
+              creators?.map((c) => {
+                return { address: c.address, share: c.share, verified: false };
+              }) ?? null,
+            primarySaleHappened: true,
+            isMutable: true,
+            tokenStandard: TokenStandard.NonFungible,
+            collection: collection ? { verified: false, key: collection } : null,
+            uses: null,
+            collectionDetails: null,
+            ruleSet: null,
+          },
+          decimals: 0,
+          printSupply: { __kind: 'Zero' },
+        },
+      }
+    );
+
+    const ata = getAssociatedTokenAddressSync(mint.publicKey, usedOwner.publicKey, false, TOKEN_2022_PROGRAM_ID);
+    const mintIx = createMintInstruction(
+      {
+        token: ata,
+        tokenOwner: usedOwner.publicKey,
+        metadata,
+        masterEdition,
+        mint: mint.publicKey,
+        payer: usedOwner.publicKey,
+        authority: usedOwner.publicKey,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        splAtaProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        splTokenProgram: TOKEN_2022_PROGRAM_ID,
+      },
+      { mintArgs: { __kind: 'V1', amount: 1, authorizationData: null } }
+    );
+
+    await buildAndSendTx({
+      provider,
+      ixs: [...ixs, createIx, mintIx],
+      extraSigners: [usedOwner, mint],
+    });
+
+    return {
+      mint: mint.publicKey,
+      ata,
+      owner: usedOwner,
+      metadata,
+      masterEdition,
+    };
+  };
+
   export const createTokenAuthorizationRules = async (
     provider: AnchorProvider,
     payer: Keypair,
@@ -0,0 +1,183 @@
+import * as anchor from "@project-serum/anchor";
+import { web3 } from '@project-serum/anchor';
+import { expect } from "chai";
+import { randomBytes } from "crypto";
+import { MadRaffle } from "../target/types/mad_raffle";
+import { MockOracle } from "../target/types/mock_oracle";
+import { raffleNumberBuffer, RAFFLE_SEED, RANDOMNESS_SEED, SUPER_RAFFLE_SEED, TRACKER_SEED, CONFIG_SEED } from "./helpers/seeds";
+import { drawEntropy } from "./helpers/randomness";
+import { receiptAccounts, ticketAccounts, ticketReceiptPda, winnerAccounts } from "./helpers/tickets";
+import { COLLECTION_KEYPAIR, AUTH_KEYPAIR, VAULT_KEYPAIR } from "./helpers/keys";
+import { buildAndSendTx, createAndFundATA, createFundedWallet } from "./utils/pnft";
+import { PNftTransferClient } from './utils/PNftTransferClient';
+import { getAssociatedTokenAddress } from "@solana/spl-token";
+const { PublicKey, Keypair } = web3;
+
+// Requires mad_raffle built with `--features mock-oracle`, skipped otherwise
+describe("VRF winner selection (mock oracle)", () => {
+  const provider = anchor.AnchorProvider.local();
+  anchor.setProvider(provider);
+
+  const pNftTransferClient = new PNftTransferClient(provider.connection, provider.wallet as anchor.Wallet);
+
+  const program = anchor.workspace.MadRaffle as anchor.Program<MadRaffle>;
+  const mockOracle = anchor.workspace.MockOracle as anchor.Program<MockOracle>;
+
+  const [trackerPda, _trackerBump] = PublicKey.findProgramAddressSync(
+    [TRACKER_SEED],
+    program.programId
+  );
+  const [superVaultPda, _superVaultBump] = PublicKey.findProgramAddressSync(
+    [SUPER_RAFFLE_SEED],
+    program.programId
+  );
+  const [configPda, _configBump] = PublicKey.findProgramAddressSync(
+    [CONFIG_SEED],
+    program.programId
+  );
+
+  let currentRaffle: number;
+  let rafflePda: web3.PublicKey;
+  let randomnessPda: web3.PublicKey;
+  let buyer: web3.Keypair;
+
+  before(async () => {
+    const raffleTracker = await program.account.raffleTracker.fetch(trackerPda);
+    currentRaffle = raffleTracker.currentRaffle.toNumber();
+    [rafflePda] = PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(currentRaffle))],
+      program.programId
+    );
+    [randomnessPda] = PublicKey.findProgramAddressSync(
+      [RANDOMNESS_SEED, rafflePda.toBuffer()],
+      mockOracle.programId
+    );
+  });
+
+  it("requests randomness from the mock oracle", async function () {
+    await mockOracle.methods.request(rafflePda)
+      .accounts({ randomness: randomnessPda, payer: provider.wallet.publicKey })
+      .rpc();
+    try {
+      await program.methods.requestRandomness({ mock: {} })
+        .accounts({
+          raffle: rafflePda,
+          tracker: trackerPda,
+          authority: AUTH_KEYPAIR.publicKey,
+          config: configPda,
+          oracle: randomnessPda,
+        })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+    } catch (e) {
+      if (e.error?.errorCode?.code === "UnsupportedOracle") this.skip();
+      throw e;
+    }
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    expect(raffleStatus.randomnessRequest.oracle.toBase58()).to.equal(randomnessPda.toBase58());
+  });
+
+  it("cannot also commit randomness", async function () {
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    if (!raffleStatus.randomnessRequest) this.skip();
+    try {
+      await program.methods.commitRandomness([...randomBytes(32)])
+        .accounts({ raffle: rafflePda, tracker: trackerPda, config: configPda, authority: AUTH_KEYPAIR.publicKey })
+        .signers([AUTH_KEYPAIR])
+        .rpc();
+      expect.fail("Commit should fail after an oracle request");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("RandomnessAlreadyCommitted");
+    }
+  });
+
+  it("ends the raffle", async function () {
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    if (!raffleStatus.randomnessRequest) this.skip();
+    buyer = await createFundedWallet(provider);
+    await program.methods.buyTicket(1)
+      .accounts({
+        raffle: rafflePda,
+        ...await ticketAccounts(program, rafflePda, buyer.publicKey),
+        buyer: buyer.publicKey,
+        feeVault: VAULT_KEYPAIR.publicKey,
+        tracker: trackerPda,
+        superVault: superVaultPda,
+        config: configPda,
+      })
+      .signers([buyer])
+      .rpc();
+
+    const nftOwner = await createFundedWallet(provider);
+    const creators = Array(5)
+      .fill(null)
+      .map((_) => ({ address: Keypair.generate().publicKey, share: 20 }));
+    const { mint, ata } = await createAndFundATA({
+      provider: provider,
+      owner: nftOwner,
+      creators,
+      royaltyBps: 50,
+      programmable: true,
+      ruleSetAddr: new PublicKey('eBJLFYPxJmMGKuFwpDWkzxZeUrad92kZRC5BJLpzyT9'),
+      collection: COLLECTION_KEYPAIR,
+      collectionVerified: true,
+      skipNewCollection: true
+    });
+    const [newRafflePda] = PublicKey.findProgramAddressSync(
+      [RAFFLE_SEED, raffleNumberBuffer(BigInt(currentRaffle + 1))],
+      program.programId
+    );
+    const builder = await pNftTransferClient.buildTransferPNFT({
+      sourceAta: ata,
+      nftMint: mint,
+      destAta: await getAssociatedTokenAddress(mint, rafflePda, true),
+      owner: nftOwner.publicKey,
+      tracker: trackerPda,
+      raffle: rafflePda,
+      newRaffle: newRafflePda,
+      creators: creators.map(creator => creator.address),
+    })
+    await buildAndSendTx({
+      provider,
+      ixs: [await builder.instruction()],
+      extraSigners: [nftOwner],
+    });
+  });
+
+  it("picks the winner once the oracle fulfills", async function () {
+    const raffleStatus = await program.account.raffle.fetch(rafflePda);
+    if (!raffleStatus.randomnessRequest) this.skip();
+    try {
+      await program.methods.consumeRandomness(new anchor.BN(currentRaffle))
+        .accounts({
+          raffle: rafflePda,
+          ...await receiptAccounts(program, ticketReceiptPda(program.programId, BigInt(currentRaffle), 0)),
+          oracle: randomnessPda,
+          config: configPda,
+          cranker: provider.wallet.publicKey,
+        })
+        .rpc();
+      expect.fail("Consume should fail before the oracle fulfills");
+    } catch (e) {
+      expect(e.error?.errorCode?.code).to.equal("RandomnessNotFulfilled");
+    }
+
+    const randomness = randomBytes(32);
+    await mockOracle.methods.fulfill([...randomness])
+      .accounts({ randomness: randomnessPda })
+      .rpc();
+    const entropy = drawEntropy(randomness, raffleNumberBuffer(BigInt(currentRaffle)), 0);
+    await program.methods.consumeRandomness(new anchor.BN(currentRaffle))
+      .accounts({
+        raffle: rafflePda,
+        ...await winnerAccounts(program, rafflePda, entropy),
+        oracle: randomnessPda,
+        config: configPda,
+        cranker: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    const postRaffleStatus = await program.account.raffle.fetch(rafflePda);
+    expect(postRaffleStatus.prizes[0].winner.toBase58()).to.equal(buyer.publicKey.toBase58());
+  });
+});